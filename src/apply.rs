@@ -0,0 +1,334 @@
+use clap::ValueEnum;
+use toml_edit::{DocumentMut, Item, Table};
+
+use crate::{
+    error::{Error, Result},
+    lint::LintLevel,
+    manifest,
+};
+
+/// How [`apply`] treats the entries already in the lint tables.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Merge {
+    /// Replace the lint tables completely, including tables of tools the
+    /// generated configuration has none for
+    Replace,
+    /// Keep the existing entries, and add generated ones for the lints they
+    /// do not mention
+    Ours,
+    /// Prefer the generated entries, and keep existing ones for the lints
+    /// they do not mention
+    #[default]
+    Theirs,
+    /// Like `theirs`, but fail if existing entries set another level or
+    /// priority than the generated ones
+    Union,
+}
+
+/// Returns the table at `path`, creating implicit tables for missing keys.
+pub(crate) fn table_at<'a>(root: &'a mut Table, path: &[&str]) -> Result<&'a mut Table> {
+    let mut table = root;
+    for &key in path {
+        table = table
+            .entry(key)
+            .or_insert_with(|| {
+                let mut table = Table::new();
+                table.set_implicit(true);
+                Item::Table(table)
+            })
+            .as_table_mut()
+            .ok_or_else(|| Error::InvalidManifest(format!("`{key}` in manifest is not a table")))?;
+    }
+    Ok(table)
+}
+
+/// Comment above the entries of a lint table that the generated
+/// configuration does not mention.
+const MANUAL_ADDITIONS: &str = "# manual additions";
+
+/// Appends the entries of `existing` that `table` lacks, below a comment.
+fn keep_manual_additions(table: &mut Table, existing: &Table) {
+    let mut first = true;
+    for (key, item) in existing {
+        if table.contains_key(key) {
+            continue;
+        }
+        let Some((key, _)) = existing.get_key_value(key) else {
+            continue;
+        };
+        let mut key = key.clone();
+        if first {
+            key.leaf_decor_mut()
+                .set_prefix(format!("\n{MANUAL_ADDITIONS}\n"));
+            first = false;
+        }
+        table.insert_formatted(&key, item.clone());
+    }
+}
+
+/// The level and priority of an entry, to compare entries regardless of
+/// their formatting.
+fn entry(tool: &str, lint: &str, item: &Item) -> Result<(LintLevel, isize)> {
+    let mut value = item
+        .as_value()
+        .ok_or_else(|| Error::InvalidManifest(format!("lint {tool}.{lint} has an invalid value")))?
+        .clone();
+    value.decor_mut().clear();
+    let value: toml::Value = value.to_string().parse().map_err(Error::manifest)?;
+    let (level, priority) = manifest::lint_value(tool, lint, &value)?;
+    Ok((level, priority.unwrap_or_default()))
+}
+
+fn describe((level, priority): (LintLevel, isize)) -> String {
+    if priority == 0 {
+        level.as_str().to_owned()
+    } else {
+        format!("{} with priority {priority}", level.as_str())
+    }
+}
+
+/// Merges the entries of `existing` that the generated `table` also has:
+/// with `ours` the existing entries replace the generated ones, otherwise
+/// the entries that differ are returned as conflicts.
+fn merge_entries(
+    tool: &str,
+    table: &mut Table,
+    existing: &Table,
+    ours: bool,
+) -> Result<Vec<String>> {
+    let mut conflicts = Vec::new();
+    for (key, item) in existing {
+        let Some(generated) = table.get_mut(key) else {
+            continue;
+        };
+        let (existing_entry, generated_entry) =
+            (entry(tool, key, item)?, entry(tool, key, generated)?);
+        if existing_entry == generated_entry {
+            continue;
+        }
+        if ours {
+            *generated = item.clone();
+        } else {
+            conflicts.push(format!(
+                "{tool}.{key} is {} in the manifest, but {} in the generated configuration",
+                describe(existing_entry),
+                describe(generated_entry)
+            ));
+        }
+    }
+    Ok(conflicts)
+}
+
+/// Inserts the lint tables of `generated` into `manifest`. Everything else in
+/// the manifest is left untouched.
+///
+/// Existing entries are merged as `merge` says. Entries that the generated
+/// tables do not mention are kept below a comment, unless the lint tables
+/// are replaced.
+pub fn apply(manifest: &str, generated: &str, workspace: bool, merge: Merge) -> Result<String> {
+    let mut document: DocumentMut = manifest.parse().map_err(Error::manifest)?;
+    let generated: DocumentMut = generated
+        .parse()
+        .map_err(Error::parse("generated configuration"))?;
+
+    let path: &[&str] = if workspace {
+        &["workspace", "lints"]
+    } else {
+        &["lints"]
+    };
+
+    let mut generated_tables = generated.as_table();
+    for &key in path {
+        generated_tables = generated_tables
+            .get(key)
+            .and_then(Item::as_table)
+            .ok_or_else(|| {
+                Error::InvalidManifest(format!("generated configuration contains no `{key}` table"))
+            })?;
+    }
+
+    let target = table_at(document.as_table_mut(), path)?;
+
+    if !workspace && target.contains_key("workspace") {
+        return Err(Error::InvalidManifest(
+            "manifest inherits its lints from the workspace, apply the configuration to the workspace root instead"
+                .to_owned(),
+        ));
+    }
+
+    if merge == Merge::Replace {
+        target.retain(|tool, _| generated_tables.contains_key(tool));
+    }
+
+    let mut conflicts = Vec::new();
+
+    for (tool, item) in generated_tables {
+        let mut table = item
+            .as_table()
+            .ok_or_else(|| {
+                Error::InvalidManifest(format!("generated `{tool}` lints are not a table"))
+            })?
+            .clone();
+
+        if let Some(existing) = target.get(tool).and_then(Item::as_table) {
+            table.set_position(existing.position());
+            *table.decor_mut() = existing.decor().clone();
+            match merge {
+                Merge::Replace => {}
+                Merge::Ours | Merge::Union => {
+                    conflicts.extend(merge_entries(
+                        tool,
+                        &mut table,
+                        existing,
+                        merge == Merge::Ours,
+                    )?);
+                    keep_manual_additions(&mut table, existing);
+                }
+                Merge::Theirs => keep_manual_additions(&mut table, existing),
+            }
+        } else {
+            table.set_position(None);
+            table.decor_mut().set_prefix("\n");
+        }
+
+        target.insert(tool, Item::Table(table));
+    }
+
+    if !conflicts.is_empty() {
+        return Err(Error::MergeConflicts(conflicts));
+    }
+
+    Ok(document.to_string())
+}
+
+/// Like [`apply`], but for a workspace member with lints of its own. The
+/// member stops inheriting the workspace lints, as cargo does not allow
+/// combining `workspace = true` with other lint tables.
+pub fn apply_member(manifest: &str, generated: &str, merge: Merge) -> Result<String> {
+    let mut document: DocumentMut = manifest.parse().map_err(Error::manifest)?;
+
+    if let Some(lints) = document.get_mut("lints").and_then(Item::as_table_mut) {
+        lints.remove("workspace");
+        if lints.is_empty() {
+            lints.set_implicit(true);
+        }
+    }
+
+    apply(&document.to_string(), generated, false, merge)
+}
+
+/// Adds `lints.workspace = true` to a workspace member.
+///
+/// `None` if the member already has lint settings, inherited or its own, as
+/// cargo does not allow combining the two.
+pub fn inherit_workspace(manifest: &str) -> Result<Option<String>> {
+    let mut document: DocumentMut = manifest.parse().map_err(Error::manifest)?;
+
+    if document
+        .get("lints")
+        .and_then(Item::as_table_like)
+        .is_some_and(|lints| !lints.is_empty())
+    {
+        return Ok(None);
+    }
+
+    let mut lints = Table::new();
+    lints.insert("workspace", toml_edit::value(true));
+    if !document.as_table().is_empty() {
+        lints.decor_mut().set_prefix("\n");
+    }
+    document.insert("lints", Item::Table(lints));
+
+    Ok(Some(document.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"[package]
+name = "demo"
+
+[lints.clippy]
+pedantic = "warn"
+dbg_macro = "deny"
+
+[lints.rustdoc]
+broken_intra_doc_links = "deny"
+"#;
+
+    const GENERATED: &str = r#"[lints.clippy]
+pedantic = "deny"
+"#;
+
+    fn merged(merge: Merge) -> Result<String> {
+        apply(MANIFEST, GENERATED, false, merge)
+    }
+
+    #[test]
+    fn replace_drops_existing_entries_and_tools() {
+        assert_eq!(
+            merged(Merge::Replace).expect("the manifest is valid"),
+            "[package]\nname = \"demo\"\n\n[lints.clippy]\npedantic = \"deny\"\n"
+        );
+    }
+
+    #[test]
+    fn theirs_prefers_generated_entries() {
+        assert_eq!(
+            merged(Merge::Theirs).expect("the manifest is valid"),
+            r#"[package]
+name = "demo"
+
+[lints.clippy]
+pedantic = "deny"
+
+# manual additions
+dbg_macro = "deny"
+
+[lints.rustdoc]
+broken_intra_doc_links = "deny"
+"#
+        );
+    }
+
+    #[test]
+    fn ours_prefers_existing_entries() {
+        let merged = merged(Merge::Ours).expect("the manifest is valid");
+        assert!(
+            merged.contains(
+                "[lints.clippy]\npedantic = \"warn\"\n\n# manual additions\ndbg_macro = \"deny\"\n"
+            ),
+            "{merged}"
+        );
+    }
+
+    #[test]
+    fn union_fails_on_different_levels() {
+        let err = merged(Merge::Union).expect_err("the levels of pedantic differ");
+        assert_eq!(
+            err.to_string(),
+            "the lint tables conflict with the generated configuration:\n\
+             clippy.pedantic is warn in the manifest, but deny in the generated configuration"
+        );
+
+        apply(
+            MANIFEST,
+            "[lints.clippy]\npedantic = \"warn\"\n",
+            false,
+            Merge::Union,
+        )
+        .expect("the levels agree");
+    }
+
+    #[test]
+    fn members_inheriting_the_workspace_are_rejected() {
+        let manifest = "[package]\nname = \"demo\"\n\n[lints]\nworkspace = true\n";
+        apply(manifest, GENERATED, false, Merge::Theirs).expect_err("the lints are inherited");
+
+        let applied = apply_member(manifest, GENERATED, Merge::Theirs)
+            .expect("the member gets its own lints");
+        assert!(!applied.contains("workspace"), "{applied}");
+        assert!(applied.contains("pedantic = \"deny\""), "{applied}");
+    }
+}
@@ -0,0 +1,160 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    path::Path,
+    process::Command,
+};
+
+use serde::Deserialize;
+
+use crate::{
+    baseline::Finding,
+    error::{Error, Result},
+    lint::LintId,
+};
+
+/// A line of the JSON output of cargo. Only compiler messages are of
+/// interest, everything else fails to match and is skipped.
+#[derive(Deserialize)]
+struct CargoMessage {
+    reason: String,
+    message: Diagnostic,
+}
+
+#[derive(Deserialize)]
+struct Diagnostic {
+    level: String,
+    message: String,
+    code: Option<DiagnosticCode>,
+    #[serde(default)]
+    spans: Vec<Span>,
+    rendered: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Span {
+    file_name: String,
+    line_start: usize,
+    is_primary: bool,
+}
+
+#[derive(Deserialize)]
+struct DiagnosticCode {
+    code: String,
+}
+
+/// How often a lint fired.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LintCount {
+    pub warnings: usize,
+    pub errors: usize,
+}
+
+/// The lint diagnostics in the output of `cargo clippy
+/// --message-format=json`, with their lint. Compiler errors like `E0308` are
+/// left out, and diagnostics reported for several targets of a package are
+/// returned once.
+fn lint_diagnostics(output: &str) -> Vec<(String, Diagnostic)> {
+    let mut seen = HashSet::new();
+
+    output
+        .lines()
+        .filter_map(|line| serde_json::from_str::<CargoMessage>(line).ok())
+        .filter(|message| message.reason == "compiler-message")
+        .filter_map(|message| {
+            let code = message.message.code.as_ref()?.code.clone();
+            let is_error_code = code
+                .strip_prefix('E')
+                .is_some_and(|number| number.chars().all(|c| c.is_ascii_digit()));
+            (!is_error_code && seen.insert((code.clone(), message.message.rendered.clone())))
+                .then_some((code, message.message))
+        })
+        .collect()
+}
+
+/// Counts the findings per lint, e.g. `clippy::needless_return` or
+/// `dead_code`, in the output of `cargo clippy --message-format=json`.
+///
+/// Compiler errors like `E0308` are not counted, and findings reported for
+/// several targets of a package are counted once.
+pub fn count_findings(output: &str) -> BTreeMap<String, LintCount> {
+    let mut counts: BTreeMap<String, LintCount> = BTreeMap::new();
+
+    for (code, diagnostic) in lint_diagnostics(output) {
+        let count = counts.entry(code).or_default();
+        match diagnostic.level.as_str() {
+            "error" => count.errors = count.errors.saturating_add(1),
+            _ => count.warnings = count.warnings.saturating_add(1),
+        }
+    }
+
+    counts
+}
+
+/// The findings in the output of `cargo clippy --message-format=json`, see
+/// [`count_findings`]. Findings without a location, e.g. about the lint
+/// configuration, are left out.
+pub fn findings(output: &str) -> Vec<Finding> {
+    lint_diagnostics(output)
+        .into_iter()
+        .filter_map(|(lint, diagnostic)| {
+            let span = diagnostic.spans.into_iter().find(|span| span.is_primary)?;
+            Some(Finding {
+                lint,
+                file: span.file_name,
+                line: span.line_start,
+                message: diagnostic.message,
+            })
+        })
+        .collect()
+}
+
+/// Runs `cargo clippy` with JSON output on the package or workspace of
+/// `manifest`, with `flags` passed to clippy. Fails only if clippy reported
+/// nothing, as denied lints fail the build as well.
+pub fn run_clippy(manifest: &Path, flags: &[String]) -> Result<String> {
+    let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let output = Command::new(cargo)
+        .args([
+            "clippy",
+            "--workspace",
+            "--all-targets",
+            "--message-format=json",
+        ])
+        .arg("--manifest-path")
+        .arg(manifest)
+        .arg("--")
+        .args(flags)
+        .output()
+        .map_err(|source| Error::Run {
+            program: "cargo clippy",
+            source,
+        })?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    if !output.status.success() && !stdout.contains("\"reason\":\"compiler-message\"") {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::Command {
+            command: "cargo clippy",
+            message: stderr.lines().last().unwrap_or_default().trim().to_owned(),
+        });
+    }
+
+    Ok(stdout)
+}
+
+/// Runs clippy on the package or workspace of `manifest` with `lints` raised
+/// to `warn`, and returns the clippy lints that fired. All lints are capped at
+/// `warn`, so denied lints do not stop the build.
+pub fn fired_lints(manifest: &Path, lints: &[LintId<'_>]) -> Result<BTreeSet<String>> {
+    let mut flags = vec!["--cap-lints=warn".to_owned()];
+    flags.extend(
+        lints
+            .iter()
+            .map(|lint| format!("--warn=clippy::{}", lint.0)),
+    );
+
+    Ok(count_findings(&run_clippy(manifest, &flags)?)
+        .into_keys()
+        .filter_map(|code| code.strip_prefix("clippy::").map(str::to_owned))
+        .collect())
+}
@@ -0,0 +1,145 @@
+use std::collections::BTreeMap;
+#[cfg(feature = "native")]
+use std::{fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "native")]
+use crate::error::{Error, Result};
+
+/// The file name of the baseline, next to the workspace root manifest.
+pub const BASELINE_NAME: &str = "clippy-baseline.toml";
+
+#[cfg(feature = "native")]
+const HEADER: &str = "# Clippy findings accepted by clippy-lints, recreate it with `cargo clippy-lints baseline create`.\n\
+                      # Do not edit this file by hand.\n\n";
+
+/// A lint finding of clippy or rustc.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Finding {
+    /// The lint, e.g. `clippy::needless_return` or `dead_code`.
+    pub lint: String,
+    /// The file of the primary span, relative to the workspace root.
+    pub file: String,
+    pub line: usize,
+    pub message: String,
+}
+
+/// The findings of a lint in a file with the same message. Lines are not
+/// recorded, so that changes elsewhere in the file keep them recognized.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BaselineEntry {
+    pub lint: String,
+    pub file: String,
+    pub message: String,
+    pub count: usize,
+}
+
+/// The findings recorded in `clippy-baseline.toml`, which are not reported
+/// as new when checking against the baseline.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Baseline {
+    #[serde(default, rename = "finding")]
+    pub entries: Vec<BaselineEntry>,
+}
+
+type Key<'a> = (&'a str, &'a str, &'a str);
+
+impl Baseline {
+    pub fn from_findings(findings: &[Finding]) -> Self {
+        Self {
+            entries: count(findings)
+                .into_iter()
+                .map(|((lint, file, message), count)| BaselineEntry {
+                    lint: lint.to_owned(),
+                    file: file.to_owned(),
+                    message: message.to_owned(),
+                    count,
+                })
+                .collect(),
+        }
+    }
+
+    fn counts(&self) -> BTreeMap<Key<'_>, usize> {
+        self.entries
+            .iter()
+            .map(|entry| {
+                (
+                    (
+                        entry.lint.as_str(),
+                        entry.file.as_str(),
+                        entry.message.as_str(),
+                    ),
+                    entry.count,
+                )
+            })
+            .collect()
+    }
+
+    /// The findings that are not in the baseline. If a lint fires more often
+    /// in a file with the same message than recorded, the findings past the
+    /// recorded count are new.
+    pub fn new_findings<'a>(&self, findings: &'a [Finding]) -> Vec<&'a Finding> {
+        let mut remaining = self.counts();
+        findings
+            .iter()
+            .filter(|finding| {
+                let key = (
+                    finding.lint.as_str(),
+                    finding.file.as_str(),
+                    finding.message.as_str(),
+                );
+                match remaining.get_mut(&key) {
+                    Some(count) if *count > 0 => {
+                        *count = count.saturating_sub(1);
+                        false
+                    }
+                    _ => true,
+                }
+            })
+            .collect()
+    }
+
+    /// How many of the recorded findings are gone.
+    pub fn fixed(&self, findings: &[Finding]) -> usize {
+        let current = count(findings);
+        self.counts()
+            .into_iter()
+            .map(|(key, recorded)| recorded.saturating_sub(current.get(&key).copied().unwrap_or(0)))
+            .sum()
+    }
+
+    /// Reads the baseline at `path`, `None` if it does not exist.
+    #[cfg(feature = "native")]
+    pub fn read(path: &Path) -> Result<Option<Self>> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(Error::io("read baseline", path)(err)),
+        };
+
+        toml::from_str(&content)
+            .map(Some)
+            .map_err(Error::parse(format!("baseline {}", path.display())))
+    }
+
+    #[cfg(feature = "native")]
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let content = format!(
+            "{HEADER}{}",
+            toml::to_string(self).map_err(Error::serialize("baseline"))?
+        );
+        fs::write(path, content).map_err(Error::io("write baseline", path))
+    }
+}
+
+fn count(findings: &[Finding]) -> BTreeMap<Key<'_>, usize> {
+    let mut counts: BTreeMap<Key<'_>, usize> = BTreeMap::new();
+    for finding in findings {
+        let count = counts
+            .entry((&finding.lint, &finding.file, &finding.message))
+            .or_default();
+        *count = count.saturating_add(1);
+    }
+    counts
+}
@@ -0,0 +1,183 @@
+use crate::{
+    config::Config,
+    error::{Error, Result},
+    lint::{AllLints, LintGroup, LintId, LintLevel},
+    profile::{
+        ExceptionsDefinition, ExhaustiveDefinition, GroupDefinition, OverrideDefinition,
+        ProfileDefinition, ToolLintDefinition,
+    },
+    version::RustVersion,
+};
+
+/// Puts together a configuration in code instead of a config file. The lints
+/// are only checked against the lint data by [`ConfigBuilder::build`].
+///
+/// ```no_run
+/// use clippy_lints::{AllLints, ConfigBuilder, LintGroup, LintLevel, Response, TomlOptions};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let json = std::fs::read_to_string("lints.json")?;
+/// let response = Response::parse(&json)?;
+/// let all_lints = AllLints::from_response(&response);
+///
+/// let mut builder = ConfigBuilder::new()
+///     .group(LintGroup::Pedantic, LintLevel::Warn)
+///     .allow("too_many_lines")
+///     .exhaustive(LintGroup::Restriction, LintLevel::Allow, LintLevel::Warn, &["dbg_macro"]);
+///
+/// println!("{}", builder.build(&all_lints, None)?.to_toml(TomlOptions::default()));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct ConfigBuilder {
+    profile: ProfileDefinition,
+    /// Lints to allow, sorted into the overrides of their groups on build.
+    allowed: Vec<String>,
+}
+
+impl ConfigBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts from an existing profile, e.g. a built-in one.
+    pub fn from_profile(profile: ProfileDefinition) -> Self {
+        Self {
+            profile,
+            allowed: Vec::new(),
+        }
+    }
+
+    /// Sets the level of a whole group, replacing an earlier level.
+    #[must_use]
+    pub fn group(mut self, group: LintGroup, level: LintLevel) -> Self {
+        self.profile
+            .groups
+            .retain(|existing| existing.group != group);
+        self.profile.groups.push(GroupDefinition {
+            group,
+            level,
+            priority: None,
+        });
+        self
+    }
+
+    /// Allows a clippy lint despite the level of its group.
+    #[must_use]
+    pub fn allow(mut self, lint: &str) -> Self {
+        self.allowed.push(lint.to_owned());
+        self
+    }
+
+    /// Sets the level of a single clippy lint, overriding all other settings.
+    #[must_use]
+    pub fn lint(mut self, lint: &str, level: LintLevel) -> Self {
+        set_tool_lint(&mut self.profile.clippy, lint, level);
+        self
+    }
+
+    /// Sets the level of a rustc lint.
+    #[must_use]
+    pub fn rust(mut self, lint: &str, level: LintLevel) -> Self {
+        set_tool_lint(&mut self.profile.rust, lint, level);
+        self
+    }
+
+    /// Sets the level of a rustdoc lint.
+    #[must_use]
+    pub fn rustdoc(mut self, lint: &str, level: LintLevel) -> Self {
+        set_tool_lint(&mut self.profile.rustdoc, lint, level);
+        self
+    }
+
+    /// Lists every lint of `group` individually: `exceptions` at
+    /// `exceptions_level`, all others at `level`.
+    #[must_use]
+    pub fn exhaustive(
+        mut self,
+        group: LintGroup,
+        level: LintLevel,
+        exceptions_level: LintLevel,
+        exceptions: &[&str],
+    ) -> Self {
+        self.profile
+            .exhaustive
+            .retain(|existing| existing.group != group);
+        self.profile.exhaustive.push(ExhaustiveDefinition {
+            group,
+            level,
+            comment: None,
+            priority: None,
+            exceptions: ExceptionsDefinition {
+                level: exceptions_level,
+                lints: exceptions.iter().map(|&lint| lint.to_owned()).collect(),
+                comment: None,
+                priority: None,
+                expires: None,
+            },
+            defaults: Vec::new(),
+            compact: false,
+        });
+        self
+    }
+
+    /// Checks the lints against the lint data and resolves the
+    /// configuration. Fails for unknown lints and for exceptions of
+    /// exhaustive groups that are part of another group.
+    pub fn build<'a>(
+        &'a mut self,
+        all_lints: &'a AllLints<'a>,
+        msrv: Option<RustVersion>,
+    ) -> Result<Config<'a>> {
+        let unknown = self.profile.unknown_lints(all_lints);
+        if !unknown.is_empty() {
+            return Err(Error::UnknownLints {
+                lints: unknown.into_iter().map(|lint| lint.name).collect(),
+            });
+        }
+
+        for lint in self.allowed.drain(..) {
+            let group = all_lints
+                .get(LintId(&lint))
+                .ok_or_else(|| Error::UnknownLint {
+                    lint: lint.clone(),
+                    group: None,
+                })?
+                .group
+                .clone();
+            match self
+                .profile
+                .overrides
+                .iter_mut()
+                .find(|existing| existing.group == group)
+            {
+                Some(existing) => {
+                    if !existing.allow.contains(&lint) {
+                        existing.allow.push(lint);
+                    }
+                }
+                None => self.profile.overrides.push(OverrideDefinition {
+                    group,
+                    comment: None,
+                    allow: vec![lint],
+                    priority: None,
+                    expires: None,
+                }),
+            }
+        }
+
+        self.profile.to_config(all_lints, msrv)
+    }
+}
+
+fn set_tool_lint(lints: &mut Vec<ToolLintDefinition>, lint: &str, level: LintLevel) {
+    lints.retain(|existing| existing.lint != lint);
+    lints.push(ToolLintDefinition {
+        lint: lint.to_owned(),
+        level,
+        priority: None,
+        reason: None,
+        expires: None,
+    });
+}
@@ -0,0 +1,122 @@
+use std::{
+    fs, io,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+
+use crate::error::{Error, Result};
+
+const CACHE_DIR_NAME: &str = "clippy-lints";
+
+pub struct Cache {
+    path: PathBuf,
+}
+
+impl Cache {
+    /// Returns `None` if the platform has no cache directory.
+    pub fn new(name: &str) -> Option<Self> {
+        dirs::cache_dir().map(|dir| Self {
+            path: dir.join(CACHE_DIR_NAME).join(format!("{name}.json")),
+        })
+    }
+
+    /// Returns the cached content if it exists and is younger than `ttl`.
+    pub fn read_fresh(&self, ttl: Duration) -> Result<Option<String>> {
+        let metadata = match fs::metadata(&self.path) {
+            Ok(metadata) => metadata,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                tracing::debug!("cache miss: {} does not exist", self.path.display());
+                return Ok(None);
+            }
+            Err(err) => return Err(Error::io("read cache file", &self.path)(err)),
+        };
+
+        let age = SystemTime::now()
+            .duration_since(
+                metadata
+                    .modified()
+                    .map_err(Error::io("read cache file", &self.path))?,
+            )
+            .unwrap_or_default();
+
+        if age >= ttl {
+            tracing::debug!("cache miss: {} is stale", self.path.display());
+            return Ok(None);
+        }
+
+        tracing::debug!("cache hit: {}", self.path.display());
+        fs::read_to_string(&self.path)
+            .map(Some)
+            .map_err(Error::io("read cache file", &self.path))
+    }
+
+    /// Marks the cached content as fresh again.
+    pub fn touch(&self) -> Result<()> {
+        fs::File::options()
+            .append(true)
+            .open(&self.path)
+            .and_then(|file| file.set_modified(SystemTime::now()))
+            .map_err(Error::io("update cache file", &self.path))
+    }
+
+    pub fn write(&self, content: &str) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(Error::io("create cache directory", dir))?;
+        }
+        fs::write(&self.path, content).map_err(Error::io("write cache file", &self.path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TTL: Duration = Duration::from_hours(1);
+
+    fn cache(name: &str) -> Cache {
+        Cache {
+            path: std::env::temp_dir()
+                .join(format!("{CACHE_DIR_NAME}-{}", std::process::id()))
+                .join(format!("{name}.json")),
+        }
+    }
+
+    fn age(cache: &Cache, age: Duration) {
+        fs::File::options()
+            .append(true)
+            .open(&cache.path)
+            .and_then(|file| {
+                file.set_modified(
+                    SystemTime::now()
+                        .checked_sub(age)
+                        .expect("the age is after the epoch"),
+                )
+            })
+            .expect("the cache file can be backdated");
+    }
+
+    #[test]
+    fn missing_file_is_a_miss() {
+        assert_eq!(cache("missing").read_fresh(TTL).expect("no error"), None);
+    }
+
+    #[test]
+    fn content_expires_after_the_ttl() {
+        let cache = cache("expires");
+        cache.write("[]").expect("the cache is writable");
+        assert_eq!(
+            cache.read_fresh(TTL).expect("no error").as_deref(),
+            Some("[]")
+        );
+
+        age(&cache, TTL);
+        assert_eq!(cache.read_fresh(TTL).expect("no error"), None);
+
+        cache.touch().expect("the cache is writable");
+        assert_eq!(
+            cache.read_fresh(TTL).expect("no error").as_deref(),
+            Some("[]")
+        );
+        fs::remove_file(&cache.path).expect("the cache file can be removed");
+    }
+}
@@ -0,0 +1,228 @@
+#[cfg(feature = "native")]
+use std::time::Duration;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write as _,
+    iter,
+};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "native")]
+use crate::{
+    cache::Cache,
+    error::{Error, Result},
+    source::Release,
+};
+use crate::{lint::AllLints, lint::LintGroup, profile::RenamedLint, renames::RenamedLints};
+
+/// A lint as recorded between runs.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct KnownLint {
+    pub id: String,
+    pub group: LintGroup,
+}
+
+/// The set of lints seen in a run, persisted to detect changes in later runs.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct KnownLints(pub BTreeSet<KnownLint>);
+
+impl KnownLints {
+    pub fn from_all_lints(all_lints: &AllLints<'_>) -> Self {
+        Self(
+            all_lints
+                .iter()
+                .map(|lint| KnownLint {
+                    id: lint.id.0.to_owned(),
+                    group: lint.group.clone(),
+                })
+                .collect(),
+        )
+    }
+
+    #[cfg(feature = "native")]
+    fn cache(release: Release) -> Option<Cache> {
+        Cache::new(&format!("known-lints-{}", release.name()))
+    }
+
+    /// Loads the lint set recorded by [`KnownLints::store`] for `release`,
+    /// if any.
+    #[cfg(feature = "native")]
+    pub fn load_previous(release: Release) -> Result<Option<Self>> {
+        Self::cache(release)
+            .map(|cache| cache.read_fresh(Duration::MAX))
+            .transpose()?
+            .flatten()
+            .map(|content| {
+                serde_json::from_str(&content).map_err(Error::parse("recorded lint set"))
+            })
+            .transpose()
+    }
+
+    #[cfg(feature = "native")]
+    pub fn store(&self, release: Release) -> Result<()> {
+        match Self::cache(release) {
+            Some(cache) => cache.write(
+                &serde_json::to_string(self).map_err(Error::serialize("recorded lint set"))?,
+            ),
+            None => Ok(()),
+        }
+    }
+}
+
+/// How [`LintChanges::report`] renders the changes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    /// Indented plain text
+    #[default]
+    Text,
+    /// Markdown, e.g. for a pull request description
+    Markdown,
+}
+
+/// A lint that is part of a different group than before.
+#[derive(Debug)]
+pub struct RegroupedLint {
+    pub id: String,
+    pub from: LintGroup,
+    pub to: LintGroup,
+}
+
+/// Lints added, removed, renamed and moved to another group between two lint
+/// sets. Added and removed lints are listed by group.
+#[derive(Debug, Default)]
+pub struct LintChanges {
+    pub added: BTreeMap<LintGroup, Vec<String>>,
+    pub removed: BTreeMap<LintGroup, Vec<String>>,
+    /// New names are without the `clippy::` prefix, except for lints that
+    /// moved to rustc.
+    pub renamed: Vec<RenamedLint>,
+    pub regrouped: Vec<RegroupedLint>,
+}
+
+impl LintChanges {
+    /// Compares two lint sets. A removed lint that `renames` maps to an added
+    /// one counts as renamed.
+    pub fn between(previous: &KnownLints, current: &KnownLints, renames: &RenamedLints) -> Self {
+        let mut added: BTreeMap<&str, LintGroup> = current
+            .0
+            .difference(&previous.0)
+            .map(|lint| (lint.id.as_str(), lint.group.clone()))
+            .collect();
+        let mut changes = Self::default();
+
+        for lint in previous.0.difference(&current.0) {
+            if let Some(group) = added.remove(lint.id.as_str()) {
+                changes.regrouped.push(RegroupedLint {
+                    id: lint.id.clone(),
+                    from: lint.group.clone(),
+                    to: group,
+                });
+                continue;
+            }
+
+            let new = renames.get(&lint.id);
+            let new_id = new.map(|new| new.strip_prefix("clippy::").unwrap_or(new));
+            match (new, new_id.and_then(|new_id| added.remove(new_id))) {
+                (Some(new), Some(_)) => changes.renamed.push(RenamedLint {
+                    old: lint.id.clone(),
+                    new: new_id.unwrap_or(new).to_owned(),
+                }),
+                _ => changes
+                    .removed
+                    .entry(lint.group.clone())
+                    .or_default()
+                    .push(lint.id.clone()),
+            }
+        }
+
+        for (id, group) in added {
+            changes.added.entry(group).or_default().push(id.to_owned());
+        }
+
+        changes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.renamed.is_empty()
+            && self.regrouped.is_empty()
+    }
+
+    /// Renders the changes as a human-readable report.
+    pub fn report(&self, format: ReportFormat) -> String {
+        let mut sections: Vec<(&str, Vec<String>)> = Vec::new();
+
+        for (title, changes) in [("added", &self.added), ("removed", &self.removed)] {
+            let lines = changes
+                .iter()
+                .flat_map(|(group, lints)| match format {
+                    ReportFormat::Text => iter::once(format!("  {group}:"))
+                        .chain(lints.iter().map(|lint| format!("    {lint}")))
+                        .collect::<Vec<_>>(),
+                    ReportFormat::Markdown => lints
+                        .iter()
+                        .map(|lint| format!("- `{lint}` ({group})"))
+                        .collect(),
+                })
+                .collect();
+            sections.push((title, lines));
+        }
+
+        sections.push((
+            "renamed",
+            self.renamed
+                .iter()
+                .map(|renamed| match format {
+                    ReportFormat::Text => format!("  {} -> {}", renamed.old, renamed.new),
+                    ReportFormat::Markdown => format!("- `{}` → `{}`", renamed.old, renamed.new),
+                })
+                .collect(),
+        ));
+        sections.push((
+            "regrouped",
+            self.regrouped
+                .iter()
+                .map(|regrouped| match format {
+                    ReportFormat::Text => {
+                        format!("  {}: {} -> {}", regrouped.id, regrouped.from, regrouped.to)
+                    }
+                    ReportFormat::Markdown => format!(
+                        "- `{}`: {} → {}",
+                        regrouped.id, regrouped.from, regrouped.to
+                    ),
+                })
+                .collect(),
+        ));
+
+        let mut output = String::new();
+        for (title, lines) in sections {
+            if lines.is_empty() {
+                continue;
+            }
+            if !output.is_empty() {
+                output.push('\n');
+            }
+            let heading = match format {
+                ReportFormat::Text => format!("{title} lints:"),
+                ReportFormat::Markdown => format!("### {} lints\n", capitalize(title)),
+            };
+            writeln!(output, "{heading}").expect("writing to string succeeds");
+            for line in lines {
+                writeln!(output, "{line}").expect("writing to string succeeds");
+            }
+        }
+
+        output.trim_end().to_owned()
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{Error, Result},
+    version::RustVersion,
+};
+
+/// Values for clippy's own configuration file `clippy.toml`, e.g.
+/// `too-many-arguments-threshold` or `disallowed-methods`. The keys are not
+/// validated here, clippy reports unknown ones itself.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct ClippyConfig(pub toml::Table);
+
+impl ClippyConfig {
+    /// Renders the values as `clippy.toml`. `msrv` takes precedence over an
+    /// `msrv` value of the profile.
+    pub fn to_toml(&self, msrv: Option<RustVersion>) -> Result<String> {
+        let mut table = self.0.clone();
+        if let Some(msrv) = msrv {
+            table.insert("msrv".to_owned(), msrv.to_string().into());
+        }
+        toml::to_string(&table).map_err(Error::serialize("clippy.toml"))
+    }
+}
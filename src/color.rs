@@ -0,0 +1,84 @@
+use std::{
+    env,
+    io::{self, IsTerminal},
+    sync::OnceLock,
+};
+
+use clap::ValueEnum;
+use clippy_lints::LintLevel;
+use tracing::Level;
+
+const RED: &str = "\x1b[31m";
+const YELLOW: &str = "\x1b[33m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// When to color the output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    /// If the output goes to a terminal and `NO_COLOR` is not set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+static CHOICE: OnceLock<ColorChoice> = OnceLock::new();
+
+/// Sets the choice for the whole program. Only the first call has an effect.
+pub fn init(choice: ColorChoice) {
+    CHOICE.get_or_init(|| choice);
+}
+
+/// Whether output to `stream` is colored.
+pub fn enabled(stream: &impl IsTerminal) -> bool {
+    match CHOICE.get().copied().unwrap_or_default() {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            env::var_os("NO_COLOR").is_none_or(|value| value.is_empty()) && stream.is_terminal()
+        }
+    }
+}
+
+fn paint(text: &str, style: &str, color: bool) -> String {
+    if color && !style.is_empty() {
+        format!("{style}{text}{RESET}")
+    } else {
+        text.to_owned()
+    }
+}
+
+/// `text` in the color of `level`: deny red, warn yellow and allow dimmed.
+pub fn level(text: &str, level: Option<LintLevel>, color: bool) -> String {
+    let style = match level {
+        Some(LintLevel::Deny) => RED,
+        Some(LintLevel::Warn) => YELLOW,
+        Some(LintLevel::Allow) => DIM,
+        Some(LintLevel::None) | None => "",
+    };
+    paint(text, style, color)
+}
+
+/// `text` highlighted as a failure, for stderr.
+pub fn error(text: &str) -> String {
+    paint(text, &format!("{BOLD}{RED}"), enabled(&io::stderr()))
+}
+
+/// The label of log messages of `level`, for stderr.
+pub fn label(level: Level) -> String {
+    let (text, style) = match level {
+        Level::ERROR => ("error:", format!("{BOLD}{RED}")),
+        Level::WARN => ("warning:", format!("{BOLD}{YELLOW}")),
+        Level::INFO => ("info:", BOLD.to_owned()),
+        Level::DEBUG => ("debug:", DIM.to_owned()),
+        Level::TRACE => ("trace:", DIM.to_owned()),
+    };
+    paint(text, &style, enabled(&io::stderr()))
+}
+
+/// Prints a warning to stderr, unless `--quiet` is given.
+pub fn warn(message: &str) {
+    tracing::warn!("{message}");
+}
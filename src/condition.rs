@@ -0,0 +1,422 @@
+use std::{fmt, str::FromStr};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use toml::Value;
+
+use crate::{
+    error::{Error, Result},
+    provider::Project,
+};
+
+/// A test of the project, e.g. whether it has a feature.
+#[derive(Clone, Debug)]
+enum Test {
+    /// The manifest declares the feature.
+    Feature(String),
+    /// The package depends on the crate, in `[dependencies]`.
+    Dependency(String),
+    /// The package name matches the pattern.
+    Name(String),
+    /// The workspace member matches the pattern.
+    Member(String),
+    /// The value at the dotted key of `package.metadata` equals the value.
+    Metadata(String, String),
+}
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Test(Test),
+    Not(Box<Self>),
+    And(Box<Self>, Box<Self>),
+    Or(Box<Self>, Box<Self>),
+}
+
+/// A condition on the project, evaluated during generation.
+///
+/// Conditions combine tests with `&&`, `||`, `!` and parentheses. The tests
+/// are `feature("cli")`, `dependency("tokio")`, `name("*-sys")`,
+/// `member("xtask")` and `metadata("team", "infra")`. `*` in the patterns
+/// of `name` and `member` matches any characters, the key of `metadata` can
+/// be dotted.
+#[derive(Clone, Debug)]
+pub struct Condition {
+    source: String,
+    expr: Expr,
+}
+
+impl Condition {
+    pub fn matches(&self, project: &Project) -> bool {
+        self.expr.matches(project)
+    }
+}
+
+impl Expr {
+    fn matches(&self, project: &Project) -> bool {
+        match *self {
+            Self::Test(ref test) => test.matches(project),
+            Self::Not(ref expr) => !expr.matches(project),
+            Self::And(ref left, ref right) => left.matches(project) && right.matches(project),
+            Self::Or(ref left, ref right) => left.matches(project) || right.matches(project),
+        }
+    }
+}
+
+impl Test {
+    fn matches(&self, project: &Project) -> bool {
+        match *self {
+            Self::Feature(ref feature) => project.features.contains(feature),
+            Self::Dependency(ref dependency) => project.dependencies.contains(dependency),
+            Self::Name(ref pattern) => project
+                .name
+                .as_deref()
+                .is_some_and(|name| glob_match(pattern, name)),
+            Self::Member(ref pattern) => project
+                .member
+                .as_deref()
+                .is_some_and(|member| glob_match(pattern, member)),
+            Self::Metadata(ref key, ref expected) => {
+                let mut parts = key.split('.');
+                let mut value = parts.next().and_then(|part| project.metadata.get(part));
+                for part in parts {
+                    value = value.and_then(|value| value.get(part));
+                }
+                value.is_some_and(|value| match *value {
+                    Value::String(ref value) => value == expected,
+                    ref value => value.to_string() == *expected,
+                })
+            }
+        }
+    }
+}
+
+/// Whether `text` matches `pattern`, where `*` matches any characters.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let Some((prefix, rest)) = pattern.split_once('*') else {
+        return pattern == text;
+    };
+    let Some(mut text) = text.strip_prefix(prefix) else {
+        return false;
+    };
+
+    let mut parts: Vec<&str> = rest.split('*').collect();
+    let last = parts.pop().unwrap_or_default();
+    for part in parts {
+        match text.find(part) {
+            Some(index) => {
+                text = text
+                    .get(index.saturating_add(part.len())..)
+                    .unwrap_or_default();
+            }
+            None => return false,
+        }
+    }
+    text.ends_with(last)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+    Comma,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            ',' => tokens.push(Token::Comma),
+            '!' => tokens.push(Token::Not),
+            '&' if chars.next_if_eq(&'&').is_some() => tokens.push(Token::And),
+            '|' if chars.next_if_eq(&'|').is_some() => tokens.push(Token::Or),
+            '"' => {
+                let mut string = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => string.extend(chars.next()),
+                        Some(c) => string.push(c),
+                        None => return Err("unterminated string".to_owned()),
+                    }
+                }
+                tokens.push(Token::Str(string));
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut ident = String::from(c);
+                while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+                    ident.push(c);
+                }
+                tokens.push(Token::Ident(ident));
+            }
+            c => return Err(format!("unexpected `{c}`")),
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// A recursive descent parser over the tokens of a condition.
+struct Parser {
+    tokens: std::vec::IntoIter<Token>,
+    peeked: Option<Token>,
+}
+
+impl Parser {
+    fn peek(&mut self) -> Option<&Token> {
+        if self.peeked.is_none() {
+            self.peeked = self.tokens.next();
+        }
+        self.peeked.as_ref()
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        self.peeked.take().or_else(|| self.tokens.next())
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        let found = self.peek() == Some(token);
+        if found {
+            self.peeked = None;
+        }
+        found
+    }
+
+    fn expect(&mut self, token: &Token, what: &str) -> Result<(), String> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(format!("expected {what}"))
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.and()?;
+        while self.eat(&Token::Or) {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.unary()?;
+        while self.eat(&Token::And) {
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat(&Token::Not) {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.eat(&Token::Open) {
+            let expr = self.or()?;
+            self.expect(&Token::Close, "`)`")?;
+            return Ok(expr);
+        }
+        self.test().map(Expr::Test)
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        match self.next() {
+            Some(Token::Str(string)) => Ok(string),
+            _ => Err("expected a string".to_owned()),
+        }
+    }
+
+    fn test(&mut self) -> Result<Test, String> {
+        let Some(Token::Ident(name)) = self.next() else {
+            return Err("expected a test like `feature(\"cli\")`".to_owned());
+        };
+        self.expect(&Token::Open, &format!("`(` after `{name}`"))?;
+        let test = match name.as_str() {
+            "feature" => Test::Feature(self.string()?),
+            "dependency" => Test::Dependency(self.string()?),
+            "name" => Test::Name(self.string()?),
+            "member" => Test::Member(self.string()?),
+            "metadata" => {
+                let key = self.string()?;
+                self.expect(&Token::Comma, "`,` between the key and the value")?;
+                Test::Metadata(key, self.string()?)
+            }
+            _ => return Err(format!("unknown test `{name}`")),
+        };
+        self.expect(&Token::Close, "`)`")?;
+        Ok(test)
+    }
+}
+
+impl FromStr for Condition {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = |problem| Error::InvalidCondition {
+            condition: s.to_owned(),
+            problem,
+        };
+
+        let mut parser = Parser {
+            tokens: tokenize(s).map_err(invalid)?.into_iter(),
+            peeked: None,
+        };
+        let expr = parser.or().map_err(invalid)?;
+        if parser.next().is_some() {
+            return Err(invalid("unexpected text after the condition".to_owned()));
+        }
+
+        Ok(Self {
+            source: s.to_owned(),
+            expr,
+        })
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl Serialize for Condition {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.source)
+    }
+}
+
+impl<'de> Deserialize<'de> for Condition {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(features: &[&str]) -> Project {
+        Project {
+            name: Some("openssl-sys".to_owned()),
+            features: features.iter().map(|&feature| feature.to_owned()).collect(),
+            ..Project::default()
+        }
+    }
+
+    fn matches(condition: &str, features: &[&str]) -> bool {
+        condition
+            .parse::<Condition>()
+            .expect("the condition is valid")
+            .matches(&project(features))
+    }
+
+    fn problem(condition: &str) -> String {
+        condition
+            .parse::<Condition>()
+            .expect_err("the condition is invalid")
+            .to_string()
+    }
+
+    #[test]
+    fn not_binds_tighter_than_and_and_and_tighter_than_or() {
+        let condition = r#"!feature("a") && feature("b") || feature("c")"#;
+        assert!(!matches(condition, &[]));
+        assert!(matches(condition, &["b"]));
+        assert!(matches(condition, &["a", "c"]));
+        assert!(!matches(condition, &["a", "b"]));
+
+        let condition = r#"feature("a") || feature("b") && feature("c")"#;
+        assert!(matches(condition, &["a"]));
+        assert!(!matches(condition, &["b"]));
+    }
+
+    #[test]
+    fn parentheses_group() {
+        let condition = r#"(feature("a") || feature("b")) && feature("c")"#;
+        assert!(!matches(condition, &["a"]));
+        assert!(matches(condition, &["a", "c"]));
+        assert!(matches(r#"!(feature("a") && feature("b"))"#, &[]));
+    }
+
+    #[test]
+    fn strings_unescape_quotes() {
+        assert_eq!(
+            tokenize(r#"name("a\"b\\c")"#),
+            Ok(vec![
+                Token::Ident("name".to_owned()),
+                Token::Open,
+                Token::Str(r#"a"b\c"#.to_owned()),
+                Token::Close,
+            ])
+        );
+    }
+
+    #[test]
+    fn unterminated_strings_fail() {
+        assert_eq!(
+            problem(r#"name("abc)"#),
+            r#"invalid condition `name("abc)`: unterminated string"#
+        );
+        assert_eq!(
+            problem(r#"name("abc\")"#),
+            r#"invalid condition `name("abc\")`: unterminated string"#
+        );
+    }
+
+    #[test]
+    fn trailing_input_fails() {
+        assert_eq!(
+            problem(r#"feature("a") feature("b")"#),
+            r#"invalid condition `feature("a") feature("b")`: unexpected text after the condition"#
+        );
+        assert_eq!(
+            problem(r#"feature("a"))"#),
+            r#"invalid condition `feature("a"))`: unexpected text after the condition"#
+        );
+        assert_eq!(
+            problem(r#"(feature("a")"#),
+            r#"invalid condition `(feature("a")`: expected `)`"#
+        );
+    }
+
+    #[test]
+    fn name_patterns() {
+        assert!(matches(r#"name("*-sys")"#, &[]));
+        assert!(!matches(r#"name("*-cli")"#, &[]));
+    }
+
+    #[test]
+    fn glob_suffix() {
+        assert!(glob_match("*-sys", "openssl-sys"));
+        assert!(glob_match("*-sys", "-sys"));
+        assert!(!glob_match("*-sys", "sys-openssl"));
+        assert!(!glob_match("*-sys", "openssl-sys2"));
+    }
+
+    #[test]
+    fn glob_several_wildcards() {
+        assert!(glob_match("a*b*c", "abc"));
+        assert!(glob_match("a*b*c", "aXbYc"));
+        assert!(glob_match("a*b*c", "abcbc"));
+        assert!(!glob_match("a*b*c", "acb"));
+        assert!(!glob_match("a*b*c", "Xabc"));
+        assert!(!glob_match("a*b*c", "abcX"));
+    }
+
+    #[test]
+    fn glob_star_matches_everything() {
+        assert!(glob_match("*", ""));
+        assert!(glob_match("*", "anything"));
+        assert!(glob_match("exact", "exact"));
+        assert!(!glob_match("exact", "exactly"));
+    }
+}
@@ -0,0 +1,1091 @@
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BTreeMap,
+    fmt::Write as _,
+};
+
+use clap::ValueEnum;
+use serde::{ser::Serializer, Serialize};
+
+use crate::{
+    date::Date,
+    error::{Error, Result},
+    lint::{AllLints, Lint, LintGroup, LintId, LintLevel, LintList},
+    version::RustVersion,
+};
+
+/// The `priority` of an entry in a lints table.
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(untagged)]
+pub enum PrioritySetting {
+    Explicit(isize),
+    Unspecified,
+}
+
+impl From<Option<isize>> for PrioritySetting {
+    fn from(value: Option<isize>) -> Self {
+        match value {
+            Some(i) => Self::Explicit(i),
+            None => Self::Unspecified,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SingleLintConfig<'a> {
+    pub lint: LintId<'a>,
+    pub priority: PrioritySetting,
+    pub level: LintLevel,
+    /// A short description of the lint, see [`Lint::summary`]. Only set for
+    /// the exceptions of exhaustively listed groups.
+    #[serde(skip)]
+    pub description: Option<&'a str>,
+    /// Why the profile sets the lint to this level, written as a comment
+    /// above the entry.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<&'a str>,
+    /// When the profile wants the level reviewed, written as a comment
+    /// above the entry.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires: Option<Date>,
+    /// The Rust version that introduced the lint, see [`Lint::version`].
+    #[serde(skip)]
+    pub version: Option<RustVersion>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GroupConfig {
+    pub group: LintGroup,
+    pub priority: PrioritySetting,
+    pub level: LintLevel,
+}
+
+/// A single entry in a lints table, either for a lint or a whole group.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum Setting<'a> {
+    Single(SingleLintConfig<'a>),
+    Group(GroupConfig),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ExhaustiveGroupClassification {
+    Default,
+    Exception,
+}
+
+/// A group split into one setting per lint, see
+/// [`Setting::split_group_exhaustive`].
+#[derive(Debug)]
+pub struct ExhaustiveGroup<'a> {
+    pub defaults: Vec<Setting<'a>>,
+    pub exceptions: Vec<Setting<'a>>,
+}
+
+pub struct Exceptions<'a> {
+    pub level: LintLevel,
+    pub priority: PrioritySetting,
+    pub lints: LintList<'a>,
+}
+
+/// Fails if `lint` is unknown or not part of `group`.
+fn check_group(all_lints: &AllLints, lint: LintId<'_>, group: &LintGroup) -> Result<()> {
+    match all_lints.get(lint) {
+        None => Err(Error::UnknownLint {
+            lint: lint.0.to_owned(),
+            group: Some(group.clone()),
+        }),
+        Some(known) if known.group != *group => Err(Error::WrongGroup {
+            lint: lint.0.to_owned(),
+            group: group.clone(),
+        }),
+        Some(_) => Ok(()),
+    }
+}
+
+impl<'a> Setting<'a> {
+    pub fn group(group: LintGroup, level: LintLevel, priority: impl Into<PrioritySetting>) -> Self {
+        Self::Group(GroupConfig {
+            group,
+            priority: priority.into(),
+            level,
+        })
+    }
+
+    /// Allows each of `lints`, failing if any of them is not part of `group`.
+    pub fn allow(
+        all_lints: &AllLints,
+        group: &LintGroup,
+        lints: &[LintId<'a>],
+        priority: impl Into<PrioritySetting>,
+    ) -> Result<Vec<Self>> {
+        let priority = priority.into();
+        lints
+            .iter()
+            .map(|lint| {
+                check_group(all_lints, *lint, group)?;
+                Ok(Self::Single(SingleLintConfig {
+                    lint: *lint,
+                    priority,
+                    level: LintLevel::Allow,
+                    description: None,
+                    reason: None,
+                    expires: None,
+                    version: all_lints.get(*lint).and_then(|known| known.version),
+                }))
+            })
+            .collect()
+    }
+
+    /// Emits every lint of `group` individually: the `exceptions` with their
+    /// level and priority, all others with `default_level` and
+    /// `default_priority`. Lints introduced after `msrv` are left out.
+    pub fn split_group_exhaustive(
+        all_lints: &'a AllLints,
+        group: &LintGroup,
+        default_level: LintLevel,
+        default_priority: PrioritySetting,
+        exceptions: &Exceptions<'a>,
+        msrv: Option<RustVersion>,
+    ) -> Result<ExhaustiveGroup<'a>> {
+        for &lint in &exceptions.lints.0 {
+            check_group(all_lints, lint, group)?;
+        }
+
+        let all_lints_in_group: Vec<&Lint<'a>> = all_lints
+            .group(group)
+            .filter(|lint| lint.is_available_in(msrv))
+            .collect();
+
+        let all_lints_in_group_len = all_lints_in_group.len();
+
+        Ok(all_lints_in_group
+            .into_iter()
+            .map(|lint| {
+                if exceptions.lints.0.contains(&lint.id) {
+                    (
+                        ExhaustiveGroupClassification::Exception,
+                        Self::Single(SingleLintConfig {
+                            lint: lint.id,
+                            priority: exceptions.priority,
+                            level: exceptions.level,
+                            description: lint.summary(),
+                            reason: None,
+                            expires: None,
+                            version: lint.version,
+                        }),
+                    )
+                } else {
+                    (
+                        ExhaustiveGroupClassification::Default,
+                        Self::Single(SingleLintConfig {
+                            lint: lint.id,
+                            priority: default_priority,
+                            level: default_level,
+                            description: None,
+                            reason: None,
+                            expires: None,
+                            version: lint.version,
+                        }),
+                    )
+                }
+            })
+            .fold(
+                {
+                    let len_1 = exceptions.lints.0.len();
+                    ExhaustiveGroup {
+                        defaults: Vec::with_capacity(all_lints_in_group_len.saturating_sub(len_1)),
+                        exceptions: Vec::with_capacity(len_1),
+                    }
+                },
+                |mut acc, (classification, setting)| {
+                    match classification {
+                        ExhaustiveGroupClassification::Default => acc.defaults.push(setting),
+                        ExhaustiveGroupClassification::Exception => acc.exceptions.push(setting),
+                    }
+                    acc
+                },
+            ))
+    }
+}
+
+impl Setting<'_> {
+    /// The key of the setting, i.e. the lint or group name.
+    pub fn name(&self) -> &str {
+        match *self {
+            Self::Single(ref single_lint_config) => single_lint_config.lint.0,
+            Self::Group(ref group_config) => group_config.group.as_str(),
+        }
+    }
+
+    pub fn level(&self) -> LintLevel {
+        match *self {
+            Self::Single(ref single_lint_config) => single_lint_config.level,
+            Self::Group(ref group_config) => group_config.level,
+        }
+    }
+
+    pub fn priority(&self) -> PrioritySetting {
+        match *self {
+            Self::Single(ref single_lint_config) => single_lint_config.priority,
+            Self::Group(ref group_config) => group_config.priority,
+        }
+    }
+}
+
+/// The tool of clippy lints.
+pub const CLIPPY: &str = "clippy";
+/// The tool of rustc lints, which are not qualified with a tool name.
+pub const RUST: &str = "rust";
+/// The tool of rustdoc lints.
+pub const RUSTDOC: &str = "rustdoc";
+
+/// The tools every configuration covers, in output order. Other tools follow
+/// in the order they first appear in.
+pub const KNOWN_TOOLS: [&str; 3] = [CLIPPY, RUST, RUSTDOC];
+
+/// A block of settings for one tool in the output, preceded by an optional
+/// comment.
+#[derive(Debug, Serialize)]
+pub struct ConfigGroup<'a> {
+    /// The tool of the lints, i.e. the `<tool>` of `[lints.<tool>]`.
+    #[serde(skip)]
+    pub tool: &'a str,
+    pub comment: Option<String>,
+    pub settings: Vec<Setting<'a>>,
+}
+
+impl ConfigGroup<'_> {
+    /// The comment of the group, or the tool if it has none.
+    pub fn name(&self) -> String {
+        self.comment
+            .clone()
+            .unwrap_or_else(|| format!("{} lints", self.tool))
+    }
+}
+
+/// The complete generated configuration. It serializes as a map from tool to
+/// its groups.
+#[derive(Debug)]
+pub struct Config<'a> {
+    pub groups: Vec<ConfigGroup<'a>>,
+}
+
+impl Serialize for Config<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(
+            self.tools()
+                .into_iter()
+                .map(|tool| (tool, self.tool_groups(tool).collect::<Vec<_>>())),
+        )
+    }
+}
+
+/// Where the documentation of each clippy lint lives, as an anchor named
+/// after the lint.
+const CLIPPY_LINT_DOCS: &str = "https://rust-lang.github.io/rust-clippy/master/index.html";
+
+/// Options for [`Config::to_toml`].
+#[derive(Clone, Copy, Debug, Default)]
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "these are independent rendering options"
+)]
+pub struct TomlOptions {
+    /// Generate `[workspace.lints.<tool>]` instead of `[lints.<tool>]` tables.
+    pub workspace: bool,
+    /// Precede every single clippy lint with a link to its documentation.
+    pub links: bool,
+    /// Follow lints that have a description with it as a comment.
+    pub descriptions: bool,
+    /// Follow single clippy lints with the Rust version that introduced them
+    /// as a comment.
+    pub versions: bool,
+}
+
+/// Writes a lints table. With `docs`, every single lint is preceded by a
+/// comment linking to its documentation at `docs#<lint>`.
+fn write_table(
+    output: &mut String,
+    header: &str,
+    groups: &[&ConfigGroup<'_>],
+    docs: Option<&str>,
+    descriptions: bool,
+    versions: bool,
+) {
+    writeln!(output, "[{header}]").expect("writing to string succeeds");
+
+    let mut iter_group = groups
+        .iter()
+        .filter(|group| !group.settings.is_empty())
+        .peekable();
+
+    while let Some(group) = iter_group.next() {
+        let last_group = iter_group.peek().is_none();
+        if let Some(ref comment) = group.comment {
+            writeln!(output, "# {comment}").expect("writing to string succeeds");
+        }
+
+        let mut iter_setting = group.settings.iter().peekable();
+        while let Some(setting) = iter_setting.next() {
+            let last_setting = iter_setting.peek().is_none();
+            if let Setting::Single(SingleLintConfig {
+                reason: Some(reason),
+                ..
+            }) = *setting
+            {
+                for line in reason.lines() {
+                    writeln!(output, "# {}", line.trim_end()).expect("writing to string succeeds");
+                }
+            }
+            if let Setting::Single(SingleLintConfig {
+                expires: Some(expires),
+                ..
+            }) = *setting
+            {
+                writeln!(output, "# expires {expires}").expect("writing to string succeeds");
+            }
+            if let Some(docs) = docs {
+                if let Setting::Single(ref single_lint_config) = *setting {
+                    writeln!(output, "# {docs}#{}", single_lint_config.lint.0)
+                        .expect("writing to string succeeds");
+                }
+            }
+            match *setting {
+                Setting::Single(ref single_lint_config) => match single_lint_config.priority {
+                    PrioritySetting::Explicit(priority) => write!(
+                        output,
+                        "{} = {{ level = \"{}\", priority = {} }}",
+                        single_lint_config.lint.0,
+                        single_lint_config.level.as_str(),
+                        priority
+                    )
+                    .expect("writing to string succeeds"),
+                    PrioritySetting::Unspecified => write!(
+                        output,
+                        "{} = \"{}\"",
+                        single_lint_config.lint.0,
+                        single_lint_config.level.as_str()
+                    )
+                    .expect("writing to string succeeds"),
+                },
+                Setting::Group(ref group_config) => match group_config.priority {
+                    PrioritySetting::Explicit(priority) => write!(
+                        output,
+                        "{} = {{ level = \"{}\", priority = {} }}",
+                        group_config.group.as_str(),
+                        group_config.level.as_str(),
+                        priority
+                    )
+                    .expect("writing to string succeeds"),
+                    PrioritySetting::Unspecified => write!(
+                        output,
+                        "{} = \"{}\"",
+                        group_config.group.as_str(),
+                        group_config.level.as_str(),
+                    )
+                    .expect("writing to string succeeds"),
+                },
+            }
+            if let Setting::Single(ref single_lint_config) = *setting {
+                let description =
+                    single_lint_config
+                        .description
+                        .filter(|_| descriptions)
+                        .map(|description| {
+                            description.split_whitespace().collect::<Vec<_>>().join(" ")
+                        });
+                let version = single_lint_config.version.filter(|_| versions);
+                match (description, version) {
+                    (Some(description), Some(version)) => {
+                        write!(output, "  # {description} (since {version})")
+                    }
+                    (Some(description), None) => write!(output, "  # {description}"),
+                    (None, Some(version)) => write!(output, "  # since {version}"),
+                    (None, None) => Ok(()),
+                }
+                .expect("writing to string succeeds");
+            }
+            if !last_setting {
+                output.push('\n');
+            }
+            if last_setting && !last_group {
+                output.push('\n');
+            }
+        }
+        if !last_group {
+            output.push('\n');
+        }
+    }
+}
+
+/// The prefix that qualifies the lint names of `tool` outside of a
+/// `[lints.<tool>]` table, e.g. in flags.
+fn tool_prefix(tool: &str) -> String {
+    if tool == RUST {
+        String::new()
+    } else {
+        format!("{tool}::")
+    }
+}
+
+/// The lint groups of rustc, which appear as single lints in the profile.
+const RUSTC_LINT_GROUPS: &[&str] = &[
+    "deprecated_safe",
+    "future_incompatible",
+    "keyword_idents",
+    "let_underscore",
+    "nonstandard_style",
+    "refining_impl_trait",
+    "rust_2018_compatibility",
+    "rust_2018_idioms",
+    "rust_2021_compatibility",
+    "rust_2024_compatibility",
+    "unknown_or_malformed_diagnostic_attributes",
+    "unused",
+    "warnings",
+];
+
+/// The lint groups of rustdoc, which appear as single lints in the profile.
+const RUSTDOC_LINT_GROUPS: &[&str] = &["all"];
+
+/// The lint groups of `tool` that appear as single lints in the profile.
+/// Clippy groups have settings of their own.
+fn tool_lint_groups(tool: &str) -> &'static [&'static str] {
+    match tool {
+        RUST => RUSTC_LINT_GROUPS,
+        RUSTDOC => RUSTDOC_LINT_GROUPS,
+        _ => &[],
+    }
+}
+
+/// The formats a [`Config`] can be rendered in.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum Format {
+    /// `[lints]` tables for `Cargo.toml`
+    Toml,
+    /// The resolved configuration, for consumption by other tools
+    Json,
+    /// The same as `json`, as YAML
+    Yaml,
+    /// `-W clippy::<lint>` style flags for `RUSTFLAGS` or `cargo clippy --`
+    Flags,
+    /// `#![warn(clippy::<lint>)]` style crate-level attributes
+    Attributes,
+}
+
+/// How the settings of a [`Config`] are ordered, see [`Config::sort`].
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum SortOrder {
+    /// As defined by the profile, settings grouped by where they come from
+    #[default]
+    Group,
+    /// Alphabetically, the settings by name and the groups by comment
+    Alpha,
+    /// By level, `deny` first, then by name
+    Level,
+}
+
+/// Ranks levels for [`SortOrder::Level`], the strictest first.
+fn severity(level: LintLevel) -> u8 {
+    match level {
+        LintLevel::Deny => 0,
+        LintLevel::Warn => 1,
+        LintLevel::Allow => 2,
+        LintLevel::None => 3,
+    }
+}
+
+/// The key below which the lint tables live, depending on whether they are
+/// meant for a workspace root.
+pub fn table_prefix(workspace: bool) -> &'static str {
+    if workspace {
+        "workspace.lints"
+    } else {
+        "lints"
+    }
+}
+
+impl PrioritySetting {
+    /// The priority cargo assumes if none is given.
+    pub fn value(self) -> isize {
+        match self {
+            Self::Explicit(priority) => priority,
+            Self::Unspecified => 0,
+        }
+    }
+}
+
+impl<'a> Config<'a> {
+    /// The groups of `tool`, in order.
+    pub fn tool_groups<'b>(
+        &'b self,
+        tool: &'b str,
+    ) -> impl DoubleEndedIterator<Item = &'b ConfigGroup<'a>> {
+        self.groups.iter().filter(move |group| group.tool == tool)
+    }
+
+    /// The settings of all groups of `tool`, in order.
+    fn tool_settings<'b>(
+        &'b self,
+        tool: &'b str,
+    ) -> impl DoubleEndedIterator<Item = &'b Setting<'a>> {
+        self.tool_groups(tool).flat_map(|group| &group.settings)
+    }
+
+    /// The tools the configuration has groups for, or always has tables for,
+    /// in output order.
+    pub fn tools(&self) -> Vec<&'a str> {
+        let mut tools = KNOWN_TOOLS.to_vec();
+        for group in &self.groups {
+            if !tools.contains(&group.tool) {
+                tools.push(group.tool);
+            }
+        }
+        tools
+    }
+
+    /// Adds `group`, removing the settings of the same lints from the groups
+    /// of its tool before it.
+    pub fn override_lints(&mut self, group: ConfigGroup<'a>) {
+        for existing in self
+            .groups
+            .iter_mut()
+            .filter(|existing| existing.tool == group.tool)
+        {
+            existing.settings.retain(|setting| {
+                !matches!(*setting, Setting::Single(_))
+                    || !group
+                        .settings
+                        .iter()
+                        .any(|new| new.name() == setting.name())
+            });
+        }
+
+        self.groups.push(group);
+    }
+
+    /// Adds the groups of `other`, e.g. the settings of a
+    /// [`ProfileProvider`](crate::ProfileProvider), removing the settings of
+    /// the same lints and lint groups from the groups of their tool before.
+    pub fn layer(&mut self, other: Self) {
+        for group in other.groups {
+            for existing in self
+                .groups
+                .iter_mut()
+                .filter(|existing| existing.tool == group.tool)
+            {
+                existing.settings.retain(|setting| {
+                    !group
+                        .settings
+                        .iter()
+                        .any(|new| new.name() == setting.name())
+                });
+            }
+            self.groups.push(group);
+        }
+    }
+
+    /// The clippy setting that decides the level of `lint`, as cargo
+    /// resolves it: the entries are passed on ordered by priority and then by
+    /// name in reverse, and the last one that covers the lint wins.
+    fn clippy_setting(&self, lint: &Lint<'_>) -> Option<&Setting<'a>> {
+        self.tool_settings(CLIPPY)
+            .filter(|setting| match **setting {
+                Setting::Single(ref single_lint_config) => single_lint_config.lint == lint.id,
+                Setting::Group(ref group_config) => group_config.group == lint.group,
+            })
+            .max_by_key(|setting| (setting.priority().value(), Reverse(setting.name())))
+    }
+
+    /// The level the configuration assigns to the clippy lint `lint`, either
+    /// directly or through its group. `None` if the configuration leaves it
+    /// at its default level.
+    pub fn clippy_level(&self, lint: &Lint<'_>) -> Option<LintLevel> {
+        self.clippy_setting(lint).map(Setting::level)
+    }
+
+    /// Settings of clippy lints that do not work as written once cargo
+    /// orders the entries, described for humans: settings that the group of
+    /// the lint overrides, and settings that only win over their group
+    /// because the name of the lint sorts before it.
+    pub fn shadowed(&self, all_lints: &AllLints<'_>) -> Vec<String> {
+        let mut found = Vec::new();
+
+        for setting in self.tool_settings(CLIPPY) {
+            let Some(lint) = all_lints.get(LintId(setting.name())) else {
+                continue;
+            };
+            let Some(group) = self.tool_settings(CLIPPY).find(|other| match **other {
+                Setting::Group(ref group_config) => group_config.group == lint.group,
+                Setting::Single(_) => false,
+            }) else {
+                continue;
+            };
+
+            let priority = setting.priority().value();
+            let group_priority = group.priority().value();
+            match (group_priority, Reverse(group.name())).cmp(&(priority, Reverse(setting.name()))) {
+                Ordering::Greater => found.push(format!(
+                    "clippy::{} = \"{}\" has no effect, the {} group (priority {group_priority}) overrides it",
+                    lint.id,
+                    setting.level().as_str(),
+                    lint.group
+                )),
+                Ordering::Less if group_priority == priority => found.push(format!(
+                    "clippy::{} only overrides the {} group because its name sorts before it, give the group a lower priority",
+                    lint.id, lint.group
+                )),
+                Ordering::Less | Ordering::Equal => {}
+            }
+        }
+
+        found
+    }
+
+    /// The clippy lints that are allowed individually, e.g. the exceptions of
+    /// a group.
+    pub fn allowed_clippy_lints(&self) -> Vec<LintId<'a>> {
+        self.tool_settings(CLIPPY)
+            .filter_map(|setting| match *setting {
+                Setting::Single(ref single_lint_config)
+                    if single_lint_config.level == LintLevel::Allow =>
+                {
+                    Some(single_lint_config.lint)
+                }
+                Setting::Single(_) | Setting::Group(_) => None,
+            })
+            .collect()
+    }
+
+    /// Turns every `warn` into `deny`, except for the lints and groups named
+    /// in `soft`. Soft clippy lints that only warn through their group get a
+    /// setting of their own, so they keep warning.
+    pub fn escalate_warnings(&mut self, all_lints: &'a AllLints<'a>, soft: &[&str]) {
+        let soft_settings: Vec<Setting<'a>> = soft
+            .iter()
+            .filter_map(|&name| all_lints.get(LintId(name)))
+            .filter(|lint| {
+                !self
+                    .tool_settings(CLIPPY)
+                    .any(|setting| setting.name() == lint.id.0)
+                    && self.clippy_level(lint) == Some(LintLevel::Warn)
+            })
+            .map(|lint| {
+                Setting::Single(SingleLintConfig {
+                    lint: lint.id,
+                    priority: PrioritySetting::Unspecified,
+                    level: LintLevel::Warn,
+                    description: None,
+                    reason: None,
+                    expires: None,
+                    version: lint.version,
+                })
+            })
+            .collect();
+
+        for setting in self.groups.iter_mut().flat_map(|group| &mut group.settings) {
+            if soft.contains(&setting.name()) {
+                continue;
+            }
+            let level = match *setting {
+                Setting::Single(ref mut single_lint_config) => &mut single_lint_config.level,
+                Setting::Group(ref mut group_config) => &mut group_config.level,
+            };
+            if *level == LintLevel::Warn {
+                *level = LintLevel::Deny;
+            }
+        }
+
+        if !soft_settings.is_empty() {
+            self.groups.push(ConfigGroup {
+                tool: CLIPPY,
+                comment: Some("soft lints".to_owned()),
+                settings: soft_settings,
+            });
+        }
+    }
+
+    /// Removes the setting of the clippy group `group` and of all its lints,
+    /// leaving them at their default levels.
+    pub fn exclude_group(&mut self, group: &LintGroup, all_lints: &AllLints<'_>) {
+        for config_group in self
+            .groups
+            .iter_mut()
+            .filter(|existing| existing.tool == CLIPPY)
+        {
+            config_group.settings.retain(|setting| match *setting {
+                Setting::Group(ref group_config) => group_config.group != *group,
+                Setting::Single(ref single_lint_config) => all_lints
+                    .get(single_lint_config.lint)
+                    .is_none_or(|lint| lint.group != *group),
+            });
+        }
+    }
+
+    /// Removes the settings of clippy lints introduced after `version`, which
+    /// a toolchain of that version reports as unknown. Returns their names.
+    pub fn remove_unavailable(
+        &mut self,
+        all_lints: &AllLints<'_>,
+        version: RustVersion,
+    ) -> Vec<&'a str> {
+        let mut removed = Vec::new();
+        for config_group in self
+            .groups
+            .iter_mut()
+            .filter(|existing| existing.tool == CLIPPY)
+        {
+            config_group.settings.retain(|setting| match *setting {
+                Setting::Group(_) => true,
+                Setting::Single(ref single_lint_config) => {
+                    let available = all_lints
+                        .get(single_lint_config.lint)
+                        .is_none_or(|lint| lint.is_available_in(Some(version)));
+                    if !available {
+                        removed.push(single_lint_config.lint.0);
+                    }
+                    available
+                }
+            });
+        }
+        removed
+    }
+
+    /// Sets the reason of every single lint without one to its entry in
+    /// `reasons`, if any.
+    pub fn add_reasons(&mut self, reasons: &'a BTreeMap<String, String>) {
+        for setting in self.groups.iter_mut().flat_map(|group| &mut group.settings) {
+            if let Setting::Single(ref mut single_lint_config) = *setting {
+                if single_lint_config.reason.is_none() {
+                    single_lint_config.reason =
+                        reasons.get(single_lint_config.lint.0).map(String::as_str);
+                }
+            }
+        }
+    }
+}
+
+impl Config<'_> {
+    /// Logs which group of the configuration sets each lint. The groups are
+    /// named after the rules of the profile they come from.
+    pub(crate) fn trace_settings(&self) {
+        if !tracing::enabled!(tracing::Level::TRACE) {
+            return;
+        }
+        for group in &self.groups {
+            let rule = group.comment.as_deref().unwrap_or("no rule");
+            for setting in &group.settings {
+                tracing::trace!(
+                    "{}::{} = {} ({rule})",
+                    group.tool,
+                    setting.name(),
+                    setting.level().as_str()
+                );
+            }
+        }
+    }
+
+    /// Gives every lint group without an explicit priority a priority below
+    /// all single lints of its tool. Cargo applies lower priorities first, so
+    /// the single lints override the groups they belong to, which is also
+    /// what `clippy::lint_groups_priority` checks for.
+    pub fn assign_priorities(&mut self) {
+        for tool in self.tools() {
+            let tool_groups = tool_lint_groups(tool);
+            let is_group = |setting: &Setting<'_>| match *setting {
+                Setting::Single(ref single_lint_config) => {
+                    tool_groups.contains(&single_lint_config.lint.0)
+                }
+                Setting::Group(_) => true,
+            };
+
+            let group_priority = self
+                .tool_settings(tool)
+                .filter(|setting| !is_group(setting))
+                .map(|setting| setting.priority().value())
+                .fold(0, isize::min)
+                .saturating_sub(1);
+
+            for setting in self
+                .groups
+                .iter_mut()
+                .filter(|group| group.tool == tool)
+                .flat_map(|group| &mut group.settings)
+            {
+                if !is_group(setting) {
+                    continue;
+                }
+                let priority = match *setting {
+                    Setting::Single(ref mut single_lint_config) => &mut single_lint_config.priority,
+                    Setting::Group(ref mut group_config) => &mut group_config.priority,
+                };
+                if matches!(*priority, PrioritySetting::Unspecified) {
+                    *priority = PrioritySetting::Explicit(group_priority);
+                }
+            }
+        }
+    }
+
+    /// Orders the settings within each group and the groups of each tool.
+    /// The order does not change the meaning of the configuration, as every
+    /// lint appears only once per tool.
+    pub fn sort(&mut self, order: SortOrder) {
+        let groups = &mut self.groups;
+        match order {
+            SortOrder::Group => (),
+            SortOrder::Alpha => {
+                groups.sort_by(|a, b| a.comment.cmp(&b.comment));
+                for group in groups.iter_mut() {
+                    group.settings.sort_by(|a, b| a.name().cmp(b.name()));
+                }
+            }
+            SortOrder::Level => {
+                for group in groups.iter_mut() {
+                    group.settings.sort_by(|a, b| {
+                        severity(a.level())
+                            .cmp(&severity(b.level()))
+                            .then_with(|| a.name().cmp(b.name()))
+                    });
+                }
+                groups.sort_by_key(|group| {
+                    group
+                        .settings
+                        .iter()
+                        .map(|setting| severity(setting.level()))
+                        .min()
+                });
+            }
+        }
+    }
+
+    /// All settings with the prefix of their tool, ordered by priority and
+    /// then by name in reverse per tool, as cargo orders them. This is the order in
+    /// which they have to be given as flags or attributes, as later ones
+    /// override earlier ones.
+    fn settings_by_priority(&self) -> Vec<(String, &Setting<'_>)> {
+        let mut result = Vec::new();
+
+        for tool in self.tools() {
+            let mut settings: Vec<&Setting<'_>> = self.tool_settings(tool).collect();
+            settings.sort_by_key(|setting| (setting.priority().value(), Reverse(setting.name())));
+
+            result.extend(
+                settings
+                    .into_iter()
+                    .map(|setting| (tool_prefix(tool), setting)),
+            );
+        }
+
+        result
+    }
+
+    /// Renders the configuration as command line flags.
+    pub fn to_flags(&self) -> String {
+        self.settings_by_priority()
+            .into_iter()
+            .filter_map(|(ref tool_prefix, setting)| {
+                setting
+                    .level()
+                    .flag()
+                    .map(|flag| format!("{flag} {tool_prefix}{}", setting.name()))
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    /// Renders the configuration as crate-level attributes, one per line.
+    pub fn to_attributes(&self) -> String {
+        self.settings_by_priority()
+            .into_iter()
+            .filter(|&(_, setting)| !matches!(setting.level(), LintLevel::None))
+            .map(|(tool_prefix, setting)| {
+                format!(
+                    "#![{}({tool_prefix}{})]",
+                    setting.level().as_str(),
+                    setting.name()
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// Renders the configuration as lints tables for `Cargo.toml`.
+    pub fn to_toml(&self, options: TomlOptions) -> String {
+        let prefix = table_prefix(options.workspace);
+
+        let mut output = String::new();
+
+        for tool in self.tools() {
+            let groups: Vec<&ConfigGroup<'_>> = self.tool_groups(tool).collect();
+            // The clippy table is always written, even if empty.
+            if tool != CLIPPY {
+                if groups.is_empty() {
+                    continue;
+                }
+                output.push_str("\n\n");
+            }
+            write_table(
+                &mut output,
+                &format!("{prefix}.{tool}"),
+                &groups,
+                (tool == CLIPPY && options.links).then_some(CLIPPY_LINT_DOCS),
+                options.descriptions,
+                tool == CLIPPY && options.versions,
+            );
+        }
+
+        output
+    }
+
+    /// Fails if a lint or lint group is set more than once for the same tool,
+    /// naming both groups that set it. Cargo rejects the duplicate keys, and
+    /// with different levels it is unclear which one is meant.
+    pub fn check(&self) -> Result<()> {
+        for tool in self.tools() {
+            let mut seen: BTreeMap<&str, (&ConfigGroup<'_>, LintLevel)> = BTreeMap::new();
+            for group in self.tool_groups(tool) {
+                for setting in &group.settings {
+                    let Some(&(first_group, first_level)) = seen.get(setting.name()) else {
+                        seen.insert(setting.name(), (group, setting.level()));
+                        continue;
+                    };
+                    return Err(Error::ConflictingSettings {
+                        tool: tool.to_owned(),
+                        lint: setting.name().to_owned(),
+                        first_group: first_group.name(),
+                        first_level,
+                        second_group: group.name(),
+                        second_level: setting.level(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Renders the configuration in the given format, after checking it with
+    /// [`Config::check`]. `toml` only affects the TOML output, see
+    /// [`Config::to_toml`].
+    pub fn render(&self, format: Format, toml: TomlOptions) -> Result<String> {
+        self.check()?;
+        match format {
+            Format::Toml => Ok(self.to_toml(toml)),
+            Format::Json => serde_json::to_string_pretty(self).map_err(Error::serialize("JSON")),
+            Format::Yaml => serde_yaml::to_string(self)
+                .map(|yaml| yaml.trim_end().to_owned())
+                .map_err(Error::serialize("YAML")),
+            Format::Flags => Ok(self.to_flags()),
+            Format::Attributes => Ok(self.to_attributes()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{manifest::LintTables, source::Response};
+
+    const LINTS: &str = r#"[
+        {"id": "module_name_repetitions", "group": "pedantic", "level": "allow", "version": "1.33.0"},
+        {"id": "similar_names", "group": "pedantic", "level": "allow", "version": "1.0.0"}
+    ]"#;
+
+    fn tables(lints: &str) -> LintTables {
+        LintTables::parse(&format!("[lints.clippy]\n{lints}"), "lints")
+            .expect("the tables are valid")
+    }
+
+    fn shadowed(lints: &str) -> Vec<String> {
+        let response = Response::parse(LINTS).expect("the lint data is valid");
+        let all_lints = AllLints::from_response(&response);
+        tables(lints).to_config().shadowed(&all_lints)
+    }
+
+    fn group<'a>(comment: &str, lint: &'a str, level: LintLevel) -> ConfigGroup<'a> {
+        ConfigGroup {
+            tool: CLIPPY,
+            comment: Some(comment.to_owned()),
+            settings: vec![Setting::Single(SingleLintConfig {
+                lint: LintId(lint),
+                priority: PrioritySetting::Unspecified,
+                level,
+                description: None,
+                reason: None,
+                expires: None,
+                version: None,
+            })],
+        }
+    }
+
+    #[test]
+    fn check_rejects_lints_set_twice() {
+        let twice = Config {
+            groups: vec![
+                group("first", "similar_names", LintLevel::Warn),
+                group("second", "similar_names", LintLevel::Allow),
+            ],
+        };
+        assert_eq!(
+            twice
+                .check()
+                .expect_err("the lint is set twice")
+                .to_string(),
+            r#"clippy::similar_names is set to warn in "first" and to allow in "second""#
+        );
+
+        let once = Config {
+            groups: vec![
+                group("first", "similar_names", LintLevel::Warn),
+                group("second", "module_name_repetitions", LintLevel::Allow),
+            ],
+        };
+        once.check().expect("each lint is set once");
+    }
+
+    #[test]
+    fn flags_follow_the_order_of_cargo() {
+        // `cargo check -v` passes this table on as
+        // `--allow=clippy::similar_names --warn=clippy::pedantic
+        // --allow=clippy::module_name_repetitions --deny=clippy::all`.
+        let tables = tables(
+            "pedantic = \"warn\"\nsimilar_names = \"allow\"\nmodule_name_repetitions = \"allow\"\nall = \"deny\"\n",
+        );
+        assert_eq!(
+            tables.to_config().to_flags(),
+            "-A clippy::similar_names -W clippy::pedantic -A clippy::module_name_repetitions -D clippy::all"
+        );
+    }
+
+    #[test]
+    fn shadowed_by_a_group_of_higher_priority() {
+        assert_eq!(
+            shadowed("pedantic = { level = \"deny\", priority = 1 }\nsimilar_names = \"allow\"\n"),
+            [
+                r#"clippy::similar_names = "allow" has no effect, the pedantic group (priority 1) overrides it"#
+            ]
+        );
+    }
+
+    #[test]
+    fn shadowed_by_the_name_order() {
+        assert_eq!(
+            shadowed("pedantic = \"warn\"\nsimilar_names = \"allow\"\n"),
+            [
+                r#"clippy::similar_names = "allow" has no effect, the pedantic group (priority 0) overrides it"#
+            ]
+        );
+        assert_eq!(
+            shadowed("pedantic = \"warn\"\nmodule_name_repetitions = \"allow\"\n"),
+            ["clippy::module_name_repetitions only overrides the pedantic group because its name sorts before it, give the group a lower priority"]
+        );
+    }
+
+    #[test]
+    fn not_shadowed_below_a_group_of_lower_priority() {
+        assert!(shadowed(
+            "pedantic = { level = \"warn\", priority = -1 }\nmodule_name_repetitions = \"allow\"\nsimilar_names = \"allow\"\n"
+        )
+        .is_empty());
+    }
+}
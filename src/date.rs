@@ -0,0 +1,191 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::{Error, Result};
+
+/// A calendar date, e.g. when an exception of a profile is due for review.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    year: u32,
+    month: u32,
+    day: u32,
+}
+
+impl Date {
+    /// The current date in UTC.
+    #[cfg(feature = "native")]
+    pub fn today() -> Self {
+        let days = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs().div_euclid(86_400));
+        Self::from_days(days)
+    }
+
+    /// The date `days` after 1970-01-01, see Howard Hinnant's
+    /// `civil_from_days`.
+    #[cfg(feature = "native")]
+    fn from_days(days: u64) -> Self {
+        // Counted from 0000-03-01, so that leap days end a year.
+        let days = days.saturating_add(719_468);
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era = day_of_era
+            .saturating_sub(day_of_era.div_euclid(1460))
+            .saturating_add(day_of_era.div_euclid(36_524))
+            .saturating_sub(day_of_era.div_euclid(146_096))
+            .div_euclid(365);
+        let day_of_year = day_of_era.saturating_sub(
+            year_of_era
+                .saturating_mul(365)
+                .saturating_add(year_of_era.div_euclid(4))
+                .saturating_sub(year_of_era.div_euclid(100)),
+        );
+        let march_month = day_of_year
+            .saturating_mul(5)
+            .saturating_add(2)
+            .div_euclid(153);
+        let day = day_of_year
+            .saturating_sub(
+                march_month
+                    .saturating_mul(153)
+                    .saturating_add(2)
+                    .div_euclid(5),
+            )
+            .saturating_add(1);
+        let month = if march_month < 10 {
+            march_month.saturating_add(3)
+        } else {
+            march_month.saturating_sub(9)
+        };
+        let year = era
+            .saturating_mul(400)
+            .saturating_add(year_of_era)
+            .saturating_add(u64::from(month <= 2));
+
+        let narrow = |value: u64| u32::try_from(value).unwrap_or(u32::MAX);
+        Self {
+            year: narrow(year),
+            month: narrow(month),
+            day: narrow(day),
+        }
+    }
+}
+
+impl FromStr for Date {
+    type Err = Error;
+
+    /// Accepts dates like `2025-09-01`.
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::InvalidDate(s.to_owned());
+
+        let mut parts = s.trim().split('-').map(|part| {
+            part.chars()
+                .all(|c| c.is_ascii_digit())
+                .then(|| part.parse::<u32>().ok())
+                .flatten()
+                .ok_or_else(invalid)
+        });
+        let mut next = || parts.next().unwrap_or_else(|| Err(invalid()));
+        let date = Self {
+            year: next()?,
+            month: next()?,
+            day: next()?,
+        };
+
+        if parts.next().is_some()
+            || !(1..=12).contains(&date.month)
+            || !(1..=31).contains(&date.day)
+        {
+            return Err(invalid());
+        }
+
+        Ok(date)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Serializes as a string like `2025-09-01`.
+impl Serialize for Date {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Either a string or, in TOML, a bare date.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DateValue {
+    String(String),
+    Toml(toml::value::Datetime),
+}
+
+impl<'de> Deserialize<'de> for Date {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = match DateValue::deserialize(deserializer)? {
+            DateValue::String(value) => value,
+            DateValue::Toml(value) => value.to_string(),
+        };
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> Date {
+        s.parse().expect("the date is valid")
+    }
+
+    #[test]
+    fn parses_and_orders_dates() {
+        assert_eq!(date("2025-09-01").to_string(), "2025-09-01");
+        assert_eq!(date("2025-9-1").to_string(), "2025-09-01");
+        assert!(date("2025-09-30") < date("2025-10-01"));
+    }
+
+    #[test]
+    fn rejects_malformed_dates() {
+        for invalid in [
+            "2025-09",
+            "2025-13-01",
+            "2025-09-00",
+            "2025-09-01-01",
+            "2025-+9-01",
+            "",
+        ] {
+            let err = invalid.parse::<Date>().expect_err("the date is malformed");
+            assert_eq!(
+                err.to_string(),
+                Error::InvalidDate(invalid.to_owned()).to_string()
+            );
+        }
+    }
+
+    #[test]
+    fn reads_strings_and_toml_dates() {
+        #[derive(Deserialize)]
+        struct Entry {
+            expires: Date,
+        }
+
+        for toml in ["expires = \"2025-09-01\"", "expires = 2025-09-01"] {
+            let entry: Entry = toml::from_str(toml).expect("the date is valid");
+            assert_eq!(entry.expires, date("2025-09-01"));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn counts_days_from_the_epoch() {
+        assert_eq!(Date::from_days(0), date("1970-01-01"));
+        assert_eq!(Date::from_days(11_016), date("2000-02-29"));
+        assert_eq!(Date::from_days(20_332), date("2025-09-01"));
+    }
+}
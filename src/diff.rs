@@ -0,0 +1,400 @@
+use std::{collections::BTreeMap, fmt::Write as _};
+
+use serde::Serialize;
+use similar::{ChangeTag, TextDiff};
+use toml::{Table, Value};
+
+use crate::{
+    config::KNOWN_TOOLS,
+    error::{Error, Result},
+};
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const CYAN: &str = "\x1b[36m";
+const RESET: &str = "\x1b[0m";
+
+/// Extracts the lint tables (e.g. `[lints.clippy]`) below `prefix` from the
+/// manifest text, in the same order and layout as the generated output.
+///
+/// The tables of the known tools come first, then those of other tools in
+/// the order they appear in.
+pub fn extract_lint_tables(manifest: &str, prefix: &str) -> String {
+    let mut tables: Vec<(usize, Vec<&str>)> = Vec::new();
+    let mut current: Option<(usize, Vec<&str>)> = None;
+
+    for line in manifest.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            tables.extend(current.take());
+
+            let name = trimmed
+                .trim_start_matches('[')
+                .split(']')
+                .next()
+                .unwrap_or_default()
+                .trim();
+
+            current = name
+                .strip_prefix(prefix)
+                .and_then(|name| name.strip_prefix('.'))
+                .filter(|tool| !tool.contains('.'))
+                .map(|tool| {
+                    let position = KNOWN_TOOLS
+                        .iter()
+                        .position(|known| *known == tool)
+                        .unwrap_or(KNOWN_TOOLS.len());
+                    (position, Vec::new())
+                });
+        }
+
+        if let Some((_, ref mut lines)) = current {
+            lines.push(line.trim_end());
+        }
+    }
+    tables.extend(current);
+
+    tables.sort_by_key(|&(position, _)| position);
+
+    tables
+        .into_iter()
+        .map(|(_, mut lines)| {
+            while lines.last().is_some_and(|line| line.is_empty()) {
+                lines.pop();
+            }
+            lines.join("\n")
+        })
+        .collect::<Vec<String>>()
+        .join("\n\n")
+}
+
+/// Renders a unified diff from `old` to `new`. Returns `None` if both are
+/// identical.
+pub fn unified(
+    old: &str,
+    new: &str,
+    old_name: &str,
+    new_name: &str,
+    color: bool,
+) -> Option<String> {
+    let old = format!("{}\n", old.trim_end());
+    let new = format!("{}\n", new.trim_end());
+
+    if old == new {
+        return None;
+    }
+
+    let diff = TextDiff::from_lines(&old, &new);
+
+    let mut output = String::new();
+
+    let (red, green, cyan, reset) = if color {
+        (RED, GREEN, CYAN, RESET)
+    } else {
+        ("", "", "", "")
+    };
+
+    writeln!(output, "{red}--- {old_name}{reset}").expect("writing to string succeeds");
+    writeln!(output, "{green}+++ {new_name}{reset}").expect("writing to string succeeds");
+
+    for hunk in diff.unified_diff().iter_hunks() {
+        writeln!(output, "{cyan}{}{reset}", hunk.header()).expect("writing to string succeeds");
+        for change in hunk.iter_changes() {
+            let (sign, color) = match change.tag() {
+                ChangeTag::Delete => ('-', red),
+                ChangeTag::Insert => ('+', green),
+                ChangeTag::Equal => (' ', ""),
+            };
+            let reset = if color.is_empty() { "" } else { reset };
+            write!(output, "{color}{sign}{change}{reset}").expect("writing to string succeeds");
+        }
+    }
+
+    Some(output)
+}
+
+/// The level and priority of an entry in a lints table.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Entry {
+    pub level: String,
+    /// `None` if not given, which cargo treats as 0.
+    pub priority: Option<i64>,
+}
+
+/// An entry of the manifest that differs from the generated configuration.
+#[derive(Debug, Serialize)]
+pub struct Difference {
+    pub tool: String,
+    pub lint: String,
+    /// The line of the entry in the manifest, starting at 1. `None` for
+    /// missing entries.
+    pub line: Option<usize>,
+    /// The entry in the manifest, `None` if it is missing.
+    pub actual: Option<Entry>,
+    /// The generated entry, `None` if the manifest has an extra entry.
+    pub expected: Option<Entry>,
+}
+
+/// How the lint tables of a manifest differ from the generated ones, by kind
+/// of difference. An entry with both another level and priority is only
+/// listed as level mismatch.
+#[derive(Debug, Default, Serialize)]
+pub struct Drift {
+    pub missing: Vec<Difference>,
+    pub extra: Vec<Difference>,
+    pub level_mismatches: Vec<Difference>,
+    pub priority_mismatches: Vec<Difference>,
+}
+
+impl Drift {
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty()
+            && self.extra.is_empty()
+            && self.level_mismatches.is_empty()
+            && self.priority_mismatches.is_empty()
+    }
+
+    /// Describes every difference, with the line in the manifest it refers
+    /// to, if any. Ordered by line, missing entries first.
+    pub fn messages(&self) -> Vec<(Option<usize>, String)> {
+        let level =
+            |entry: Option<&Entry>| entry.map_or_else(String::new, |entry| entry.level.clone());
+        let priority =
+            |entry: Option<&Entry>| entry.and_then(|entry| entry.priority).unwrap_or_default();
+
+        let mut messages: Vec<(Option<usize>, String)> = self
+            .missing
+            .iter()
+            .map(|difference| {
+                (
+                    difference.line,
+                    format!(
+                        "{} lint {} is missing, expected \"{}\"",
+                        difference.tool,
+                        difference.lint,
+                        level(difference.expected.as_ref())
+                    ),
+                )
+            })
+            .chain(self.extra.iter().map(|difference| {
+                (
+                    difference.line,
+                    format!(
+                        "{} lint {} is not part of the generated configuration",
+                        difference.tool, difference.lint
+                    ),
+                )
+            }))
+            .chain(self.level_mismatches.iter().map(|difference| {
+                (
+                    difference.line,
+                    format!(
+                        "{} lint {} is \"{}\", expected \"{}\"",
+                        difference.tool,
+                        difference.lint,
+                        level(difference.actual.as_ref()),
+                        level(difference.expected.as_ref())
+                    ),
+                )
+            }))
+            .chain(self.priority_mismatches.iter().map(|difference| {
+                (
+                    difference.line,
+                    format!(
+                        "{} lint {} has priority {}, expected {}",
+                        difference.tool,
+                        difference.lint,
+                        priority(difference.actual.as_ref()),
+                        priority(difference.expected.as_ref())
+                    ),
+                )
+            }))
+            .collect();
+
+        messages.sort_by_key(|&(line, _)| line);
+        messages
+    }
+}
+
+/// The entries of the lint tables below `prefix`, by tool and lint.
+fn lint_entries(
+    document: &str,
+    prefix: &str,
+    parse_error: impl FnOnce(toml::de::Error) -> Error,
+) -> Result<BTreeMap<(String, String), Entry>> {
+    let document: Table = toml::from_str(document).map_err(parse_error)?;
+
+    let mut tables = Some(&document);
+    for key in prefix.split('.') {
+        tables = tables.and_then(|table| table.get(key)?.as_table());
+    }
+
+    let mut entries = BTreeMap::new();
+    for (tool, table) in tables.into_iter().flatten() {
+        let Some(table) = table.as_table() else {
+            continue;
+        };
+        for (lint, value) in table {
+            let entry = match *value {
+                Value::String(ref level) => Entry {
+                    level: level.clone(),
+                    priority: None,
+                },
+                Value::Table(ref table) => Entry {
+                    level: table
+                        .get("level")
+                        .and_then(Value::as_str)
+                        .ok_or_else(|| {
+                            Error::InvalidManifest(format!("lint {tool}.{lint} has no level"))
+                        })?
+                        .to_owned(),
+                    priority: table.get("priority").and_then(Value::as_integer),
+                },
+                _ => {
+                    return Err(Error::InvalidManifest(format!(
+                        "lint {tool}.{lint} has an invalid value"
+                    )))
+                }
+            };
+            entries.insert((tool.clone(), lint.clone()), entry);
+        }
+    }
+
+    Ok(entries)
+}
+
+/// The line of the entry for `lint` in the `[<prefix>.<tool>]` table of the
+/// manifest, starting at 1. Only entries on lines of their own are found.
+fn entry_line(manifest: &str, prefix: &str, tool: &str, lint: &str) -> Option<usize> {
+    let header = format!("{prefix}.{tool}");
+    let mut in_table = false;
+
+    for (index, text) in manifest.lines().enumerate() {
+        let text = text.trim();
+        if let Some(name) = text.strip_prefix('[') {
+            in_table = name
+                .split(']')
+                .next()
+                .is_some_and(|name| name.trim() == header);
+            continue;
+        }
+        let key = text
+            .split_once('=')
+            .map(|(key, _)| key.trim().trim_matches('"'));
+        if in_table && key == Some(lint) {
+            return Some(index.saturating_add(1));
+        }
+    }
+
+    None
+}
+
+/// Compares the lint tables below `prefix` in the manifest with the
+/// generated ones.
+pub fn compare(manifest: &str, generated: &str, prefix: &str) -> Result<Drift> {
+    let actual = lint_entries(manifest, prefix, Error::manifest)?;
+    let expected = lint_entries(generated, prefix, Error::parse("generated configuration"))?;
+
+    let mut drift = Drift::default();
+
+    for (key, expected_entry) in &expected {
+        let (ref tool, ref lint) = *key;
+        let difference = |actual: Option<&Entry>| Difference {
+            tool: tool.clone(),
+            lint: lint.clone(),
+            line: actual.and_then(|_| entry_line(manifest, prefix, tool, lint)),
+            actual: actual.cloned(),
+            expected: Some(expected_entry.clone()),
+        };
+        match actual.get(key) {
+            None => drift.missing.push(difference(None)),
+            Some(actual_entry) if actual_entry.level != expected_entry.level => {
+                drift.level_mismatches.push(difference(Some(actual_entry)));
+            }
+            Some(actual_entry)
+                if actual_entry.priority.unwrap_or_default()
+                    != expected_entry.priority.unwrap_or_default() =>
+            {
+                drift
+                    .priority_mismatches
+                    .push(difference(Some(actual_entry)));
+            }
+            Some(_) => (),
+        }
+    }
+
+    for (key, actual_entry) in actual {
+        if expected.contains_key(&key) {
+            continue;
+        }
+        let (tool, lint) = key;
+        drift.extra.push(Difference {
+            line: entry_line(manifest, prefix, &tool, &lint),
+            tool,
+            lint,
+            actual: Some(actual_entry),
+            expected: None,
+        });
+    }
+
+    Ok(drift)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extract_orders_known_tools_first() {
+        let manifest = r#"[package]
+name = "demo"
+
+[lints.rustdoc]
+broken_intra_doc_links = "deny"
+
+[lints.custom_tool]
+some_lint = "warn"
+
+[lints.clippy]
+pedantic = "warn"
+
+[lints.clippy.nested]
+ignored = true
+
+[dependencies]
+"#;
+        assert_eq!(
+            extract_lint_tables(manifest, "lints"),
+            r#"[lints.clippy]
+pedantic = "warn"
+
+[lints.rustdoc]
+broken_intra_doc_links = "deny"
+
+[lints.custom_tool]
+some_lint = "warn""#
+        );
+    }
+
+    #[test]
+    fn compare_reports_each_kind_of_drift() {
+        let manifest = "[lints.clippy]\npedantic = \"warn\"\ndbg_macro = \"deny\"\nall = { level = \"warn\", priority = -2 }\n";
+        let generated = "[lints.clippy]\npedantic = \"deny\"\nall = { level = \"warn\", priority = -1 }\nunwrap_used = \"deny\"\n";
+
+        let drift = compare(manifest, generated, "lints").expect("both tables are valid");
+        let lints = |differences: &[Difference]| -> Vec<String> {
+            differences
+                .iter()
+                .map(|difference| difference.lint.clone())
+                .collect()
+        };
+        assert_eq!(lints(&drift.missing), ["unwrap_used"]);
+        assert_eq!(lints(&drift.extra), ["dbg_macro"]);
+        assert_eq!(lints(&drift.level_mismatches), ["pedantic"]);
+        assert_eq!(lints(&drift.priority_mismatches), ["all"]);
+        assert_eq!(
+            drift.extra.first().and_then(|difference| difference.line),
+            Some(3)
+        );
+    }
+}
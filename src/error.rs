@@ -0,0 +1,249 @@
+#[cfg(feature = "native")]
+use std::path::Path;
+use std::{io, path::PathBuf};
+
+use crate::lint::{LintGroup, LintLevel};
+
+/// An error of another crate, e.g. of the TOML parser or the HTTP client.
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// The ways the library can fail, so callers can match on them instead of on
+/// messages.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// Downloading the lint data failed, after all attempts.
+    #[error("failed to download {url}: {reason} (attempt {attempt} of {attempts})")]
+    Network {
+        url: String,
+        /// What went wrong, e.g. `DNS lookup failed`.
+        reason: String,
+        attempt: u32,
+        attempts: u32,
+        #[source]
+        source: BoxError,
+    },
+
+    /// The last attempt to download the lint data timed out.
+    #[error("timed out fetching lint data from {url} (attempt {attempt} of {attempts})")]
+    TimedOut {
+        url: String,
+        attempt: u32,
+        attempts: u32,
+        #[source]
+        source: BoxError,
+    },
+
+    #[error("invalid proxy {proxy}")]
+    InvalidProxy {
+        proxy: String,
+        #[source]
+        source: BoxError,
+    },
+
+    /// Reading or writing a file failed.
+    #[error("failed to {action} {}", path.display())]
+    Io {
+        /// What was done to the file, e.g. `read lockfile`.
+        action: &'static str,
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    /// Starting a clippy program failed.
+    #[error("failed to run {program}, is clippy installed?")]
+    Run {
+        program: &'static str,
+        #[source]
+        source: io::Error,
+    },
+
+    /// A clippy program ran, but failed.
+    #[error("{command} failed: {message}")]
+    Command {
+        command: &'static str,
+        /// The relevant part of its error output.
+        message: String,
+    },
+
+    /// Data or a file could not be parsed.
+    #[error("failed to parse {what}")]
+    Parse {
+        /// What was parsed, e.g. `downloaded lint data`.
+        what: String,
+        #[source]
+        source: BoxError,
+    },
+
+    #[error("failed to serialize {what}")]
+    Serialize {
+        what: &'static str,
+        #[source]
+        source: BoxError,
+    },
+
+    #[error("failed to parse manifest")]
+    ManifestParse(#[source] BoxError),
+
+    /// The manifest or the generated configuration parses, but is not laid
+    /// out as expected.
+    #[error("{0}")]
+    InvalidManifest(String),
+
+    /// The config file parses, but is not laid out as expected, e.g. the
+    /// clippy lints of a profile are not a list.
+    #[error("invalid config file: {0}")]
+    InvalidConfigFile(String),
+
+    /// An entry of the lint data does not have the expected shape, e.g. a
+    /// field is missing or has a level the crate does not know.
+    #[error(
+        "entry {index}{} of the lint data is invalid: {problem}",
+        id.as_ref().map(|id| format!(" ({id})")).unwrap_or_default()
+    )]
+    InvalidLintEntry {
+        /// The position of the entry, counting from 0.
+        index: usize,
+        id: Option<String>,
+        /// What is wrong, naming the field if possible.
+        problem: String,
+    },
+
+    /// The lint data parses, but is not usable.
+    #[error("{0}")]
+    InvalidLintData(String),
+
+    /// A lint that the lint data does not know. `group` is the group it was
+    /// expected in, if any.
+    #[error(
+        "unknown lint {lint}{}",
+        group.as_ref().map(|group| format!(" in group {group}")).unwrap_or_default()
+    )]
+    UnknownLint {
+        lint: String,
+        group: Option<LintGroup>,
+    },
+
+    #[error("unknown lints: {}", lints.join(", "))]
+    UnknownLints { lints: Vec<String> },
+
+    /// A lint listed for a group it is not part of.
+    #[error("lint {lint} not part of group {group}")]
+    WrongGroup { lint: String, group: LintGroup },
+
+    /// A [`CommandProvider`](crate::CommandProvider) ran, but failed.
+    #[error("profile provider {command} failed: {message}")]
+    Provider {
+        command: String,
+        /// The relevant part of its error output.
+        message: String,
+    },
+
+    /// Two groups of the configuration set the same lint, see
+    /// [`Config::check`](crate::Config::check). The groups are named by
+    /// their comments.
+    #[error(
+        "{tool}::{lint} is set to {} in \"{first_group}\" and to {} in \"{second_group}\"",
+        first_level.as_str(),
+        second_level.as_str()
+    )]
+    ConflictingSettings {
+        tool: String,
+        lint: String,
+        first_group: String,
+        first_level: LintLevel,
+        second_group: String,
+        second_level: LintLevel,
+    },
+
+    /// Entries of the lint tables of a manifest set other levels or
+    /// priorities than the generated configuration, see
+    /// [`Merge::Union`](crate::apply::Merge::Union).
+    #[error(
+        "the lint tables conflict with the generated configuration:\n{}",
+        .0.join("\n")
+    )]
+    MergeConflicts(Vec<String>),
+
+    #[error("unknown profile {0}")]
+    UnknownProfile(String),
+
+    #[error("no profile given")]
+    NoProfile,
+
+    /// Profiles extending each other in a cycle, starting and ending with
+    /// the same name.
+    #[error("profile inheritance cycle: {}", .0.join(" -> "))]
+    ProfileCycle(Vec<String>),
+
+    /// Resolving the parent of profile `name` failed.
+    #[error("failed to resolve profile {name}")]
+    Profile {
+        name: String,
+        #[source]
+        source: Box<Self>,
+    },
+
+    #[error("profile defines no member {0}")]
+    UnknownMember(String),
+
+    /// A condition of a rule in a profile does not parse, see
+    /// [`Condition`](crate::Condition).
+    #[error("invalid condition `{condition}`: {problem}")]
+    InvalidCondition { condition: String, problem: String },
+
+    #[error("invalid rust version \"{0}\", expected e.g. \"1.77\"")]
+    InvalidVersion(String),
+
+    #[error("invalid date \"{0}\", expected e.g. \"2025-09-01\"")]
+    InvalidDate(String),
+
+    #[error(
+        "lockfile {} does not match its hash, run `cargo clippy-lints update` to recreate it",
+        .0.display()
+    )]
+    LockfileHash(PathBuf),
+
+    /// An error of a [`LintSource`](crate::LintSource) implemented outside
+    /// the crate.
+    #[error(transparent)]
+    Custom(BoxError),
+}
+
+impl Error {
+    /// For `map_err` on file operations.
+    #[cfg(feature = "native")]
+    pub(crate) fn io(action: &'static str, path: &Path) -> impl FnOnce(io::Error) -> Self {
+        let path = path.to_owned();
+        move |source| Self::Io {
+            action,
+            path,
+            source,
+        }
+    }
+
+    /// For `map_err` on parsers.
+    pub(crate) fn parse<E: Into<BoxError>>(what: impl Into<String>) -> impl FnOnce(E) -> Self {
+        let what = what.into();
+        move |source| Self::Parse {
+            what,
+            source: source.into(),
+        }
+    }
+
+    /// For `map_err` on serializers.
+    pub(crate) fn serialize<E: Into<BoxError>>(what: &'static str) -> impl FnOnce(E) -> Self {
+        move |source| Self::Serialize {
+            what,
+            source: source.into(),
+        }
+    }
+
+    /// For `map_err` on parsing a manifest.
+    pub(crate) fn manifest(source: impl Into<BoxError>) -> Self {
+        Self::ManifestParse(source.into())
+    }
+}
@@ -0,0 +1,314 @@
+use std::{
+    fmt, io, thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{BoxError, Error, Result},
+    proxy,
+};
+
+/// Upper bound for the delay between two attempts.
+const MAX_BACKOFF: Duration = Duration::from_mins(1);
+
+/// How failed downloads are retried. Only failures that may go away on
+/// their own are retried, e.g. timeouts or server errors, but not an
+/// invalid certificate or a missing file.
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    /// How often to try in total, at least once.
+    pub attempts: u32,
+    /// The delay before the first retry, doubled for every further one.
+    pub backoff: Duration,
+    /// Randomize the delays, so that many clients failing at the same time
+    /// do not retry in lockstep.
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            backoff: Duration::from_secs(1),
+            jitter: true,
+        }
+    }
+}
+
+/// How long a download may take before it is given up, per attempt.
+#[derive(Clone, Copy, Debug)]
+pub struct Timeouts {
+    /// For establishing the connection, including DNS and TLS.
+    pub connect: Duration,
+    /// For the whole request, including reading the response.
+    pub overall: Duration,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            connect: Duration::from_secs(10),
+            overall: Duration::from_mins(1),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The delay before attempt `attempt`, counting from 1.
+    fn delay(&self, attempt: u32) -> Duration {
+        let delay = self
+            .backoff
+            .checked_mul(2_u32.saturating_pow(attempt.saturating_sub(2)))
+            .unwrap_or(MAX_BACKOFF)
+            .min(MAX_BACKOFF);
+
+        if self.jitter {
+            // Wait between half and all of the delay. The sub-second part of
+            // the clock is random enough for that.
+            let nanos = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .subsec_nanos();
+            let half = delay.checked_div(2).unwrap_or_default();
+            half.saturating_add(
+                half.checked_mul(nanos % 1000)
+                    .and_then(|jitter| jitter.checked_div(1000))
+                    .unwrap_or_default(),
+            )
+        } else {
+            delay
+        }
+    }
+}
+
+/// The cause of a failed download.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FailureKind {
+    Dns,
+    Tls,
+    Timeout,
+    Connection,
+    Status(u16),
+    Other,
+}
+
+impl FailureKind {
+    fn of(error: &ureq::Error) -> Self {
+        let transport = match *error {
+            ureq::Error::Status(status, _) => return Self::Status(status),
+            ureq::Error::Transport(ref transport) => transport,
+        };
+
+        let io_kind = std::error::Error::source(transport)
+            .and_then(|source| source.downcast_ref::<io::Error>())
+            .map(io::Error::kind);
+
+        match (transport.kind(), io_kind) {
+            (ureq::ErrorKind::Dns, _) => Self::Dns,
+            (_, Some(io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock)) => Self::Timeout,
+            // rustls reports handshake and certificate errors as invalid data.
+            (_, Some(io::ErrorKind::InvalidData)) => Self::Tls,
+            (
+                ureq::ErrorKind::ConnectionFailed
+                | ureq::ErrorKind::ProxyConnect
+                | ureq::ErrorKind::Io,
+                _,
+            ) => Self::Connection,
+            _ => Self::Other,
+        }
+    }
+
+    fn is_transient(self) -> bool {
+        match self {
+            Self::Dns | Self::Timeout | Self::Connection => true,
+            Self::Status(status) => status == 408 || status == 429 || status >= 500,
+            Self::Tls | Self::Other => false,
+        }
+    }
+}
+
+impl fmt::Display for FailureKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Dns => write!(f, "DNS lookup failed"),
+            Self::Tls => write!(f, "TLS error"),
+            Self::Timeout => write!(f, "timed out"),
+            Self::Connection => write!(f, "connection failed"),
+            Self::Status(status) => write!(f, "server responded with HTTP status {status}"),
+            Self::Other => write!(f, "request failed"),
+        }
+    }
+}
+
+/// Response headers that allow asking the server whether a previously
+/// downloaded copy is still current.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    fn of(response: &ureq::Response) -> Self {
+        Self {
+            etag: response.header("ETag").map(str::to_owned),
+            last_modified: response.header("Last-Modified").map(str::to_owned),
+        }
+    }
+}
+
+/// The outcome of a successful [`get`].
+pub enum Fetched {
+    /// The copy described by the given validators is still current.
+    NotModified,
+    Modified {
+        content: String,
+        validators: Validators,
+    },
+}
+
+fn get_once(
+    agent: &ureq::Agent,
+    url: &str,
+    validators: Option<&Validators>,
+) -> Result<Fetched, (FailureKind, BoxError)> {
+    let mut request = agent.get(url);
+    if let Some(validators) = validators {
+        if let Some(ref etag) = validators.etag {
+            request = request.set("If-None-Match", etag);
+        }
+        if let Some(ref last_modified) = validators.last_modified {
+            request = request.set("If-Modified-Since", last_modified);
+        }
+    }
+
+    let response = request
+        .call()
+        .map_err(|err| (FailureKind::of(&err), BoxError::from(err)))?;
+
+    if response.status() == 304 {
+        return Ok(Fetched::NotModified);
+    }
+
+    let validators = Validators::of(&response);
+
+    response
+        .into_string()
+        .map(|content| Fetched::Modified {
+            content,
+            validators,
+        })
+        .map_err(|err| {
+            let kind = match err.kind() {
+                io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => FailureKind::Timeout,
+                _ => FailureKind::Connection,
+            };
+            (kind, BoxError::from(err))
+        })
+}
+
+/// Downloads `url` as text, through `proxy` if given, see
+/// [`proxy::for_url`]. With `validators` of a previous download, the server
+/// may answer that the previous copy is still current instead.
+pub fn get(
+    url: &str,
+    proxy: Option<&str>,
+    retry: RetryPolicy,
+    timeouts: Timeouts,
+    validators: Option<&Validators>,
+) -> Result<Fetched> {
+    let mut agent = ureq::AgentBuilder::new()
+        .try_proxy_from_env(false)
+        .timeout_connect(timeouts.connect)
+        .timeout(timeouts.overall);
+    if let Some(proxy) = proxy::for_url(url, proxy) {
+        let parsed = ureq::Proxy::new(&proxy).map_err(|source| Error::InvalidProxy {
+            proxy: proxy.clone(),
+            source: source.into(),
+        })?;
+        agent = agent.proxy(parsed);
+    }
+    let agent = agent.build();
+
+    let attempts = retry.attempts.max(1);
+    let mut attempt = 1;
+    loop {
+        tracing::debug!("fetching {url} (attempt {attempt} of {attempts})");
+        match get_once(&agent, url, validators) {
+            Ok(Fetched::NotModified) => {
+                tracing::debug!("{url} is unchanged since the last download");
+                return Ok(Fetched::NotModified);
+            }
+            Ok(fetched) => return Ok(fetched),
+            Err((kind, _)) if attempt < attempts && kind.is_transient() => {
+                attempt = attempt.saturating_add(1);
+                let delay = retry.delay(attempt);
+                tracing::debug!("download failed: {kind}, retrying in {delay:.1?}");
+                thread::sleep(delay);
+            }
+            Err((FailureKind::Timeout, source)) => {
+                return Err(Error::TimedOut {
+                    url: url.to_owned(),
+                    attempt,
+                    attempts,
+                    source,
+                });
+            }
+            Err((kind, source)) => {
+                return Err(Error::Network {
+                    url: url.to_owned(),
+                    reason: kind.to_string(),
+                    attempt,
+                    attempts,
+                    source,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(jitter: bool) -> RetryPolicy {
+        RetryPolicy {
+            attempts: 10,
+            backoff: Duration::from_secs(1),
+            jitter,
+        }
+    }
+
+    #[test]
+    fn delay_doubles_up_to_the_maximum() {
+        let policy = policy(false);
+        assert_eq!(policy.delay(2), Duration::from_secs(1));
+        assert_eq!(policy.delay(3), Duration::from_secs(2));
+        assert_eq!(policy.delay(4), Duration::from_secs(4));
+        assert_eq!(policy.delay(8), MAX_BACKOFF);
+        assert_eq!(policy.delay(u32::MAX), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn jitter_waits_at_least_half_of_the_delay() {
+        for attempt in 2..10 {
+            let delay = policy(true).delay(attempt);
+            let full = policy(false).delay(attempt);
+            assert!(
+                delay >= full / 2 && delay <= full,
+                "{delay:?} is not between half and all of {full:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn only_transient_failures_are_retried() {
+        assert!(FailureKind::Timeout.is_transient());
+        assert!(FailureKind::Status(503).is_transient());
+        assert!(FailureKind::Status(429).is_transient());
+        assert!(!FailureKind::Status(404).is_transient());
+        assert!(!FailureKind::Tls.is_transient());
+    }
+}
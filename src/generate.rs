@@ -0,0 +1,387 @@
+#[cfg(feature = "native")]
+use std::fs;
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    config::Config,
+    error::{Error, Result},
+    lint::{AllLints, LintGroup, LintLevel},
+    manifest::{self, Adjustment},
+    profile::{ConfigFile, ProfileDefinition, ToolLintDefinition},
+    provider::{ProfileProvider as _, Project},
+    renames::RenamedLints,
+    version::RustVersion,
+};
+
+/// The profiles to layer and the clippy lints to set on top of them, e.g.
+/// from the command line.
+#[derive(Clone, Debug, Default)]
+pub struct ProfileSelection {
+    /// Names of built-in profiles or of profiles of `config_file`, later ones
+    /// take precedence.
+    pub profiles: Vec<String>,
+    pub config_file: Option<ConfigFile>,
+    /// Clippy lints to allow on top of the profiles, with or without the
+    /// `clippy::` prefix.
+    pub allow: Vec<String>,
+    pub warn: Vec<String>,
+    /// Of the lints given to several of `allow`, `warn` and `deny`, the
+    /// strictest level wins.
+    pub deny: Vec<String>,
+    /// Warn about the settings of layered profiles that override each other.
+    pub strict: bool,
+}
+
+impl ProfileSelection {
+    /// Resolves the profiles, each layered on top of the previous ones, and
+    /// sets the lints of `allow`, `warn` and `deny`. Renamed lints are
+    /// replaced by their new names with a warning.
+    pub fn load(&self) -> Result<ProfileDefinition> {
+        let resolve = |name: &str| ProfileDefinition::resolve(name, self.config_file.clone());
+
+        let mut profiles = self.profiles.iter();
+        let mut profile = resolve(profiles.next().ok_or(Error::NoProfile)?)?;
+
+        for name in profiles {
+            let next = resolve(name)?;
+            if self.strict {
+                for conflict in next.conflicts(&profile) {
+                    tracing::warn!("profile {name} overrides earlier profiles: {conflict}");
+                }
+            }
+            profile = next.inherit(profile);
+        }
+
+        for (lints, level) in [
+            (&self.allow, LintLevel::Allow),
+            (&self.warn, LintLevel::Warn),
+            (&self.deny, LintLevel::Deny),
+        ] {
+            for lint in lints {
+                let lint = lint.strip_prefix("clippy::").unwrap_or(lint);
+                profile.clippy.retain(|existing| existing.lint != lint);
+                profile.clippy.push(ToolLintDefinition {
+                    lint: lint.to_owned(),
+                    level,
+                    priority: None,
+                    reason: None,
+                    expires: None,
+                });
+            }
+        }
+
+        for renamed in profile.rename_lints(&RenamedLints::bundled()?) {
+            if let Some(new) = renamed.new.strip_prefix("clippy::") {
+                tracing::warn!(
+                    "lint {} has been renamed to {new}, using the new name",
+                    renamed.old
+                );
+            } else {
+                tracing::warn!(
+                    "leaving out lint {}, it has been renamed to the rustc lint {}",
+                    renamed.old,
+                    renamed.new
+                );
+            }
+        }
+
+        Ok(profile)
+    }
+}
+
+/// `profile` with the levels of `adjustments`, e.g. those of
+/// [`Package::adjustments`].
+///
+/// They replace the settings of the profile for the same clippy lints,
+/// except for the lints in `keep`, e.g. those of
+/// [`ProfileSelection::allow`], whose entries stay last. The profile is loaded once and adjusted for each package. Borrowing it is
+/// enough without adjustments, as providers keep the profile they printed for
+/// each project apart.
+pub fn adjust_profile<'a>(
+    profile: &'a ProfileDefinition,
+    adjustments: &[Adjustment],
+    keep: &[&str],
+) -> Cow<'a, ProfileDefinition> {
+    if adjustments.is_empty() {
+        return Cow::Borrowed(profile);
+    }
+
+    let mut profile = profile.clone();
+    for adjustment in adjustments {
+        let Some(level) = adjustment.level else {
+            continue;
+        };
+        if keep.contains(&adjustment.lint) {
+            continue;
+        }
+        profile
+            .clippy
+            .retain(|existing| existing.lint != adjustment.lint);
+        let position = profile
+            .clippy
+            .iter()
+            .position(|existing| keep.contains(&existing.lint.as_str()))
+            .unwrap_or(profile.clippy.len());
+        profile.clippy.insert(
+            position,
+            ToolLintDefinition {
+                lint: adjustment.lint.to_owned(),
+                level,
+                priority: None,
+                reason: Some(adjustment.reason.to_owned()),
+                expires: None,
+            },
+        );
+    }
+    Cow::Owned(profile)
+}
+
+/// A `Cargo.toml` manifest and its content.
+#[derive(Clone, Debug)]
+pub struct Manifest {
+    pub path: PathBuf,
+    pub content: String,
+}
+
+/// What generating the lint tables of a package needs to know about it, see
+/// [`Manifest::package`].
+#[derive(Clone, Debug)]
+pub struct Package {
+    /// The version of the toolchain pinned for the package.
+    pub toolchain: Option<RustVersion>,
+    /// `None` for workspace lint tables, which apply to several packages,
+    /// and for virtual manifests.
+    pub targets: Option<manifest::PackageTargets>,
+}
+
+impl Package {
+    /// The levels that follow from the targets of the package, see
+    /// [`PackageTargets::adjustments`](manifest::PackageTargets::adjustments).
+    pub fn adjustments(&self) -> Vec<Adjustment> {
+        self.targets
+            .map(manifest::PackageTargets::adjustments)
+            .unwrap_or_default()
+    }
+}
+
+impl Manifest {
+    #[cfg(feature = "native")]
+    pub fn read(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).map_err(Error::io("read manifest", path))?;
+
+        Ok(Self {
+            path: path.to_owned(),
+            content,
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        self.path.parent().unwrap_or_else(|| Path::new(""))
+    }
+
+    /// Whether to write workspace lint tables. `workspace`, e.g. from
+    /// `--workspace`, takes precedence over the kind of the manifest.
+    pub fn workspace_tables(&self, workspace: Option<bool>) -> Result<bool> {
+        match workspace {
+            Some(workspace) => Ok(workspace),
+            None => Ok(manifest::kind(&self.content)?.is_workspace()),
+        }
+    }
+
+    /// `msrv` if given, falling back to the `rust-version` of the manifest
+    /// and then to the version of a pinned toolchain.
+    #[cfg(feature = "native")]
+    pub fn msrv(&self, msrv: Option<RustVersion>) -> Result<Option<RustVersion>> {
+        match msrv {
+            Some(msrv) => Ok(Some(msrv)),
+            None => match manifest::rust_version(&self.content)? {
+                Some(msrv) => Ok(Some(msrv)),
+                None => manifest::toolchain_version(self.dir()),
+            },
+        }
+    }
+
+    /// The pinned toolchain and the targets of the package. Workspace lint
+    /// tables apply to several packages, so their targets are not detected.
+    #[cfg(feature = "native")]
+    pub fn package(&self, workspace: bool) -> Result<Package> {
+        Ok(Package {
+            toolchain: manifest::toolchain_version(self.dir())?,
+            targets: if workspace {
+                None
+            } else {
+                manifest::package_targets(&self.content, self.dir())?
+            },
+        })
+    }
+}
+
+/// Reads the manifests at `paths` and runs `run` on each, as the results are
+/// asked for. A failure does not stop the others.
+#[cfg(feature = "native")]
+pub fn for_each_manifest<T, E: From<Error>>(
+    paths: Vec<PathBuf>,
+    mut run: impl FnMut(&Manifest) -> Result<T, E>,
+) -> impl Iterator<Item = (PathBuf, Result<T, E>)> {
+    paths.into_iter().map(move |path| {
+        let result = Manifest::read(&path)
+            .map_err(E::from)
+            .and_then(|manifest| run(&manifest));
+        (path, result)
+    })
+}
+
+/// How [`resolve`] turns a profile into the configuration of a package.
+#[derive(Clone, Debug, Default)]
+pub struct GenerateOptions {
+    /// Lints introduced after this version are left out.
+    pub msrv: Option<RustVersion>,
+    /// The workspace member to resolve the member-specific configuration of,
+    /// see [`ProfileDefinition::to_member_config`].
+    pub member: Option<String>,
+    /// The project the rules and providers of the profile see. Its `member`
+    /// and, if set, its `rust_version` are taken from the options.
+    pub project: Project,
+    /// Escalate `warn` to `deny`, except for the soft lints of the profile,
+    /// see [`Config::escalate_warnings`].
+    pub escalate_warnings: bool,
+    /// Groups left out of the configuration, see [`Config::exclude_group`].
+    pub exclude_groups: Vec<LintGroup>,
+    /// The version of the toolchain pinned for the package. Lints it does not
+    /// know are left out with a warning.
+    pub toolchain: Option<RustVersion>,
+}
+
+/// Resolves the profile, or the configuration of `options.member`, and
+/// layers the settings of its rules and providers over it.
+///
+/// Shadowed settings and the lints the pinned toolchain does not know are
+/// logged as warnings.
+pub fn resolve<'a>(
+    profile: &'a ProfileDefinition,
+    all_lints: &'a AllLints<'a>,
+    options: &GenerateOptions,
+) -> Result<Config<'a>> {
+    let mut config = match options.member {
+        Some(ref member) => profile.to_member_config(member, all_lints, options.msrv)?,
+        None => profile.to_config(all_lints, options.msrv)?,
+    };
+
+    if !profile.rules.is_empty() || !profile.providers.is_empty() {
+        let project = Project {
+            member: options.member.clone(),
+            rust_version: options.msrv.or(options.project.rust_version),
+            ..options.project.clone()
+        };
+        for rule in &profile.rules {
+            config.layer(rule.config(all_lints, &project)?);
+        }
+        #[cfg(feature = "native")]
+        for provider in &profile.providers {
+            config.layer(provider.config(all_lints, &project)?);
+        }
+        #[cfg(not(feature = "native"))]
+        if let Some(provider) = profile.providers.first() {
+            return Err(Error::Provider {
+                command: provider.command().join(" "),
+                message: "running commands needs the native feature".to_owned(),
+            });
+        }
+    }
+
+    if options.escalate_warnings {
+        let soft: Vec<&str> = profile.soft.iter().map(String::as_str).collect();
+        config.escalate_warnings(all_lints, &soft);
+    }
+
+    for group in &options.exclude_groups {
+        config.exclude_group(group, all_lints);
+    }
+
+    if let Some(toolchain) = options.toolchain {
+        let unknown = config.remove_unavailable(all_lints, toolchain);
+        if !unknown.is_empty() {
+            tracing::warn!(
+                "the lint data is newer than the pinned toolchain {toolchain}, leaving out lints it does not know: {}",
+                unknown.join(", ")
+            );
+        }
+    }
+
+    for shadowed in config.shadowed(all_lints) {
+        tracing::warn!("{shadowed}");
+    }
+
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lints(profile: &ProfileDefinition) -> Vec<(&str, LintLevel)> {
+        profile
+            .clippy
+            .iter()
+            .map(|lint| (lint.lint.as_str(), lint.level))
+            .collect()
+    }
+
+    #[test]
+    fn given_lints_come_last_and_win() {
+        let selection = ProfileSelection {
+            profiles: vec!["layered".to_owned()],
+            config_file: Some(
+                toml::from_str(
+                    r#"
+                    [profile.layered]
+                    clippy = [{ lint = "print_stdout", level = "deny" }]
+                    "#,
+                )
+                .expect("the config file is valid"),
+            ),
+            allow: vec!["clippy::print_stdout".to_owned()],
+            deny: vec!["print_stderr".to_owned()],
+            ..ProfileSelection::default()
+        };
+
+        let profile = selection.load().expect("the profile resolves");
+        assert_eq!(
+            lints(&profile),
+            [
+                ("print_stdout", LintLevel::Allow),
+                ("print_stderr", LintLevel::Deny)
+            ]
+        );
+
+        let adjustments = Package {
+            toolchain: None,
+            targets: Some(manifest::PackageTargets {
+                lib: false,
+                bin: true,
+                publish: false,
+            }),
+        }
+        .adjustments();
+        let adjusted = adjust_profile(&profile, &adjustments, &["print_stdout", "print_stderr"]);
+        assert_eq!(
+            lints(&adjusted),
+            [
+                ("cargo_common_metadata", LintLevel::Allow),
+                ("print_stdout", LintLevel::Allow),
+                ("print_stderr", LintLevel::Deny)
+            ]
+        );
+    }
+
+    #[test]
+    fn no_profile_given() {
+        let err = ProfileSelection::default()
+            .load()
+            .expect_err("there is nothing to resolve");
+        assert_eq!(err.to_string(), "no profile given");
+    }
+}
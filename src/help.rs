@@ -0,0 +1,123 @@
+#[cfg(feature = "native")]
+use std::process::Command;
+use std::{borrow::Cow, collections::HashMap};
+
+use crate::{
+    error::{Error, Result},
+    lint::{LintGroup, LintLevel},
+    source::{LintResponse, Response},
+};
+
+const CLIPPY_PREFIX: &str = "clippy::";
+
+/// The lint name as used in the lint data, e.g. `clippy::needless-return`
+/// becomes `needless_return`.
+fn lint_name(name: &str) -> Option<String> {
+    name.strip_prefix(CLIPPY_PREFIX)
+        .map(|name| name.replace('-', "_"))
+}
+
+fn parse_level(level: &str) -> Result<LintLevel> {
+    match level {
+        "allow" => Ok(LintLevel::Allow),
+        "warn" => Ok(LintLevel::Warn),
+        "deny" | "forbid" => Ok(LintLevel::Deny),
+        _ => Err(Error::InvalidLintData(format!(
+            "unknown lint level {level}"
+        ))),
+    }
+}
+
+fn parse_group(group: &str) -> Option<LintGroup> {
+    Some(match group {
+        "cargo" => LintGroup::Cargo,
+        "complexity" => LintGroup::Complexity,
+        "correctness" => LintGroup::Correctness,
+        "nursery" => LintGroup::Nursery,
+        "pedantic" => LintGroup::Pedantic,
+        "perf" => LintGroup::Perf,
+        "restriction" => LintGroup::Restriction,
+        "style" => LintGroup::Style,
+        "suspicious" => LintGroup::Suspicious,
+        "deprecated" => LintGroup::Deprecated,
+        _ => return None,
+    })
+}
+
+/// Parses the output of `clippy-driver -W help`.
+pub fn parse(output: &str) -> Result<Response<'static>> {
+    let mut groups: HashMap<String, LintGroup> = HashMap::new();
+    let mut lints: Vec<(String, LintLevel, String)> = Vec::new();
+
+    for line in output.lines() {
+        let Some((name, rest)) = line.trim().split_once(char::is_whitespace) else {
+            continue;
+        };
+        let Some(name) = lint_name(name).filter(|name| name != "all") else {
+            continue;
+        };
+        let rest = rest.trim_start();
+
+        if let Some(group) = parse_group(&name) {
+            for member in rest
+                .split(',')
+                .filter_map(|member| lint_name(member.trim()))
+            {
+                groups.insert(member, group.clone());
+            }
+        } else {
+            let (level, docs) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            lints.push((name, parse_level(level)?, docs.trim().to_owned()));
+        }
+    }
+
+    // Sorted by the names with dashes, sort them like `lints.json` does.
+    lints.sort_by(|a, b| a.0.cmp(&b.0));
+
+    if lints.is_empty() {
+        return Err(Error::InvalidLintData(
+            "no clippy lints found in lint help".to_owned(),
+        ));
+    }
+
+    Ok(Response {
+        lints: lints
+            .into_iter()
+            .filter_map(|(id, default_level, docs)| {
+                Some(LintResponse {
+                    group: groups.get(&id)?.clone(),
+                    id: id.into(),
+                    default_level,
+                    version: Cow::Borrowed(""),
+                    docs: docs.into(),
+                    applicability: None,
+                })
+            })
+            .collect(),
+        skipped: Vec::new(),
+    })
+}
+
+/// Runs `clippy-driver -W help` and parses its output.
+#[cfg(feature = "native")]
+pub fn installed() -> Result<Response<'static>> {
+    let output = Command::new("clippy-driver")
+        .args(["-W", "help"])
+        .output()
+        .map_err(|source| Error::Run {
+            program: "clippy-driver",
+            source,
+        })?;
+
+    if !output.status.success() {
+        return Err(Error::Command {
+            command: "clippy-driver -W help",
+            message: String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+        });
+    }
+
+    parse(&String::from_utf8_lossy(&output.stdout)).map_err(|source| Error::Parse {
+        what: "the lint help of clippy-driver".to_owned(),
+        source: Box::new(source),
+    })
+}
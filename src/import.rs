@@ -0,0 +1,279 @@
+use std::collections::BTreeMap;
+
+use toml::{Table, Value};
+
+use crate::{
+    error::{Error, Result},
+    lint::{AllLints, Lint, LintGroup, LintId, LintLevel},
+    manifest::{self, LintEntry, LintTables},
+    profile::{
+        ExceptionsDefinition, ExhaustiveDefinition, GroupDefinition, OverrideDefinition,
+        ProfileDefinition, ToolLintDefinition,
+    },
+    version::RustVersion,
+};
+
+/// A profile read from the lint tables of a manifest.
+#[derive(Debug)]
+pub struct Imported {
+    pub profile: ProfileDefinition,
+    /// Entries that were left out, with the reason.
+    pub skipped: Vec<String>,
+}
+
+/// The level and priority of an entry of a lints table.
+type Entry = (LintLevel, Option<isize>);
+
+/// The value all `values` share, `None` if they differ.
+fn common<T: PartialEq + Copy>(mut values: impl Iterator<Item = T>) -> Option<T> {
+    let first = values.next()?;
+    values.all(|value| value == first).then_some(first)
+}
+
+/// Reads a profile from the `[lints]` tables of `manifest`, or from
+/// `[workspace.lints]` of a workspace root, e.g. to start using profiles
+/// for a hand-written configuration.
+///
+/// Explicit group levels become `groups`, and allowed lints of a group with
+/// another level become its `overrides`. A group whose lints are all listed
+/// individually becomes `exhaustive`, with the most common level as the
+/// level of the group, as long as the other lints share a level. Clippy
+/// lints the lint data does not know are left out. The comments above the
+/// entries become their reasons.
+pub fn from_manifest(manifest: &str, all_lints: &AllLints<'_>) -> Result<Imported> {
+    let document: Table = toml::from_str(manifest).map_err(Error::manifest)?;
+
+    let workspace_lints = document
+        .get("workspace")
+        .and_then(|workspace| workspace.get("lints"))
+        .and_then(Value::as_table);
+    let lints = document.get("lints").and_then(Value::as_table);
+    let prefix = match (workspace_lints, lints) {
+        (Some(_), _) => "workspace.lints",
+        (None, Some(lints)) if lints.get("workspace").and_then(Value::as_bool) == Some(true) => {
+            return Err(Error::InvalidManifest(
+                "the manifest inherits its lints from the workspace, import the workspace root instead"
+                    .to_owned(),
+            ))
+        }
+        (None, Some(_)) => "lints",
+        (None, None) => {
+            return Err(Error::InvalidManifest(
+                "the manifest has no lints table".to_owned(),
+            ))
+        }
+    };
+
+    let msrv = manifest::rust_version(manifest)?;
+    let mut imported = Imported {
+        profile: ProfileDefinition::default(),
+        skipped: Vec::new(),
+    };
+
+    for table in &LintTables::parse(manifest, prefix)?.tables {
+        let entries = table.blocks.iter().flat_map(|block| &block.entries);
+        let definitions = match table.tool.as_str() {
+            "clippy" => {
+                imported.profile.reasons.extend(
+                    entries
+                        .clone()
+                        .filter_map(|entry| Some((entry.name.clone(), entry.comment.clone()?))),
+                );
+                import_clippy(entries.clone(), all_lints, msrv, &mut imported);
+                for definition in &mut imported.profile.clippy {
+                    definition.expires = entries
+                        .clone()
+                        .find(|entry| entry.name == definition.lint)
+                        .and_then(|entry| entry.expires);
+                }
+                continue;
+            }
+            "rust" => &mut imported.profile.rust,
+            "rustdoc" => &mut imported.profile.rustdoc,
+            tool => {
+                imported
+                    .skipped
+                    .push(format!("{tool}: lints of this tool are not supported"));
+                continue;
+            }
+        };
+        definitions.extend(entries.map(|entry| ToolLintDefinition {
+            lint: entry.name.clone(),
+            level: entry.level,
+            priority: entry.priority,
+            reason: entry.comment.clone(),
+            expires: entry.expires,
+        }));
+    }
+
+    Ok(imported)
+}
+
+fn import_clippy<'a, 'e>(
+    entries: impl Iterator<Item = &'e LintEntry>,
+    all_lints: &'a AllLints<'a>,
+    msrv: Option<RustVersion>,
+    imported: &mut Imported,
+) {
+    let mut groups: BTreeMap<LintGroup, Entry> = BTreeMap::new();
+    let mut lints: BTreeMap<LintGroup, Vec<(&'a Lint<'a>, Entry)>> = BTreeMap::new();
+
+    for &LintEntry {
+        name: ref key,
+        level,
+        priority,
+        ..
+    } in entries
+    {
+        let entry = (level, priority);
+        if let Some(lint) = all_lints.get(LintId(key)) {
+            lints
+                .entry(lint.group.clone())
+                .or_default()
+                .push((lint, entry));
+            continue;
+        }
+
+        if key == "all" {
+            imported
+                .skipped
+                .push("clippy::all: set the levels of its groups instead".to_owned());
+            continue;
+        }
+
+        let group = LintGroup::from_name(key);
+        if all_lints.group(&group).next().is_some() {
+            groups.insert(group, entry);
+        } else {
+            imported
+                .skipped
+                .push(format!("clippy::{key}: unknown lint"));
+        }
+    }
+
+    let profile = &mut imported.profile;
+    for (group, &(level, priority)) in &groups {
+        profile.groups.push(GroupDefinition {
+            group: group.clone(),
+            level,
+            priority,
+        });
+
+        let mut allow = Vec::new();
+        for (lint, (lint_level, lint_priority)) in lints.remove(group).unwrap_or_default() {
+            if lint_level == level {
+                continue;
+            }
+            if lint_level == LintLevel::Allow && lint_priority.unwrap_or_default() == 0 {
+                allow.push(lint.id.0.to_owned());
+            } else {
+                profile.clippy.push(ToolLintDefinition {
+                    lint: lint.id.0.to_owned(),
+                    level: lint_level,
+                    priority: lint_priority,
+                    reason: None,
+                    expires: None,
+                });
+            }
+        }
+        if !allow.is_empty() {
+            profile.overrides.push(OverrideDefinition {
+                group: group.clone(),
+                comment: None,
+                allow,
+                priority: None,
+                expires: None,
+            });
+        }
+    }
+
+    for (group, listed) in lints {
+        match exhaustive(&group, &listed, all_lints, msrv) {
+            Some(definition) => profile.exhaustive.push(definition),
+            None => {
+                profile
+                    .clippy
+                    .extend(listed.into_iter().map(|(lint, (level, priority))| {
+                        ToolLintDefinition {
+                            lint: lint.id.0.to_owned(),
+                            level,
+                            priority,
+                            reason: None,
+                            expires: None,
+                        }
+                    }));
+            }
+        }
+    }
+}
+
+/// The group as exhaustive definition, if `listed` covers every lint of the
+/// group that is available in `msrv` and the lints at another level than
+/// the most common one share their level. Priorities have to be the same for
+/// the lints at each level. A single listed lint stays an individual
+/// setting.
+fn exhaustive(
+    group: &LintGroup,
+    listed: &[(&Lint<'_>, Entry)],
+    all_lints: &AllLints<'_>,
+    msrv: Option<RustVersion>,
+) -> Option<ExhaustiveDefinition> {
+    if *group == LintGroup::Deprecated || listed.len() < 2 {
+        return None;
+    }
+    let complete = all_lints
+        .group(group)
+        .filter(|lint| lint.is_available_in(msrv))
+        .all(|lint| listed.iter().any(|&(listed, _)| listed.id == lint.id));
+    if !complete {
+        return None;
+    }
+
+    let count = |level| {
+        listed
+            .iter()
+            .filter(|&&(_, (listed_level, _))| listed_level == level)
+            .count()
+    };
+    let level = [LintLevel::Allow, LintLevel::Warn, LintLevel::Deny]
+        .into_iter()
+        .rev()
+        .max_by_key(|&level| count(level))?;
+
+    let (defaults, exceptions): (Vec<_>, Vec<_>) = listed
+        .iter()
+        .partition(|&&(_, (listed_level, _))| listed_level == level);
+    let exception_level = if exceptions.is_empty() {
+        level
+    } else {
+        common(exceptions.iter().map(|&&(_, (level, _))| level))?
+    };
+    let priority = |entries: &[&(&Lint<'_>, Entry)]| {
+        if entries.is_empty() {
+            return Some(None);
+        }
+        common(entries.iter().map(|&&(_, (_, priority))| priority))
+    };
+    let names = |entries: &[&(&Lint<'_>, Entry)]| -> Vec<String> {
+        entries
+            .iter()
+            .map(|&&(lint, _)| lint.id.0.to_owned())
+            .collect()
+    };
+
+    Some(ExhaustiveDefinition {
+        group: group.clone(),
+        level,
+        comment: None,
+        priority: priority(&defaults)?,
+        exceptions: ExceptionsDefinition {
+            level: exception_level,
+            lints: names(&exceptions),
+            comment: None,
+            priority: priority(&exceptions)?,
+            expires: None,
+        },
+        defaults: names(&defaults),
+        compact: false,
+    })
+}
@@ -0,0 +1,159 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Context as _, Result};
+use clippy_lints::{Lint, LintLevel};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Color, Style, Stylize as _},
+    text::{Line, Span},
+    widgets::{Block, List, ListState, Paragraph},
+    DefaultTerminal,
+};
+
+const HELP: &str =
+    "↑/↓ move  PgUp/PgDn page  space change level  r reset  enter accept  q/esc abort";
+
+const PAGE: u16 = 20;
+
+/// A lint in the review list.
+pub struct Entry<'a> {
+    pub lint: &'a Lint<'a>,
+    /// The level the profile assigns, `None` if it leaves the default.
+    pub profile_level: Option<LintLevel>,
+    /// The level chosen during the review, `None` if unchanged.
+    pub level: Option<LintLevel>,
+}
+
+impl Entry<'_> {
+    fn effective_level(&self) -> LintLevel {
+        self.level
+            .or(self.profile_level)
+            .unwrap_or(self.lint.default_level)
+    }
+
+    fn cycle(&mut self) {
+        let next = match self.effective_level() {
+            LintLevel::Allow => LintLevel::Warn,
+            LintLevel::Warn => LintLevel::Deny,
+            LintLevel::Deny | LintLevel::None => LintLevel::Allow,
+        };
+        self.level = (Some(next) != self.profile_level).then_some(next);
+    }
+
+    fn line(&self) -> Line<'_> {
+        let level = self.effective_level();
+        let color = match level {
+            LintLevel::Allow => Color::Green,
+            LintLevel::Warn => Color::Yellow,
+            LintLevel::Deny => Color::Red,
+            LintLevel::None => Color::DarkGray,
+        };
+
+        let mut level = Span::styled(level.as_str(), Style::new().fg(color));
+        if self.level.is_none() && self.profile_level.is_none() {
+            level = level.dim();
+        }
+
+        Line::from(vec![
+            Span::raw(format!("{:<12} {:<50} ", self.lint.group, self.lint.id)),
+            level,
+            Span::raw(if self.level.is_some() { " *" } else { "" }),
+        ])
+    }
+}
+
+struct App<'a> {
+    title: String,
+    entries: Vec<Entry<'a>>,
+    state: ListState,
+}
+
+impl<'a> App<'a> {
+    fn selected(&mut self) -> Option<&mut Entry<'a>> {
+        let index = self
+            .state
+            .selected()?
+            .min(self.entries.len().saturating_sub(1));
+        self.entries.get_mut(index)
+    }
+
+    fn draw(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        let changed = self
+            .entries
+            .iter()
+            .filter(|entry| entry.level.is_some())
+            .count();
+        let title = format!(" {} ({changed} changed) ", self.title);
+
+        let list = List::new(self.entries.iter().map(Entry::line))
+            .block(Block::bordered().title(title))
+            .highlight_style(Style::new().reversed());
+
+        terminal.draw(|frame| {
+            let [list_area, help_area] =
+                Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+            frame.render_stateful_widget(list, list_area, &mut self.state);
+            frame.render_widget(Paragraph::new(HELP).dim(), help_area);
+        })?;
+
+        Ok(())
+    }
+
+    /// Handles key presses until the review is accepted or aborted.
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<bool> {
+        loop {
+            self.draw(terminal)?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            match key.code {
+                KeyCode::Up | KeyCode::Char('k') => self.state.select_previous(),
+                KeyCode::Down | KeyCode::Char('j') => self.state.select_next(),
+                KeyCode::PageUp => self.state.scroll_up_by(PAGE),
+                KeyCode::PageDown => self.state.scroll_down_by(PAGE),
+                KeyCode::Home | KeyCode::Char('g') => self.state.select_first(),
+                KeyCode::End | KeyCode::Char('G') => self.state.select_last(),
+                KeyCode::Char(' ') => {
+                    if let Some(entry) = self.selected() {
+                        entry.cycle();
+                    }
+                }
+                KeyCode::Char('r') => {
+                    if let Some(entry) = self.selected() {
+                        entry.level = None;
+                    }
+                }
+                KeyCode::Enter => return Ok(true),
+                KeyCode::Esc | KeyCode::Char('q') => return Ok(false),
+                _ => (),
+            }
+        }
+    }
+}
+
+/// Lets the user review and change the level of each lint. Returns the
+/// changed levels by lint name, or `None` if the review was aborted.
+pub fn review(title: &str, entries: Vec<Entry<'_>>) -> Result<Option<BTreeMap<String, LintLevel>>> {
+    let mut app = App {
+        title: title.to_owned(),
+        entries,
+        state: ListState::default().with_selected(Some(0)),
+    };
+
+    let mut terminal = ratatui::try_init().context("failed to initialize terminal")?;
+    let accepted = app.run(&mut terminal);
+    ratatui::try_restore().context("failed to restore terminal")?;
+
+    Ok(accepted?.then(|| {
+        app.entries
+            .iter()
+            .filter_map(|entry| Some((entry.lint.id.0.to_owned(), entry.level?)))
+            .collect()
+    }))
+}
@@ -0,0 +1,108 @@
+//! Generates `[lints]` tables for `Cargo.toml` from clippy's lint data.
+//!
+//! The lint data ([`Response`]) is either downloaded with [`Response::load`],
+//! taken from the snapshot bundled at build time or parsed from a local
+//! `lints.json` with [`Response::parse`]. These are also available as a
+//! [`LintSource`], which can be implemented to load the lint data in other
+//! ways. A [`ProfileDefinition`] is then resolved against it into a
+//! [`Config`], which can be rendered as TOML:
+//!
+//! ```no_run
+//! use clippy_lints::{AllLints, Profile, ProfileDefinition, Response, TomlOptions};
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let json = std::fs::read_to_string("lints.json")?;
+//! let response = Response::parse(&json)?;
+//! let all_lints = AllLints::from_response(&response);
+//!
+//! let profile = ProfileDefinition::builtin(Profile::Publish)?;
+//! let config = profile.to_config(&all_lints, None)?;
+//!
+//! println!("{}", config.to_toml(TomlOptions::default()));
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Instead of a profile, a configuration can also be put together in code
+//! with a [`ConfigBuilder`]. Policies that depend on the project, e.g. on
+//! its features, can be expressed as rules with a [`Condition`] or added
+//! with a [`ProfileProvider`]. The [`generate`] module puts these steps
+//! together like the command line tool: layering profiles, adjusting them to
+//! the targets of a package and resolving them with their rules and
+//! providers.
+//!
+//! Downloading and caching the lint data, reading files and running cargo
+//! and clippy need the default `native` feature. Without it, the generation
+//! core builds for `wasm32-unknown-unknown`, e.g. for a web configurator
+//! that hands the downloaded `lints.json` to [`Response::parse_bytes`].
+//!
+//! Fallible functions return an [`Error`], whose variants tell the kinds of
+//! failures apart, e.g. [`Error::UnknownLint`] or [`Error::ManifestParse`].
+
+mod baseline;
+mod builder;
+#[cfg(feature = "native")]
+mod cache;
+mod changes;
+mod clippy_config;
+mod condition;
+mod config;
+mod date;
+mod error;
+#[cfg(feature = "native")]
+mod fetch;
+mod help;
+mod lint;
+mod lockfile;
+mod profile;
+mod provider;
+#[cfg(feature = "native")]
+mod proxy;
+mod renames;
+mod snapshot;
+mod source;
+mod version;
+
+pub mod apply;
+#[cfg(feature = "native")]
+pub mod audit;
+pub mod diff;
+pub mod generate;
+pub mod import;
+pub mod manifest;
+pub mod migrate;
+pub mod ratchet;
+pub mod validate;
+pub mod workspace;
+
+pub use baseline::{Baseline, BaselineEntry, Finding, BASELINE_NAME};
+pub use builder::ConfigBuilder;
+pub use changes::{KnownLint, KnownLints, LintChanges, RegroupedLint, ReportFormat};
+pub use clippy_config::ClippyConfig;
+pub use condition::Condition;
+pub use config::{
+    table_prefix, Config, ConfigGroup, Exceptions, ExhaustiveGroup, Format, GroupConfig,
+    PrioritySetting, Setting, SingleLintConfig, SortOrder, TomlOptions,
+};
+pub use date::Date;
+pub use error::{BoxError, Error, Result};
+#[cfg(feature = "native")]
+pub use fetch::{RetryPolicy, Timeouts};
+pub use lint::{AllLints, Applicability, Lint, LintFilter, LintGroup, LintId, LintLevel, LintList};
+pub use lockfile::{LockedLint, Lockfile, LOCKFILE_NAME};
+pub use profile::{
+    ConfigFile, ExceptionsDefinition, ExhaustiveDefinition, ExpiredException, GroupDefinition,
+    MemberDefinition, OverrideDefinition, Profile, ProfileDefinition, RenamedLint, RuleDefinition,
+    SelectionDefinition, ToolLintDefinition, UnknownLint,
+};
+pub use provider::{CommandProvider, ProfileProvider, Project};
+pub use renames::RenamedLints;
+pub use snapshot::{Snapshot, SNAPSHOT_NAME};
+#[cfg(feature = "async")]
+pub use source::AsyncLintSource;
+#[cfg(feature = "bundled")]
+pub use source::BundledSource;
+pub use source::{Channel, LintResponse, LintSource, Release, Response, SkippedLint};
+#[cfg(feature = "native")]
+pub use source::{FetchOptions, FileSource, InstalledClippySource};
+pub use version::RustVersion;
@@ -0,0 +1,425 @@
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    fmt, slice,
+};
+
+use clap::{builder::PossibleValue, ValueEnum};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{source::Response, version::RustVersion};
+
+/// A clippy lint group, as reported in the lint data.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LintGroup {
+    Cargo,
+    Complexity,
+    Correctness,
+    Nursery,
+    Pedantic,
+    Perf,
+    Restriction,
+    Style,
+    Suspicious,
+    Deprecated,
+    /// A group added to clippy after this version of the crate. Its lints
+    /// are handled like those of the other groups, but the built-in
+    /// profiles do not mention it.
+    Other(String),
+}
+
+/// The groups known to this version of the crate.
+const KNOWN_GROUPS: &[LintGroup] = &[
+    LintGroup::Cargo,
+    LintGroup::Complexity,
+    LintGroup::Correctness,
+    LintGroup::Nursery,
+    LintGroup::Pedantic,
+    LintGroup::Perf,
+    LintGroup::Restriction,
+    LintGroup::Style,
+    LintGroup::Suspicious,
+    LintGroup::Deprecated,
+];
+
+impl LintGroup {
+    pub fn as_str(&self) -> &str {
+        match *self {
+            Self::Other(ref name) => name,
+            _ => self.known_name().unwrap_or_default(),
+        }
+    }
+
+    /// The name of a known group, `None` for [`LintGroup::Other`].
+    fn known_name(&self) -> Option<&'static str> {
+        Some(match *self {
+            Self::Cargo => "cargo",
+            Self::Complexity => "complexity",
+            Self::Correctness => "correctness",
+            Self::Nursery => "nursery",
+            Self::Pedantic => "pedantic",
+            Self::Perf => "perf",
+            Self::Restriction => "restriction",
+            Self::Style => "style",
+            Self::Suspicious => "suspicious",
+            Self::Deprecated => "deprecated",
+            Self::Other(_) => return None,
+        })
+    }
+
+    /// The group named `name`, [`LintGroup::Other`] for unknown names.
+    pub fn from_name(name: &str) -> Self {
+        KNOWN_GROUPS
+            .iter()
+            .find(|group| group.as_str() == name)
+            .cloned()
+            .unwrap_or_else(|| Self::Other(name.to_owned()))
+    }
+}
+
+impl fmt::Display for LintGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Only the known groups are offered on the command line.
+impl ValueEnum for LintGroup {
+    fn value_variants<'a>() -> &'a [Self] {
+        KNOWN_GROUPS
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        self.known_name().map(PossibleValue::new)
+    }
+}
+
+impl Serialize for LintGroup {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for LintGroup {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = Cow::<str>::deserialize(deserializer)?;
+        Ok(Self::from_name(&name))
+    }
+}
+
+/// The level of a lint. `None` only appears as the default level of
+/// deprecated lints in the lint data.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+    None,
+}
+
+impl LintLevel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Allow => "allow",
+            Self::Warn => "warn",
+            Self::Deny => "deny",
+            Self::None => "none",
+        }
+    }
+
+    /// The command line flag setting this level, if any.
+    pub fn flag(self) -> Option<&'static str> {
+        match self {
+            Self::Allow => Some("-A"),
+            Self::Warn => Some("-W"),
+            Self::Deny => Some("-D"),
+            Self::None => None,
+        }
+    }
+}
+
+/// How reliable the suggestions of a lint are, as reported in the lint data.
+/// Accepts the names of the lint data, e.g. `MachineApplicable`, as well.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Applicability {
+    /// The suggestions can be applied automatically, e.g. by `cargo clippy
+    /// --fix`.
+    #[serde(alias = "MachineApplicable")]
+    MachineApplicable,
+    #[serde(alias = "MaybeIncorrect")]
+    MaybeIncorrect,
+    #[serde(alias = "HasPlaceholders")]
+    HasPlaceholders,
+    /// Not known, e.g. because the lint makes no suggestions.
+    #[serde(alias = "Unspecified", alias = "Unresolved")]
+    Unspecified,
+}
+
+impl Applicability {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::MachineApplicable => "machine-applicable",
+            Self::MaybeIncorrect => "maybe-incorrect",
+            Self::HasPlaceholders => "has-placeholders",
+            Self::Unspecified => "unspecified",
+        }
+    }
+}
+
+/// A single clippy lint with the group it belongs to.
+#[derive(Debug)]
+pub struct Lint<'a> {
+    pub id: LintId<'a>,
+    pub group: LintGroup,
+    pub default_level: LintLevel,
+    /// The release that introduced the lint, `None` if the lint data
+    /// contains no parsable version.
+    pub version: Option<RustVersion>,
+    /// `None` if the lint data does not report it.
+    pub applicability: Option<Applicability>,
+    /// The documentation of the lint, in markdown.
+    pub docs: &'a str,
+}
+
+impl<'a> Lint<'a> {
+    /// Whether the lint is known to a toolchain of version `msrv`. Lints
+    /// without a version are assumed to be available everywhere.
+    pub fn is_available_in(&self, msrv: Option<RustVersion>) -> bool {
+        match (msrv, self.version) {
+            (Some(msrv), Some(version)) => version <= msrv,
+            _ => true,
+        }
+    }
+
+    /// The reason given for the deprecation of the lint, which usually names
+    /// its replacement. `None` if the lint is not deprecated or the lint data
+    /// gives no reason.
+    pub fn deprecation_reason(&self) -> Option<&str> {
+        if self.group != LintGroup::Deprecated {
+            return None;
+        }
+
+        let (_, reason) = self.docs.split_once("Deprecation reason")?;
+        let reason = reason
+            .trim_start_matches('#')
+            .trim()
+            .split("\n#")
+            .next()
+            .unwrap_or_default()
+            .trim();
+
+        (!reason.is_empty()).then_some(reason)
+    }
+
+    /// The first paragraph of the documentation, without the `What it does`
+    /// heading that clippy's documentation starts with. It may span several
+    /// lines.
+    pub fn summary(&self) -> Option<&'a str> {
+        let docs = self.docs.trim_start();
+        let docs = match docs.split_once('\n') {
+            Some((heading, rest)) if heading.contains("What it does") => rest,
+            _ => docs,
+        };
+
+        let summary = docs
+            .trim_start()
+            .split("\n\n")
+            .next()
+            .unwrap_or_default()
+            .trim();
+
+        (!summary.is_empty()).then_some(summary)
+    }
+}
+
+/// The name of a lint, without the `clippy::` prefix.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct LintId<'a>(pub &'a str);
+
+impl From<&'static str> for LintId<'static> {
+    fn from(value: &'static str) -> Self {
+        Self(value)
+    }
+}
+
+impl fmt::Display for LintId<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+pub struct LintList<'a>(pub Vec<LintId<'a>>);
+
+impl<'a> From<Vec<&'a str>> for LintList<'a> {
+    fn from(value: Vec<&'a str>) -> Self {
+        Self(value.into_iter().map(|s| LintId(s)).collect())
+    }
+}
+
+/// The Levenshtein distance between `a` and `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i.saturating_add(1)];
+        for (j, &b) in b.iter().enumerate() {
+            let at = |row: &[usize], index: usize| row.get(index).copied().unwrap_or_default();
+            let substitution = at(&previous, j).saturating_add(usize::from(a != b));
+            let deletion = at(&previous, j.saturating_add(1)).saturating_add(1);
+            let insertion = at(&current, j).saturating_add(1);
+            current.push(substitution.min(deletion).min(insertion));
+        }
+        previous = current;
+    }
+
+    previous.last().copied().unwrap_or_default()
+}
+
+/// All clippy lints known to the lint data, borrowing from a [`Response`].
+/// Indexed by name and by group, as profiles look up lints a lot.
+#[derive(Debug)]
+pub struct AllLints<'a> {
+    lints: Vec<Lint<'a>>,
+    /// The position of each lint in `lints`.
+    by_id: HashMap<&'a str, usize>,
+    /// The positions of the lints of each group in `lints`.
+    by_group: BTreeMap<LintGroup, Vec<usize>>,
+}
+
+impl<'a> AllLints<'a> {
+    pub fn from_response(response: &'a Response) -> Self {
+        let lints: Vec<Lint<'a>> = response
+            .lints
+            .iter()
+            .map(|lint| Lint {
+                id: LintId(&lint.id),
+                group: lint.group.clone(),
+                default_level: lint.default_level,
+                version: RustVersion::from_lint_data(&lint.version),
+                applicability: lint.applicability,
+                docs: &lint.docs,
+            })
+            .collect();
+
+        let mut by_id = HashMap::with_capacity(lints.len());
+        let mut by_group: BTreeMap<LintGroup, Vec<usize>> = BTreeMap::new();
+        for (position, lint) in lints.iter().enumerate() {
+            // The first of several lints with the same name wins, as with a
+            // linear search.
+            by_id.entry(lint.id.0).or_insert(position);
+            by_group
+                .entry(lint.group.clone())
+                .or_default()
+                .push(position);
+        }
+
+        if tracing::enabled!(tracing::Level::DEBUG) {
+            let counts: Vec<String> = by_group
+                .iter()
+                .map(|(group, lints)| format!("{group} {}", lints.len()))
+                .collect();
+            tracing::debug!("loaded {} lints: {}", lints.len(), counts.join(", "));
+        }
+
+        Self {
+            lints,
+            by_id,
+            by_group,
+        }
+    }
+
+    /// All lints, in the order of the lint data.
+    pub fn iter(&self) -> slice::Iter<'_, Lint<'a>> {
+        self.lints.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lints.is_empty()
+    }
+
+    pub fn get(&self, id: LintId<'_>) -> Option<&Lint<'a>> {
+        self.by_id
+            .get(id.0)
+            .and_then(|&position| self.lints.get(position))
+    }
+
+    /// The lints of `group`, in the order of the lint data.
+    pub fn group(&self, group: &LintGroup) -> impl Iterator<Item = &Lint<'a>> {
+        self.by_group
+            .get(group)
+            .map_or(&[][..], Vec::as_slice)
+            .iter()
+            .filter_map(|&position| self.lints.get(position))
+    }
+
+    /// The lints matching `filter`, in the order of the lint data.
+    pub fn filter<'b>(&'b self, filter: &'b LintFilter) -> impl Iterator<Item = &'b Lint<'a>> {
+        self.lints.iter().filter(|lint| filter.matches(lint))
+    }
+
+    /// The lints whose name or documentation contains every word of `query`,
+    /// ignoring case. Lints whose name contains every word come first, the
+    /// rest keeps the order of the lint data.
+    pub fn search(&self, query: &str) -> Vec<&Lint<'a>> {
+        let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        let contains_all = |text: &str| {
+            let text = text.to_lowercase();
+            words.iter().all(|word| text.contains(word.as_str()))
+        };
+
+        let (mut by_name, by_docs): (Vec<&Lint<'a>>, Vec<&Lint<'a>>) = self
+            .lints
+            .iter()
+            .filter(|lint| contains_all(lint.id.0) || contains_all(lint.docs))
+            .partition(|lint| contains_all(lint.id.0));
+        by_name.extend(by_docs);
+        by_name
+    }
+
+    /// The name of the lint closest to `name`, if it differs in at most a
+    /// third of the characters. Used to suggest fixes for misspelled names.
+    pub fn closest(&self, name: &str) -> Option<&'a str> {
+        self.lints
+            .iter()
+            .map(|lint| (edit_distance(name, lint.id.0), lint.id.0))
+            .filter(|&(distance, _)| distance.saturating_mul(3) <= name.len().max(3))
+            .min_by_key(|&(distance, _)| distance)
+            .map(|(_, lint)| lint)
+    }
+}
+
+impl<'s, 'a> IntoIterator for &'s AllLints<'a> {
+    type Item = &'s Lint<'a>;
+    type IntoIter = slice::Iter<'s, Lint<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Selects clippy lints by their properties instead of their names, see
+/// [`AllLints::filter`]. An empty list matches every lint.
+#[derive(Clone, Debug, Default)]
+pub struct LintFilter {
+    pub groups: Vec<LintGroup>,
+    /// Lints without an applicability in the lint data never match a
+    /// non-empty list.
+    pub applicability: Vec<Applicability>,
+}
+
+impl LintFilter {
+    pub fn matches(&self, lint: &Lint<'_>) -> bool {
+        (self.groups.is_empty() || self.groups.contains(&lint.group))
+            && (self.applicability.is_empty()
+                || lint
+                    .applicability
+                    .is_some_and(|applicability| self.applicability.contains(&applicability)))
+    }
+}
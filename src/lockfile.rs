@@ -0,0 +1,217 @@
+#[cfg(feature = "native")]
+use std::{fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "native")]
+use crate::error::Error;
+use crate::{
+    changes::{KnownLint, KnownLints},
+    error::Result,
+    lint::{AllLints, Applicability, LintGroup, LintLevel},
+    source::{LintResponse, LintSource, Response},
+};
+
+/// The file name of the lockfile, next to the manifest.
+pub const LOCKFILE_NAME: &str = "clippy-lints.lock";
+
+#[cfg(feature = "native")]
+const HEADER: &str =
+    "# Lint data used by clippy-lints, refresh it with `cargo clippy-lints update`.\n\
+                      # Do not edit this file by hand.\n\n";
+
+/// A lint as recorded in the lockfile.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct LockedLint {
+    pub id: String,
+    pub group: LintGroup,
+    pub level: LintLevel,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub version: String,
+    /// The first paragraph of the documentation, on a single line. The full
+    /// documentation is not recorded.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub summary: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub applicability: Option<Applicability>,
+}
+
+/// The lint data pinned in `clippy-lints.lock`, so generating the
+/// configuration gives the same result until the lockfile is updated.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Lockfile {
+    /// The newest release that introduced one of the lints, i.e. the clippy
+    /// version the lint data is from. `None` if the lint data has no
+    /// versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clippy_version: Option<String>,
+    /// A hash of the lints, see [`Lockfile::compute_hash`].
+    pub hash: String,
+    #[serde(rename = "lint")]
+    pub lints: Vec<LockedLint>,
+}
+
+impl Lockfile {
+    pub fn from_response(response: &Response) -> Self {
+        let all_lints = AllLints::from_response(response);
+
+        let lints: Vec<LockedLint> = all_lints
+            .iter()
+            .zip(&response.lints)
+            .map(|(lint, data)| LockedLint {
+                id: data.id.clone().into_owned(),
+                group: data.group.clone(),
+                level: data.default_level,
+                version: data.version.clone().into_owned(),
+                summary: lint
+                    .summary()
+                    .map(|summary| summary.split_whitespace().collect::<Vec<_>>().join(" "))
+                    .unwrap_or_default(),
+                applicability: data.applicability,
+            })
+            .collect();
+
+        Self {
+            clippy_version: all_lints
+                .iter()
+                .filter_map(|lint| lint.version)
+                .max()
+                .map(|version| version.to_string()),
+            hash: Self::compute_hash(&lints),
+            lints,
+        }
+    }
+
+    /// A FNV-1a hash over the ids, groups, levels, versions and
+    /// applicabilities of the lints. The summaries are left out, they do not
+    /// change the configuration. Missing applicabilities are left out as
+    /// well, so that lockfiles written without them keep their hash.
+    pub fn compute_hash(lints: &[LockedLint]) -> String {
+        const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0100_0000_01b3;
+
+        let mut hash = OFFSET_BASIS;
+        for lint in lints {
+            for field in [
+                lint.id.as_str(),
+                lint.group.as_str(),
+                lint.level.as_str(),
+                lint.version.as_str(),
+            ]
+            .into_iter()
+            .chain(lint.applicability.map(Applicability::as_str))
+            {
+                for byte in field.bytes().chain([b'\n']) {
+                    hash = (hash ^ u64::from(byte)).wrapping_mul(PRIME);
+                }
+            }
+        }
+
+        format!("{hash:016x}")
+    }
+
+    /// Reads the lockfile at `path`, `None` if it does not exist. Fails if
+    /// the lints do not match the recorded hash.
+    #[cfg(feature = "native")]
+    pub fn read(path: &Path) -> Result<Option<Self>> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(Error::io("read lockfile", path)(err)),
+        };
+
+        let lockfile: Self = toml::from_str(&content)
+            .map_err(Error::parse(format!("lockfile {}", path.display())))?;
+
+        if Self::compute_hash(&lockfile.lints) != lockfile.hash {
+            return Err(Error::LockfileHash(path.to_owned()));
+        }
+
+        Ok(Some(lockfile))
+    }
+
+    #[cfg(feature = "native")]
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let content = format!(
+            "{HEADER}{}",
+            toml::to_string(self).map_err(Error::serialize("lockfile"))?
+        );
+        fs::write(path, content).map_err(Error::io("write lockfile", path))
+    }
+
+    /// The recorded lint data. The documentation of the lints only consists
+    /// of their summaries.
+    pub fn to_response(&self) -> Response<'static> {
+        Response {
+            lints: self
+                .lints
+                .iter()
+                .map(|lint| LintResponse {
+                    id: lint.id.clone().into(),
+                    group: lint.group.clone(),
+                    default_level: lint.level,
+                    version: lint.version.clone().into(),
+                    docs: lint.summary.clone().into(),
+                    applicability: lint.applicability,
+                })
+                .collect(),
+            skipped: Vec::new(),
+        }
+    }
+
+    pub fn known_lints(&self) -> KnownLints {
+        KnownLints(
+            self.lints
+                .iter()
+                .map(|lint| KnownLint {
+                    id: lint.id.clone(),
+                    group: lint.group.clone(),
+                })
+                .collect(),
+        )
+    }
+}
+
+/// The pinned lint data, see [`Lockfile::to_response`].
+impl LintSource for Lockfile {
+    fn fetch<'a>(&self, _buffer: &'a mut String) -> Result<Response<'a>> {
+        Ok(self.to_response())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lint(summary: &str, applicability: Option<Applicability>) -> LockedLint {
+        LockedLint {
+            id: "similar_names".to_owned(),
+            group: LintGroup::Pedantic,
+            level: LintLevel::Allow,
+            version: "1.50.0".to_owned(),
+            summary: summary.to_owned(),
+            applicability,
+        }
+    }
+
+    #[test]
+    fn hash_is_stable() {
+        assert_eq!(Lockfile::compute_hash(&[]), "cbf29ce484222325");
+        assert_eq!(
+            Lockfile::compute_hash(&[lint("", None)]),
+            "c224cb79eba61af0"
+        );
+    }
+
+    #[test]
+    fn hash_leaves_out_the_summary() {
+        assert_eq!(
+            Lockfile::compute_hash(&[lint("", None)]),
+            Lockfile::compute_hash(&[lint("checks for similar names", None)])
+        );
+        assert_ne!(
+            Lockfile::compute_hash(&[lint("", None)]),
+            Lockfile::compute_hash(&[lint("", Some(Applicability::MachineApplicable))])
+        );
+    }
+}
@@ -1,554 +1,3310 @@
-use std::fmt::{self, Write as _};
-
-use anyhow::{anyhow, Result};
-use clap::{Parser, ValueEnum};
-use serde::Deserialize;
-
-#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
-#[serde(rename_all = "lowercase")]
-enum LintGroup {
-    Cargo,
-    Complexity,
-    Correctness,
-    Nursery,
-    Pedantic,
-    Perf,
-    Restriction,
-    Style,
-    Suspicious,
-    Deprecated,
+use std::{
+    borrow::Cow,
+    cmp::Reverse,
+    error::Error,
+    ffi::{OsStr, OsString},
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    process::{self, ExitCode},
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Context as _, Result};
+use clap::{
+    builder::{BoolishValueParser, PossibleValue, StringValueParser, TypedValueParser},
+    ArgAction, CommandFactory as _, Parser, Subcommand, ValueEnum,
+};
+use clap_complete::Shell;
+#[cfg(feature = "bundled")]
+use clippy_lints::BundledSource;
+use clippy_lints::{
+    apply::{self, Merge},
+    audit, diff,
+    generate::{self, GenerateOptions, Manifest, ProfileSelection},
+    import, manifest, migrate, ratchet, table_prefix, validate, workspace, AllLints, Baseline,
+    Channel, Config, ConfigFile, Date, FetchOptions, FileSource, Finding, Format,
+    InstalledClippySource, KnownLints, LintChanges, LintGroup, LintLevel, LintSource, Lockfile,
+    Profile, ProfileDefinition, Project, Release, RenamedLints, ReportFormat, Response,
+    RetryPolicy, RustVersion, Snapshot, SortOrder, Timeouts, TomlOptions, BASELINE_NAME,
+    LOCKFILE_NAME, SNAPSHOT_NAME,
+};
+use serde::Serialize;
+
+mod color;
+#[cfg(feature = "interactive")]
+mod interactive;
+mod logging;
+
+use color::ColorChoice;
+
+const EXIT_CODES: &str = "\
+Exit status:
+  0  success, or no differences or problems found by diff, hook, verify,
+     validate and baseline check
+  1  diff, hook or verify found differences, validate found problems, or
+     baseline check found new findings
+  2  invalid command line arguments or profiles
+  3  the lint data could not be loaded
+  4  any other error";
+
+/// Generates the `[lints]` table of a Cargo manifest from a profile of
+/// clippy lints.
+#[derive(Parser, Debug)]
+#[command(
+    bin_name = "cargo clippy-lints",
+    args_conflicts_with_subcommands = true,
+    after_help = EXIT_CODES
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    args: Option<Args>,
+
+    #[command(flatten)]
+    source: SourceArgs,
+
+    #[command(flatten)]
+    output: OutputArgs,
+
+    /// When to color the output. `auto` colors output to a terminal unless
+    /// `NO_COLOR` is set
+    #[arg(long, global = true, value_enum, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+
+    /// Log what is done and why, e.g. the URL the lint data is fetched from
+    /// and cache hits. Twice to also log which rule of the profile sets each
+    /// lint
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    verbose: u8,
+
+    /// Print only errors, no warnings or status messages
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
 }
 
-#[derive(Clone, Copy, Debug, ValueEnum)]
-enum Profile {
-    Publish,
-    Personal,
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print the generated configuration (the default)
+    Generate(GenerateArgs),
+    /// Show how the lint tables of a manifest differ from the generated ones
+    Diff(DiffArgs),
+    /// Write the generated lint tables into a manifest
+    Apply(ApplyArgs),
+    /// Check a manifest for drift from the profile, for pre-commit hooks:
+    /// silent if it matches, one line per difference otherwise
+    Hook(HookArgs),
+    /// Report lints added or removed since the previous run of this command
+    NewLints(SourceArgs),
+    /// Download the lint data, pin it in `clippy-lints.lock` next to the
+    /// workspace root Cargo.toml and report the changes to the previously
+    /// pinned lints
+    Update(UpdateArgs),
+    /// Write the lint data to `lints-snapshot.json` next to the workspace
+    /// root Cargo.toml, to commit it as a record of the lints. `--offline`
+    /// prefers it to the bundled lint data
+    Snapshot(SnapshotArgs),
+    /// Review the lints of a profile in the terminal and change their levels
+    /// before generating the configuration
+    #[cfg(feature = "interactive")]
+    Interactive(InteractiveArgs),
+    /// List every lint with the level the profile assigns
+    List(ListArgs),
+    /// Find lints by words in their names and documentation, e.g. to decide
+    /// which restriction lints to enable
+    Search(SearchArgs),
+    /// List every clippy lint whose effective level differs between two
+    /// profiles
+    Compare(CompareArgs),
+    /// Check that a file matches the generated configuration, e.g. a golden
+    /// file kept in the repository
+    Verify(VerifyArgs),
+    /// Check the clippy lints table of a manifest against the lint data,
+    /// independent of any profile
+    Validate(ValidateArgs),
+    /// Run clippy with the allowed lints of the profile raised to warn and
+    /// report the ones that never fire
+    AuditAllows(AuditArgs),
+    /// Run clippy with the generated configuration and count the findings
+    /// per lint
+    Audit(AuditArgs),
+    /// Run clippy with the lints the profile leaves allowed raised to warn
+    /// and report the ones without findings, to promote them to warn
+    Ratchet(RatchetArgs),
+    /// Record the current clippy findings in `clippy-baseline.toml` next to
+    /// the workspace root Cargo.toml, or check for findings not recorded
+    /// there, to adopt a stricter profile without fixing everything at once
+    #[command(subcommand)]
+    Baseline(BaselineCommand),
+    /// Write a config file with a built-in profile to customize
+    Init(InitArgs),
+    /// Write a config file with a profile equivalent to the lint tables of
+    /// an existing manifest
+    Import(ImportArgs),
+    /// Report `allow` attributes for clippy lints in the source code and
+    /// move crate-level ones into the manifest
+    Migrate(MigrateArgs),
+    /// Print shell completions
+    Completions { shell: Shell },
 }
 
-#[derive(Parser, Debug)]
-struct Args {
+#[derive(clap::Args, Debug)]
+struct AuditArgs {
+    #[command(flatten)]
+    args: Args,
+
+    #[command(flatten)]
+    source: SourceArgs,
+
+    /// Path to the Cargo.toml manifest to run clippy on. Defaults to the
+    /// nearest one in the current directory or its parents
     #[arg(long)]
-    profile: Profile,
+    manifest_path: Option<PathBuf>,
+}
+
+#[derive(clap::Args, Debug)]
+struct RatchetArgs {
+    #[command(flatten)]
+    audit: AuditArgs,
 
+    /// Only consider the lints of this group. Can be repeated, defaults to
+    /// every group but restriction, whose lints partly contradict each
+    /// other
+    #[arg(long, value_enum)]
+    group: Vec<LintGroup>,
+
+    /// Add the lints without findings to the profile in the config file, at
+    /// level warn
     #[arg(long)]
-    workspace: bool,
+    apply: bool,
 }
 
-impl LintGroup {
-    fn as_str(self) -> &'static str {
-        match self {
-            Self::Cargo => "cargo",
-            Self::Complexity => "complexity",
-            Self::Correctness => "correctness",
-            Self::Nursery => "nursery",
-            Self::Pedantic => "pedantic",
-            Self::Perf => "perf",
-            Self::Restriction => "restriction",
-            Self::Style => "style",
-            Self::Suspicious => "suspicious",
-            Self::Deprecated => "deprecated",
-        }
-    }
+#[derive(Subcommand, Debug)]
+enum BaselineCommand {
+    /// Run clippy with the generated configuration and record its findings
+    /// in the baseline, replacing the previous ones
+    Create(BaselineArgs),
+    /// Run clippy with the generated configuration and fail if it finds
+    /// anything not recorded in the baseline
+    Check(BaselineArgs),
 }
 
-impl fmt::Display for LintGroup {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.as_str())
+#[derive(clap::Args, Debug)]
+struct BaselineArgs {
+    #[command(flatten)]
+    audit: AuditArgs,
+
+    /// Path to the baseline. Defaults to `clippy-baseline.toml` next to the
+    /// workspace root Cargo.toml
+    #[arg(long)]
+    baseline: Option<PathBuf>,
+}
+
+impl BaselineArgs {
+    fn path(&self) -> PathBuf {
+        self.baseline
+            .clone()
+            .unwrap_or_else(|| project_file(self.audit.manifest_path.as_deref(), BASELINE_NAME))
+    }
+
+    /// Runs clippy like `audit`, with all lints capped at warn so that denied
+    /// lints do not stop the build before every package is checked.
+    fn findings(&self) -> Result<Vec<Finding>> {
+        let (manifest, args, msrv) = self.audit.package()?;
+        let manifest_path = manifest.path;
+
+        let profile = load_profile(&args)?;
+        let profile = package_profile(&profile, &args);
+        let mut buffer = String::new();
+        let response = self
+            .audit
+            .source
+            .load_response(Some(&manifest_path), &mut buffer)?;
+        let all_lints = AllLints::from_response(&response);
+        let config = resolve_config(&args, &profile, &all_lints, msrv, None)?;
+
+        let mut flags = vec!["--cap-lints=warn".to_owned()];
+        flags.extend(config.to_flags().split_whitespace().map(str::to_owned));
+        Ok(audit::findings(&audit::run_clippy(&manifest_path, &flags)?))
     }
 }
 
-#[derive(Clone, Copy, Debug, Deserialize)]
-#[serde(rename_all = "lowercase")]
-enum LintLevel {
-    Allow,
-    Warn,
-    Deny,
-    None,
+#[derive(clap::Args, Debug)]
+struct UpdateArgs {
+    #[command(flatten)]
+    source: SourceArgs,
+
+    /// Format of the change report
+    #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+    format: ReportFormat,
 }
 
-impl LintLevel {
-    fn as_str(self) -> &'static str {
-        match self {
-            Self::Allow => "allow",
-            Self::Warn => "warn",
-            Self::Deny => "deny",
-            Self::None => "none",
-        }
-    }
+#[derive(clap::Args, Debug)]
+struct SnapshotArgs {
+    #[command(flatten)]
+    source: SourceArgs,
+
+    /// File to write. Defaults to `lints-snapshot.json` next to the workspace
+    /// root Cargo.toml
+    #[arg(long)]
+    out: Option<PathBuf>,
 }
 
-#[derive(Debug)]
-struct Lint<'a> {
-    id: LintId<'a>,
-    group: LintGroup,
+#[derive(clap::Args, Debug)]
+struct MigrateArgs {
+    /// Path to the Cargo.toml manifest of the package. Defaults to the
+    /// nearest one in the current directory or its parents
+    #[arg(long)]
+    manifest_path: Option<PathBuf>,
+
+    /// Move the lints of crate-level attributes into `[lints.clippy]` and
+    /// remove them from the attributes
+    #[arg(long)]
+    fix: bool,
 }
 
-#[derive(Debug, Deserialize)]
-#[expect(dead_code, reason = "this is an external data definition")]
-struct LintResponse {
-    id: String,
-    group: LintGroup,
-    #[serde(rename = "level")]
-    default_level: LintLevel,
-    version: String,
+#[derive(clap::Args, Debug)]
+struct InitArgs {
+    /// The built-in profile to start from
+    #[arg(long, value_enum, default_value_t = Profile::Publish)]
+    profile: Profile,
+
+    /// Name of the profile in the config file. Defaults to the name of the
+    /// built-in profile
+    #[arg(long)]
+    name: Option<String>,
+
+    /// File to write. Defaults to `clippy-lints.toml` next to the workspace
+    /// root Cargo.toml
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+
+    /// Overwrite the file if it already exists
+    #[arg(long)]
+    force: bool,
 }
 
-#[derive(Clone, Copy, Debug)]
-enum PrioritySetting {
-    Explicit(isize),
-    Unspecified,
+#[derive(clap::Args, Debug)]
+struct ImportArgs {
+    #[command(flatten)]
+    source: SourceArgs,
+
+    /// Path to the Cargo.toml manifest to import. Defaults to the nearest one
+    /// in the current directory or its parents
+    #[arg(long)]
+    manifest_path: Option<PathBuf>,
+
+    /// Name of the profile in the config file
+    #[arg(long, default_value = "imported")]
+    name: String,
+
+    /// File to write the config file to, `-` for stdout
+    #[arg(long, short, default_value = "-")]
+    output: PathBuf,
+
+    /// Overwrite the output file if it already exists
+    #[arg(long)]
+    force: bool,
 }
 
-impl From<Option<isize>> for PrioritySetting {
-    fn from(value: Option<isize>) -> Self {
-        match value {
-            Some(i) => Self::Explicit(i),
-            None => Self::Unspecified,
-        }
-    }
+#[derive(clap::Args, Debug)]
+struct ValidateArgs {
+    #[command(flatten)]
+    source: SourceArgs,
+
+    /// Path to the Cargo.toml manifest. Defaults to the nearest one in the
+    /// current directory or its parents
+    #[arg(long)]
+    manifest_path: Option<PathBuf>,
+
+    /// Format of the problems
+    #[arg(long, value_enum, default_value_t = ProblemFormat::Text)]
+    format: ProblemFormat,
 }
 
-#[derive(Debug, Deserialize, PartialEq, Eq)]
-struct LintId<'a>(&'a str);
+#[derive(clap::Args, Debug)]
+struct VerifyArgs {
+    #[command(flatten)]
+    args: Args,
 
-impl From<&'static str> for LintId<'static> {
-    fn from(value: &'static str) -> Self {
-        Self(value)
-    }
+    #[command(flatten)]
+    source: SourceArgs,
+
+    /// Format of the file
+    #[arg(long, value_enum, default_value_t = Format::Toml)]
+    format: Format,
+
+    #[command(flatten)]
+    layout: LayoutArgs,
+
+    #[command(flatten)]
+    frame: FrameArgs,
+
+    /// Also fail if exhaustively listed groups contain lints the profile
+    /// lists neither as exception nor as default, e.g. newly released ones
+    #[arg(long)]
+    require_triage: bool,
+
+    /// What to do about exceptions of the profile past their `expires` date
+    #[arg(long, value_enum, default_value_t = Expired::Fail)]
+    expired: Expired,
+
+    /// The file to compare with the generated configuration
+    file: PathBuf,
 }
 
-impl fmt::Display for LintId<'_> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.0)
-    }
+#[derive(clap::Args, Debug)]
+struct GenerateArgs {
+    #[command(flatten)]
+    args: Args,
+
+    #[command(flatten)]
+    source: SourceArgs,
+
+    #[command(flatten)]
+    output: OutputArgs,
 }
 
-struct LintList<'a>(Vec<LintId<'a>>);
+#[cfg(feature = "interactive")]
+#[derive(clap::Args, Debug)]
+struct InteractiveArgs {
+    #[command(flatten)]
+    args: Args,
 
-impl<'a> From<Vec<&'a str>> for LintList<'a> {
-    fn from(value: Vec<&'a str>) -> Self {
-        Self(value.into_iter().map(|s| LintId(s)).collect())
-    }
+    #[command(flatten)]
+    source: SourceArgs,
+
+    #[command(flatten)]
+    output: OutputArgs,
+
+    /// Save the profile including the changes as a config file, under the
+    /// names given by --profile joined by `+`
+    #[arg(long)]
+    save: Option<PathBuf>,
+}
+
+#[derive(clap::Args, Debug)]
+struct ListArgs {
+    #[command(flatten)]
+    args: Args,
+
+    #[command(flatten)]
+    source: SourceArgs,
+
+    /// Only list the lints of this group
+    #[arg(long, value_enum)]
+    group: Option<LintGroup>,
+
+    /// Format of the list
+    #[arg(long, value_enum, default_value_t = ListFormat::Table)]
+    format: ListFormat,
+}
+
+#[derive(clap::Args, Debug)]
+struct SearchArgs {
+    #[command(flatten)]
+    args: Args,
+
+    #[command(flatten)]
+    source: SourceArgs,
+
+    /// Words that the name or documentation of a lint must all contain,
+    /// ignoring case
+    #[arg(required = true)]
+    keywords: Vec<String>,
+
+    /// Only search the lints of this group
+    #[arg(long, value_enum)]
+    group: Option<LintGroup>,
+
+    /// Format of the results
+    #[arg(long, value_enum, default_value_t = ListFormat::Table)]
+    format: ListFormat,
+}
+
+#[derive(clap::Args, Debug)]
+struct CompareArgs {
+    #[command(flatten)]
+    args: Args,
+
+    #[command(flatten)]
+    source: SourceArgs,
+
+    /// The profile to compare with, layered like --profile. The other
+    /// options apply to both profiles
+    #[arg(
+        long,
+        required = true,
+        value_delimiter = ',',
+        value_parser = ProfileNameParser,
+        hide_possible_values = true
+    )]
+    against: Vec<String>,
+
+    /// Format of the list
+    #[arg(long, value_enum, default_value_t = ListFormat::Table)]
+    format: ListFormat,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum ListFormat {
+    /// Aligned columns for reading
+    Table,
+    /// An array of objects, for consumption by other tools
+    Json,
+}
+
+/// A row of the `list` output.
+#[derive(Serialize)]
+struct ListEntry<'a> {
+    lint: &'a str,
+    group: LintGroup,
+    default_level: LintLevel,
+    /// `None` if the profile leaves the lint at its default level.
+    level: Option<LintLevel>,
 }
 
-#[derive(Debug)]
-struct SingleLintConfig<'a> {
-    lint: &'a LintId<'a>,
-    priority: PrioritySetting,
+/// A row of the `search` output.
+#[derive(Serialize)]
+struct SearchEntry<'a> {
+    lint: &'a str,
+    group: LintGroup,
+    /// The level the profile gives the lint, or its default level.
     level: LintLevel,
+    /// The first paragraph of the documentation, on a single line.
+    summary: String,
 }
 
-#[derive(Debug)]
-struct GroupConfig {
+/// A row of the `compare` output.
+#[derive(Serialize)]
+struct CompareEntry<'a> {
+    lint: &'a str,
     group: LintGroup,
-    priority: PrioritySetting,
+    /// The effective level with --profile.
     level: LintLevel,
+    /// The effective level with --against.
+    against: LintLevel,
 }
 
-#[derive(Debug)]
-enum Setting<'a> {
-    Single(SingleLintConfig<'a>),
-    Group(GroupConfig),
+#[derive(clap::Args, Debug)]
+struct OutputArgs {
+    /// File to write the configuration to, `-` for stdout
+    #[arg(long, short, default_value = "-")]
+    output: PathBuf,
+
+    /// Overwrite the output file if it already exists
+    #[arg(long)]
+    force: bool,
+
+    /// Format of the generated configuration
+    #[arg(
+        long,
+        value_enum,
+        env = "CLIPPY_LINTS_FORMAT",
+        default_value_t = Format::Toml
+    )]
+    format: Format,
+
+    /// What to generate
+    #[arg(long, value_enum, default_value_t = Emit::Lints)]
+    emit: Emit,
+
+    #[command(flatten)]
+    layout: LayoutArgs,
+
+    #[command(flatten)]
+    frame: FrameArgs,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-enum ExhaustiveGroupClassification {
-    Default,
-    Exception,
+// How the generated configuration is laid out.
+#[derive(clap::Args, Debug)]
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "these are independent command line flags"
+)]
+struct LayoutArgs {
+    /// Order of the settings and of the groups of settings
+    #[arg(long, value_enum, default_value_t = SortOrder::Group)]
+    sort: SortOrder,
+
+    /// Produce byte-identical output for the same profile and lint data:
+    /// settings sorted alphabetically and whitespace normalized
+    #[arg(long, conflicts_with_all = ["sort", "with_links", "with_descriptions", "with_versions"])]
+    canonical: bool,
+
+    /// Set exhaustively listed groups as a whole and only list their
+    /// exceptions, instead of listing every lint of the group
+    #[arg(long)]
+    compact: bool,
+
+    /// Precede every clippy lint with a link to its documentation
+    #[arg(long)]
+    with_links: bool,
+
+    /// Follow every exception of an exhaustively listed group with a short
+    /// description of the lint
+    #[arg(long)]
+    with_descriptions: bool,
+
+    /// Follow every single clippy lint with the Rust version that introduced
+    /// it, e.g. `# since 1.64.0`
+    #[arg(long)]
+    with_versions: bool,
 }
 
-#[derive(Debug)]
-struct ExhausiveGroup<'a> {
-    defaults: Vec<Setting<'a>>,
-    exceptions: Vec<Setting<'a>>,
+impl LayoutArgs {
+    fn sort(&self) -> SortOrder {
+        if self.canonical {
+            SortOrder::Alpha
+        } else {
+            self.sort
+        }
+    }
+
+    /// Applies the options that change the profile, see
+    /// [`ExhaustiveDefinition::compact`](clippy_lints::ExhaustiveDefinition::compact).
+    fn adjust(&self, profile: &mut ProfileDefinition) {
+        if self.compact {
+            for exhaustive in &mut profile.exhaustive {
+                exhaustive.compact = true;
+            }
+        }
+    }
+
+    fn toml_options(&self, workspace: bool) -> TomlOptions {
+        TomlOptions {
+            workspace,
+            links: self.with_links,
+            descriptions: self.with_descriptions,
+            versions: self.with_versions,
+        }
+    }
 }
 
-struct Exceptions<'a> {
-    level: LintLevel,
-    lints: LintList<'a>,
+// Comments around the generated configuration.
+#[derive(clap::Args, Debug)]
+struct FrameArgs {
+    /// File with text to write as a comment above the generated
+    /// configuration, instead of the `header` of the profile
+    #[arg(long)]
+    header_file: Option<PathBuf>,
+
+    /// File with text to write as a comment below the generated
+    /// configuration, instead of the `footer` of the profile
+    #[arg(long)]
+    footer_file: Option<PathBuf>,
 }
 
-impl<'a> Setting<'a> {
-    fn group(group: LintGroup, level: LintLevel, priority: impl Into<PrioritySetting>) -> Self {
-        Self::Group(GroupConfig {
-            group,
-            priority: priority.into(),
-            level,
-        })
-    }
+impl FrameArgs {
+    /// Wraps `output` in the header and footer, each line as a comment. Both
+    /// are left out for formats without comments.
+    fn apply(&self, output: String, format: Format, profile: &ProfileDefinition) -> Result<String> {
+        let read = |file: Option<&Path>, text: Option<&String>| -> Result<Option<String>> {
+            match file {
+                Some(path) => fs::read_to_string(path)
+                    .map(Some)
+                    .with_context(|| format!("failed to read {}", path.display())),
+                None => Ok(text.cloned()),
+            }
+        };
+        let header = read(self.header_file.as_deref(), profile.header.as_ref())?;
+        let footer = read(self.footer_file.as_deref(), profile.footer.as_ref())?;
+        if header.is_none() && footer.is_none() {
+            return Ok(output);
+        }
 
-    fn allow(
-        all_lints: &'a AllLints,
-        group: LintGroup,
-        lints: &'a [LintId<'a>],
-    ) -> Result<Vec<Self>> {
-        lints
-            .iter()
-            .map(|lint| {
-                let found = all_lints
-                    .0
-                    .iter()
-                    .find(|r| r.id == *lint && r.group == group);
-                if found.is_none() {
-                    Err(anyhow!("lint {} not in group {}", lint, group.as_str()))
-                } else {
-                    Ok(Self::Single(SingleLintConfig {
-                        lint,
-                        priority: PrioritySetting::Unspecified,
-                        level: LintLevel::Allow,
-                    }))
-                }
-            })
-            .collect()
+        let marker = match format {
+            Format::Toml | Format::Yaml => "#",
+            Format::Attributes => "//",
+            Format::Json | Format::Flags => {
+                color::warn("the format has no comments, leaving out the header and footer");
+                return Ok(output);
+            }
+        };
+        let comment = |text: &str| {
+            text.trim_end()
+                .lines()
+                .map(|line| format!("{marker} {line}").trim_end().to_owned())
+                .collect::<Vec<String>>()
+                .join("\n")
+        };
+
+        let mut parts = Vec::new();
+        parts.extend(header.as_deref().map(comment));
+        parts.push(output);
+        parts.extend(footer.as_deref().map(comment));
+        Ok(parts.join("\n\n"))
     }
+}
 
-    fn split_group_exhaustive(
-        all_lints: &'a AllLints,
-        group: LintGroup,
-        default_level: LintLevel,
-        exceptions: &Exceptions<'a>,
-    ) -> Result<ExhausiveGroup<'a>> {
-        let all_lints_in_group: Vec<&LintId> = all_lints
-            .0
-            .iter()
-            .filter(|lint| lint.group == group)
-            .map(|lint| &lint.id)
-            .collect();
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Emit {
+    /// The lint levels, in the format given by --format
+    Lints,
+    /// The `clippy.toml` values of the profile, always as TOML
+    ClippyToml,
+}
 
-        let all_lints_in_group_len = all_lints_in_group.len();
+impl OutputArgs {
+    fn write(&self, output: &str) -> Result<()> {
+        if self.output == Path::new("-") {
+            #[expect(clippy::print_stdout, reason = "this is the main program output")]
+            {
+                println!("{output}");
+            }
+            return Ok(());
+        }
 
-        exceptions
-            .lints
-            .0
-            .iter()
-            .find(|lint| (!all_lints_in_group.contains(lint)))
-            .map(|lint| Err(anyhow!("lint {lint} not part of group {group}")))
-            .unwrap_or(Ok(()))?;
+        if !self.force && self.output.exists() {
+            return Err(anyhow!(
+                "output file {} already exists, use --force to overwrite it",
+                self.output.display()
+            ));
+        }
 
-        Ok(all_lints_in_group
-            .into_iter()
-            .map(|lint| {
-                if exceptions.lints.0.contains(lint) {
-                    (
-                        ExhaustiveGroupClassification::Exception,
-                        Self::Single(SingleLintConfig {
-                            lint,
-                            priority: PrioritySetting::Unspecified,
-                            level: exceptions.level,
-                        }),
-                    )
-                } else {
-                    (
-                        ExhaustiveGroupClassification::Default,
-                        Self::Single(SingleLintConfig {
-                            lint,
-                            priority: PrioritySetting::Unspecified,
-                            level: default_level,
-                        }),
-                    )
-                }
-            })
-            .fold(
-                {
-                    let len_1 = exceptions.lints.0.len();
-                    ExhausiveGroup {
-                        defaults: Vec::with_capacity(
-                            all_lints_in_group_len.checked_sub(len_1).expect(
-                                "exceptions are a subset of of all lints in group, checked above",
-                            ),
-                        ),
-                        exceptions: Vec::with_capacity(len_1),
-                    }
-                },
-                |mut acc, (classification, setting)| {
-                    match classification {
-                        ExhaustiveGroupClassification::Default => acc.defaults.push(setting),
-                        ExhaustiveGroupClassification::Exception => acc.exceptions.push(setting),
-                    };
-                    acc
-                },
-            ))
+        fs::write(&self.output, format!("{output}\n"))
+            .with_context(|| format!("failed to write output file {}", self.output.display()))
     }
 }
 
-#[derive(Debug)]
-struct ConfigGroup<'a> {
-    comment: Option<String>,
-    settings: Vec<Setting<'a>>,
+#[derive(clap::Args, Debug)]
+struct ManifestArgs {
+    #[command(flatten)]
+    args: Args,
+
+    #[command(flatten)]
+    source: SourceArgs,
+
+    /// Path to the Cargo.toml manifest. Defaults to the nearest one in the
+    /// current directory or its parents. Several paths run the command on
+    /// each manifest, e.g. the workspaces of a monorepo
+    #[arg(long, num_args = 1..)]
+    manifest_path: Vec<PathBuf>,
+
+    /// File listing more manifests to run on, one path per line, `-` for
+    /// stdin
+    #[arg(long, value_name = "FILE")]
+    manifest_list: Option<PathBuf>,
+
+    #[command(flatten)]
+    layout: LayoutArgs,
+
+    /// Print the detected targets of the package and the lint levels they
+    /// change
+    #[arg(long)]
+    explain_decisions: bool,
 }
 
-#[derive(Debug)]
-struct Config<'a>(Vec<ConfigGroup<'a>>);
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum DiffFormat {
+    /// A unified diff of the lint tables
+    Unified,
+    /// The missing, extra and mismatching entries, e.g. for CI bots
+    Json,
+    /// GitHub Actions workflow commands annotating the manifest
+    Github,
+}
 
-impl Config<'_> {
-    fn to_toml(&self, args: &Args) -> String {
-        let mut output = if args.workspace {
-            String::from("[workspace.lints.clippy]\n")
-        } else {
-            String::from("[lints.clippy]\n")
-        };
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum ProblemFormat {
+    /// One `<file>:<line>: <problem>` line per problem
+    Text,
+    /// GitHub Actions workflow commands annotating the manifest
+    Github,
+}
 
-        let mut iter_group = self.0.iter().peekable();
+/// A GitHub Actions workflow command that shows `message` as an error
+/// annotation of `file`, at `line` if given. Paths below the current
+/// directory are made relative, as GitHub expects paths relative to the
+/// repository.
+fn github_error(file: &Path, line: Option<usize>, message: &str) -> String {
+    let current_dir = std::env::current_dir().unwrap_or_default();
+    let file = file.strip_prefix(&current_dir).unwrap_or(file);
 
-        while let Some(group) = iter_group.next() {
-            let last_group = iter_group.peek().is_none();
-            if let Some(ref comment) = group.comment {
-                writeln!(output, "# {comment}").expect("writing to string succeeds");
-            }
+    let escape_data = |text: &str| {
+        text.replace('%', "%25")
+            .replace('\r', "%0D")
+            .replace('\n', "%0A")
+    };
+    let escape_property = |text: &str| escape_data(text).replace(':', "%3A").replace(',', "%2C");
 
-            let mut iter_setting = group.settings.iter().peekable();
-            while let Some(setting) = iter_setting.next() {
-                let last_setting = iter_setting.peek().is_none();
-                match *setting {
-                    Setting::Single(ref single_lint_config) => match single_lint_config.priority {
-                        PrioritySetting::Explicit(priority) => write!(
-                            output,
-                            "{} = {{ level = \"{}\", priority = {} }}",
-                            single_lint_config.lint.0,
-                            single_lint_config.level.as_str(),
-                            priority
-                        )
-                        .expect("writing to string succeeds"),
-                        PrioritySetting::Unspecified => write!(
-                            output,
-                            "{} = \"{}\"",
-                            single_lint_config.lint.0,
-                            single_lint_config.level.as_str()
-                        )
-                        .expect("writing to string succeeds"),
-                    },
-                    Setting::Group(ref group_config) => match group_config.priority {
-                        PrioritySetting::Explicit(priority) => write!(
-                            output,
-                            "{} = {{ level = \"{}\", priority = {} }}",
-                            group_config.group.as_str(),
-                            group_config.level.as_str(),
-                            priority
-                        )
-                        .expect("writing to string succeeds"),
-                        PrioritySetting::Unspecified => write!(
-                            output,
-                            "{} = \"{}\"",
-                            group_config.group.as_str(),
-                            group_config.level.as_str(),
-                        )
-                        .expect("writing to string succeeds"),
-                    },
-                };
-                if !last_setting {
-                    output.push('\n');
-                }
-                if last_setting && !last_group {
-                    output.push('\n');
-                }
-            }
-            if !last_group {
-                output.push('\n');
+    let line = line.map(|line| format!(",line={line}")).unwrap_or_default();
+    format!(
+        "::error file={}{line}::{}",
+        escape_property(&file.display().to_string()),
+        escape_data(message)
+    )
+}
+
+#[derive(clap::Args, Debug)]
+struct DiffArgs {
+    #[command(flatten)]
+    manifest: ManifestArgs,
+
+    /// Format of the differences
+    #[arg(long, value_enum, default_value_t = DiffFormat::Unified)]
+    format: DiffFormat,
+
+    /// What to do about exceptions of the profile past their `expires` date
+    #[arg(long, value_enum, default_value_t = Expired::Fail)]
+    expired: Expired,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Expired {
+    /// Fail like for drift
+    Fail,
+    /// Only print a warning
+    Warn,
+}
+
+#[derive(clap::Args, Debug)]
+struct HookArgs {
+    #[command(flatten)]
+    manifest: ManifestArgs,
+
+    /// What to do about exceptions of the profile past their `expires` date
+    #[arg(long, value_enum, default_value_t = Expired::Fail)]
+    expired: Expired,
+}
+
+#[derive(clap::Args, Debug)]
+struct ApplyArgs {
+    #[command(flatten)]
+    manifest: ManifestArgs,
+
+    /// How to treat the entries already in the lint tables
+    #[arg(long, value_enum, default_value_t = Merge::Theirs)]
+    merge: Merge,
+
+    /// Replace the lint tables completely, the same as `--merge replace`
+    #[arg(long, conflicts_with = "merge")]
+    prune: bool,
+
+    /// Print the changes to each manifest as a unified diff instead of
+    /// writing them
+    #[arg(long)]
+    dry_run: bool,
+}
+
+impl ApplyArgs {
+    fn merge(&self) -> Merge {
+        if self.prune {
+            Merge::Replace
+        } else {
+            self.merge
+        }
+    }
+
+    /// Writes the updated manifest, or prints the diff to it with
+    /// `--dry-run`.
+    fn write(&self, path: &Path, old: &str, new: String) -> Result<()> {
+        if !self.dry_run {
+            return write_manifest(path, new);
+        }
+
+        let name = path.display().to_string();
+        if let Some(diff) = diff::unified(old, &new, &name, &name, color::enabled(&io::stdout())) {
+            #[expect(clippy::print_stdout, reason = "this is the main program output")]
+            {
+                print!("{diff}");
             }
         }
 
-        output
+        Ok(())
     }
 }
 
-#[derive(Debug, Deserialize)]
-struct Response(Vec<LintResponse>);
+/// Finds the nearest `Cargo.toml` in the current directory or its parents
+/// with `cargo locate-project`, or `given` if there is one. With
+/// `workspace`, the root manifest of its workspace is returned instead. If
+/// cargo cannot be run, the directories are searched directly.
+fn locate_manifest(given: Option<&Path>, workspace: bool) -> Result<PathBuf> {
+    let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let mut command = process::Command::new(cargo);
+    command.args(["locate-project", "--message-format", "plain"]);
+    if let Some(path) = given {
+        command.arg("--manifest-path").arg(path);
+    }
+    if workspace {
+        command.arg("--workspace");
+    }
+    if let Ok(output) = command.output() {
+        if !output.status.success() {
+            return Err(anyhow!(
+                "cargo locate-project failed, use --manifest-path: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        return Ok(PathBuf::from(
+            String::from_utf8_lossy(&output.stdout).trim_end(),
+        ));
+    }
 
-#[derive(Debug)]
-struct AllLints<'a>(Vec<Lint<'a>>);
+    let current_dir = match given.and_then(Path::parent) {
+        Some(dir) => dir.to_path_buf(),
+        None => std::env::current_dir().context("failed to get current directory")?,
+    };
+    let mut manifests = current_dir
+        .ancestors()
+        .map(|dir| dir.join("Cargo.toml"))
+        .filter(|path| path.is_file());
 
-impl<'a> AllLints<'a> {
-    fn from_response(response: &'a Response) -> Self {
-        Self(
-            response
-                .0
-                .iter()
-                .map(|lint| Lint {
-                    id: LintId(&lint.id),
-                    group: lint.group,
-                })
-                .collect(),
+    let nearest = manifests.next().ok_or_else(|| {
+        anyhow!(
+            "no Cargo.toml found in {} or its parents, use --manifest-path",
+            current_dir.display()
         )
+    })?;
+    if !workspace {
+        return Ok(nearest);
+    }
+
+    // Like cargo, the closest manifest with a `[workspace]` table is the root.
+    let is_root = |path: &PathBuf| {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| manifest::kind(&content).ok())
+            .is_some_and(manifest::ManifestKind::is_workspace)
+    };
+    if is_root(&nearest) {
+        return Ok(nearest);
     }
+    Ok(manifests.find(is_root).unwrap_or(nearest))
 }
 
-fn main() -> Result<()> {
-    let args = Args::parse();
+/// The nearest `Cargo.toml`, see [`locate_manifest`].
+fn discover_manifest() -> Result<PathBuf> {
+    locate_manifest(None, false)
+}
 
-    let response: Response = ureq::get("https://rust-lang.github.io/rust-clippy/stable/lints.json")
-        .call()?
-        .into_json::<Response>()?;
+/// `--manifest-path` if it was given, otherwise the nearest `Cargo.toml`.
+fn manifest_path(given: Option<&PathBuf>) -> Result<PathBuf> {
+    match given {
+        Some(path) => Ok(path.clone()),
+        None => discover_manifest(),
+    }
+}
 
-    let all_lints = AllLints::from_response(&response);
+/// The path of a file next to the root `Cargo.toml` of the workspace of
+/// `manifest`, or of the nearest manifest if none is given, so that it is
+/// found from every member. In the current directory if there is no
+/// manifest.
+fn project_file(manifest: Option<&Path>, name: &str) -> PathBuf {
+    locate_manifest(manifest, true)
+        .ok()
+        .and_then(|manifest| manifest.parent().map(Path::to_path_buf))
+        .unwrap_or_default()
+        .join(name)
+}
 
-    let restriction_group = Setting::split_group_exhaustive(
-        &all_lints,
-        LintGroup::Restriction,
-        LintLevel::Allow,
-        &Exceptions {
-            level: LintLevel::Warn,
-            lints: vec![
-                "allow_attributes",
-                "allow_attributes_without_reason",
-                "arithmetic_side_effects",
-                "as_conversions",
-                "assertions_on_result_states",
-                "cfg_not_test",
-                "clone_on_ref_ptr",
-                "create_dir",
-                "dbg_macro",
-                "decimal_literal_representation",
-                "default_numeric_fallback",
-                "deref_by_slicing",
-                "disallowed_script_idents",
-                "else_if_without_else",
-                "empty_drop",
-                "empty_enum_variants_with_brackets",
-                "empty_structs_with_brackets",
-                "exit",
-                "filetype_is_file",
-                "float_arithmetic",
-                "float_cmp_const",
-                "fn_to_numeric_cast_any",
-                "format_push_string",
-                "get_unwrap",
-                "indexing_slicing",
-                "infinite_loop",
-                "inline_asm_x86_att_syntax",
-                "inline_asm_x86_intel_syntax",
-                "integer_division",
-                "iter_over_hash_type",
-                "large_include_file",
-                "let_underscore_must_use",
-                "let_underscore_untyped",
-                "little_endian_bytes",
-                "lossy_float_literal",
-                "map_err_ignore",
-                "mem_forget",
-                "missing_assert_message",
-                "missing_asserts_for_indexing",
-                "mixed_read_write_in_expression",
-                "modulo_arithmetic",
-                "multiple_inherent_impl",
-                "multiple_unsafe_ops_per_block",
-                "mutex_atomic",
-                "panic",
-                "partial_pub_fields",
-                "pattern_type_mismatch",
-                "print_stderr",
-                "print_stdout",
-                "pub_without_shorthand",
-                "rc_buffer",
-                "rc_mutex",
-                "redundant_type_annotations",
-                "renamed_function_params",
-                "rest_pat_in_fully_bound_structs",
-                "same_name_method",
-                "self_named_module_files",
-                "semicolon_inside_block",
-                "str_to_string",
-                "string_add",
-                "string_lit_chars_any",
-                "string_slice",
-                "string_to_string",
-                "suspicious_xor_used_as_pow",
-                "tests_outside_test_module",
-                "todo",
-                "try_err",
-                "undocumented_unsafe_blocks",
-                "unimplemented",
-                "unnecessary_safety_comment",
-                "unnecessary_safety_doc",
-                "unnecessary_self_imports",
-                "unneeded_field_pattern",
-                "unseparated_literal_suffix",
-                "unused_result_ok",
-                "unwrap_used",
-                "use_debug",
-                "verbose_file_reads",
-            ]
-            .into(),
-        },
-    )?;
+impl ManifestArgs {
+    /// Every `--manifest-path` and the manifests of `--manifest-list`, or
+    /// the nearest manifest if there are none.
+    fn manifest_paths(&self) -> Result<Vec<PathBuf>> {
+        let mut paths = self.manifest_path.clone();
+        if let Some(ref list) = self.manifest_list {
+            let content = if list.as_os_str() == "-" {
+                io::read_to_string(io::stdin())
+                    .context("failed to read manifest list from stdin")?
+            } else {
+                fs::read_to_string(list)
+                    .with_context(|| format!("failed to read manifest list {}", list.display()))?
+            };
+            paths.extend(
+                content
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(PathBuf::from),
+            );
+        }
 
-    let cargo_lints = {
-        let mut v = vec!["multiple_crate_versions".into()];
-        match args.profile {
-            Profile::Publish => (),
-            Profile::Personal => v.push("cargo_common_metadata".into()),
+        if paths.is_empty() {
+            paths.push(discover_manifest()?);
         }
-        v
-    };
+        Ok(paths)
+    }
 
-    let pedantic_allows = &[
-        "too_many_lines".into(),
-        "must_use_candidate".into(),
-        "map_unwrap_or".into(),
-        "missing_errors_doc".into(),
-        "if_not_else".into(),
-        "similar_names".into(),
-    ];
-
-    let nursery_allows = &[
-        "missing_const_for_fn".into(),
-        "option_if_let_else".into(),
-        "redundant_pub_crate".into(),
-    ];
-
-    let complexity_allows = &["too_many_arguments".into()];
-
-    let style_allows = &["new_without_default".into(), "redundant_closure".into()];
-
-    let config = Config(vec![
-        ConfigGroup {
-            comment: Some("enabled groups".to_owned()),
-            settings: vec![
-                Setting::group(LintGroup::Correctness, LintLevel::Deny, Some(-1)),
-                Setting::group(LintGroup::Suspicious, LintLevel::Warn, Some(-1)),
-                Setting::group(LintGroup::Style, LintLevel::Warn, Some(-1)),
-                Setting::group(LintGroup::Complexity, LintLevel::Warn, Some(-1)),
-                Setting::group(LintGroup::Perf, LintLevel::Warn, Some(-1)),
-                Setting::group(LintGroup::Cargo, LintLevel::Warn, Some(-1)),
-                Setting::group(LintGroup::Pedantic, LintLevel::Warn, Some(-1)),
-                Setting::group(LintGroup::Nursery, LintLevel::Warn, Some(-1)),
-            ],
-        },
-        ConfigGroup {
-            comment: Some("pedantic overrides".to_owned()),
-            settings: Setting::allow(&all_lints, LintGroup::Pedantic, pedantic_allows)?,
-        },
-        ConfigGroup {
-            comment: Some("nursery overrides".to_owned()),
-            settings: Setting::allow(&all_lints, LintGroup::Nursery, nursery_allows)?,
-        },
-        ConfigGroup {
-            comment: Some("complexity overrides".to_owned()),
-            settings: Setting::allow(&all_lints, LintGroup::Complexity, complexity_allows)?,
-        },
-        ConfigGroup {
-            comment: Some("style overrides".to_owned()),
-            settings: Setting::allow(&all_lints, LintGroup::Style, style_allows)?,
-        },
-        ConfigGroup {
-            comment: Some("cargo overrides".to_owned()),
-            settings: Setting::allow(&all_lints, LintGroup::Cargo, &cargo_lints)?,
-        },
-        ConfigGroup {
-            comment: Some("selected restrictions".to_owned()),
-            settings: restriction_group.exceptions,
-        },
-        ConfigGroup {
-            comment: Some("restrictions explicit allows".to_owned()),
-            settings: restriction_group.defaults,
-        },
-    ]);
+    /// Runs `run` on every manifest. With several manifests, a failure does
+    /// not stop the others, and a summary of the outcomes follows. `done`
+    /// describes a manifest that `run` succeeded on without drift.
+    fn for_each_manifest(
+        &self,
+        done: &str,
+        mut run: impl FnMut(&Manifest) -> Result<Outcome>,
+    ) -> Result<Outcome> {
+        let paths = self.manifest_paths()?;
+        if let [ref path] = paths[..] {
+            return run(&Manifest::read(path)?);
+        }
 
-    let output = config.to_toml(&args);
+        let mut results = Vec::new();
+        for (path, result) in generate::for_each_manifest(paths, &mut run) {
+            if let Err(ref err) = result {
+                #[expect(
+                    clippy::print_stderr,
+                    clippy::use_debug,
+                    reason = "the debug format of anyhow includes the causes"
+                )]
+                {
+                    eprintln!("{} {}: {err:?}", color::error("Error:"), path.display());
+                }
+            }
+            results.push((path, result));
+        }
 
-    #[expect(clippy::print_stdout, reason = "this is the main program output")]
-    {
-        println!("{output}");
+        let width = results
+            .iter()
+            .map(|entry| entry.0.display().to_string().len())
+            .chain(["MANIFEST".len()])
+            .max()
+            .unwrap_or_default();
+        let mut lines = vec![format!("{:<width$}  RESULT", "MANIFEST")];
+        lines.extend(results.iter().map(|entry| {
+            let result = match entry.1 {
+                Ok(Outcome::Success) => done,
+                Ok(Outcome::Drift) => "drifted",
+                Err(_) => "failed",
+            };
+            format!("{:<width$}  {result}", entry.0.display().to_string())
+        }));
+        #[expect(clippy::print_stderr, reason = "status messages go to stderr")]
+        {
+            eprintln!("{}", lines.join("\n"));
+        }
+
+        let failed = results.iter().filter(|entry| entry.1.is_err()).count();
+        if failed > 0 {
+            return Err(anyhow!("{failed} of {} manifests failed", results.len()));
+        }
+        Ok(
+            if results
+                .iter()
+                .any(|entry| matches!(entry.1, Ok(Outcome::Drift)))
+            {
+                Outcome::Drift
+            } else {
+                Outcome::Success
+            },
+        )
     }
 
-    Ok(())
+    /// Whether to write workspace lint tables, see
+    /// [`Manifest::workspace_tables`].
+    fn workspace(&self, manifest: &Manifest) -> Result<bool> {
+        Ok(manifest.workspace_tables(self.args.workspace())?)
+    }
+
+    /// The arguments for the package of `manifest`, see
+    /// [`Args::for_package`], explained with `--explain-decisions`.
+    fn package_args(&self, manifest: &Manifest, workspace: bool) -> Result<Args> {
+        let (args, targets) = self.args.for_package(manifest, workspace)?;
+
+        if self.explain_decisions {
+            let mut lines = vec![match targets {
+                Some(targets) => format!("package targets: {targets}"),
+                None => "workspace lint tables, not adjusted to package targets".to_owned(),
+            }];
+            lines.extend(args.adjustments.iter().map(|adjustment| {
+                let level = adjustment
+                    .level
+                    .map_or("level of the profile", LintLevel::as_str);
+                format!("{}: {level}, {}", adjustment.lint, adjustment.reason)
+            }));
+            #[expect(clippy::print_stderr, reason = "status messages go to stderr")]
+            {
+                eprintln!("{}", lines.join("\n"));
+            }
+        }
+
+        Ok(args)
+    }
+
+    /// The profile, loaded once for all manifests and members. Its providers
+    /// are shared by them too, and run once for each manifest and member they
+    /// are asked about.
+    fn load_profile(&self) -> Result<ProfileDefinition> {
+        let mut profile = load_profile(&self.args)?;
+        self.layout.adjust(&mut profile);
+        Ok(profile)
+    }
+
+    /// The lint tables for the workspace `member` of `root`, from the
+    /// member-specific configuration of the profile.
+    fn generate_member(
+        &self,
+        profile: &ProfileDefinition,
+        all_lints: &AllLints<'_>,
+        member: &workspace::Member,
+        root: &Manifest,
+    ) -> Result<String> {
+        let msrv = match self.args.msrv {
+            Some(msrv) => Some(msrv),
+            None => member.rust_version.or(self.msrv(root)?),
+        };
+
+        // Rules and providers see the manifest of the member, not the root.
+        let mut args = self.args.clone();
+        args.toolchain = manifest::toolchain_version(
+            member
+                .manifest_path
+                .parent()
+                .unwrap_or_else(|| Path::new("")),
+        )?;
+        args.manifest = Some(member.manifest_path.clone());
+
+        generate(
+            &args,
+            profile,
+            all_lints,
+            Some(&member.name),
+            Format::Toml,
+            msrv,
+            &self.layout,
+            false,
+            None,
+        )
+    }
+
+    /// The MSRV for `manifest`, see [`Manifest::msrv`].
+    fn msrv(&self, manifest: &Manifest) -> Result<Option<RustVersion>> {
+        Ok(manifest.msrv(self.args.msrv)?)
+    }
+}
+
+#[derive(clap::Args, Clone, Debug)]
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "these are independent command line flags"
+)]
+struct Args {
+    /// Name of a built-in profile or of a profile defined in the config file.
+    /// Repeat to layer profiles, later ones take precedence
+    #[arg(
+        long,
+        required = true,
+        env = "CLIPPY_LINTS_PROFILE",
+        value_delimiter = ',',
+        value_parser = ProfileNameParser,
+        hide_possible_values = true
+    )]
+    profile: Vec<String>,
+
+    /// TOML file containing additional profile definitions
+    #[arg(long, env = "CLIPPY_LINTS_CONFIG")]
+    config: Option<PathBuf>,
+
+    /// Leave out lints introduced after this Rust version when listing all
+    /// lints of a group. Defaults to the `rust-version` of the manifest for
+    /// commands operating on one
+    #[arg(long, env = "CLIPPY_LINTS_MSRV")]
+    msrv: Option<RustVersion>,
+
+    /// Generate `[workspace.lints]` tables. Detected from the manifest for
+    /// commands operating on one
+    #[arg(long, env = "CLIPPY_LINTS_WORKSPACE", value_parser = BoolishValueParser::new())]
+    workspace: bool,
+
+    /// Generate `[lints]` tables even if the manifest is a workspace root.
+    /// Takes precedence over --workspace
+    #[arg(long)]
+    no_workspace: bool,
+
+    /// Fail instead of warning if the profile references deprecated lints,
+    /// and warn about settings of layered profiles that override each other
+    #[arg(long, env = "CLIPPY_LINTS_STRICT", value_parser = BoolishValueParser::new())]
+    strict: bool,
+
+    /// Deny every lint that would warn, except for the soft lints of the
+    /// profile, e.g. to enforce the profile in CI
+    #[arg(long, env = "CLIPPY_LINTS_ESCALATE_WARNINGS", value_parser = BoolishValueParser::new())]
+    escalate_warnings: bool,
+
+    /// Allow a clippy lint on top of the profile. Can be repeated
+    #[arg(long, value_name = "LINT")]
+    allow: Vec<String>,
+
+    /// Warn about a clippy lint on top of the profile. Can be repeated
+    #[arg(long, value_name = "LINT")]
+    warn: Vec<String>,
+
+    /// Deny a clippy lint on top of the profile. Can be repeated. If a lint is
+    /// given to several of --allow, --warn and --deny, the strictest wins
+    #[arg(long, value_name = "LINT")]
+    deny: Vec<String>,
+
+    /// Leave out the settings of a clippy group and of all its lints, so they
+    /// keep their default levels. Can be repeated
+    #[arg(long, value_enum)]
+    exclude_group: Vec<LintGroup>,
+
+    /// Levels that follow from the targets of the package, see
+    /// [`package_profile`]. Set by the commands operating on a manifest.
+    #[arg(skip)]
+    adjustments: Vec<manifest::Adjustment>,
+
+    /// The version of the toolchain pinned for the package. Lints it does not
+    /// know are left out. Set by the commands operating on a manifest.
+    #[arg(skip)]
+    toolchain: Option<RustVersion>,
+
+    /// The manifest passed on to profile providers. Set by the commands
+    /// operating on a manifest, otherwise the nearest one is used.
+    #[arg(skip)]
+    manifest: Option<PathBuf>,
+}
+
+impl Args {
+    /// `--workspace` or `--no-workspace`, if given. `--no-workspace` wins, so
+    /// that it can override `CLIPPY_LINTS_WORKSPACE`.
+    fn workspace(&self) -> Option<bool> {
+        if self.no_workspace {
+            Some(false)
+        } else if self.workspace {
+            Some(true)
+        } else {
+            None
+        }
+    }
+
+    /// The arguments with the manifest and toolchain of the package and the
+    /// levels that follow from its targets, see [`Manifest::package`], and
+    /// the targets themselves.
+    fn for_package(
+        &self,
+        manifest: &Manifest,
+        workspace: bool,
+    ) -> Result<(Self, Option<manifest::PackageTargets>)> {
+        let package = manifest.package(workspace)?;
+        let mut args = self.clone();
+        args.toolchain = package.toolchain;
+        args.manifest = Some(manifest.path.clone());
+        args.adjustments = package.adjustments();
+        Ok((args, package.targets))
+    }
+
+    /// The profiles of --profile and --config with the lints of --allow,
+    /// --warn and --deny.
+    fn profile_selection(&self) -> Result<ProfileSelection> {
+        Ok(ProfileSelection {
+            profiles: self.profile.clone(),
+            config_file: self.config.as_deref().map(ConfigFile::load).transpose()?,
+            allow: self.allow.clone(),
+            warn: self.warn.clone(),
+            deny: self.deny.clone(),
+            strict: self.strict,
+        })
+    }
+
+    /// The clippy lints of --allow, --warn and --deny, without prefix.
+    fn given_lints(&self) -> Vec<&str> {
+        self.allow
+            .iter()
+            .chain(&self.warn)
+            .chain(&self.deny)
+            .map(|lint| lint.strip_prefix("clippy::").unwrap_or(lint))
+            .collect()
+    }
+}
+
+/// Accepts any profile name, but offers the built-in profiles as possible
+/// values for shell completions.
+#[derive(Clone)]
+struct ProfileNameParser;
+
+impl TypedValueParser for ProfileNameParser {
+    type Value = String;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &OsStr,
+    ) -> Result<String, clap::Error> {
+        StringValueParser::new().parse_ref(cmd, arg, value)
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        Some(Box::new(
+            Profile::value_variants()
+                .iter()
+                .filter_map(Profile::to_possible_value),
+        ))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum InstalledClippy {
+    /// If the lint data cannot be fetched
+    Fallback,
+    /// Instead of fetching the lint data
+    Always,
+    /// Not at all, even if the lint data cannot be fetched
+    Never,
+}
+
+/// Options controlling where the lint data comes from.
+#[derive(clap::Args, Clone, Debug)]
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "these are independent command line flags"
+)]
+struct SourceArgs {
+    /// Read the lint data from this `lints.json` file, `-` for stdin, instead
+    /// of fetching it
+    #[arg(long, conflicts_with_all = ["lints_url", "rust_version", "refresh"])]
+    input: Option<PathBuf>,
+
+    /// When to use the lint list of the installed clippy. It lacks the
+    /// versions that introduced the lints. `always` implies --no-lockfile
+    #[arg(
+        long,
+        value_enum,
+        env = "CLIPPY_LINTS_INSTALLED_CLIPPY",
+        default_value_t = InstalledClippy::Fallback
+    )]
+    installed_clippy: InstalledClippy,
+
+    /// Use the lint data bundled at build time instead of fetching it
+    #[cfg(feature = "bundled")]
+    #[arg(long, env = "CLIPPY_LINTS_OFFLINE", value_parser = BoolishValueParser::new(), conflicts_with = "input")]
+    offline: bool,
+
+    /// How long downloaded lint data is reused, e.g. `30m`, `24h` or `7d`
+    #[arg(
+        long,
+        env = "CLIPPY_LINTS_CACHE_TTL",
+        value_parser = parse_duration,
+        default_value = "24h"
+    )]
+    cache_ttl: Duration,
+
+    /// Download the lint data even if the cached copy is still fresh
+    #[arg(long)]
+    refresh: bool,
+
+    /// Release channel whose lint data is used, `stable` if not given.
+    /// Implies --no-lockfile
+    #[arg(long, value_enum, env = "CLIPPY_LINTS_CHANNEL")]
+    channel: Option<Channel>,
+
+    /// Use the lint data of a specific Rust release, e.g. `1.77`, instead of
+    /// the latest release of a channel. Implies --no-lockfile
+    #[arg(long, conflicts_with = "channel")]
+    rust_version: Option<RustVersion>,
+
+    /// Download the lint data from this URL instead, e.g. from a mirror.
+    /// `{release}` is replaced by the channel or `rust-<version>`. Implies
+    /// --no-lockfile
+    #[arg(long, env = "CLIPPY_LINTS_URL")]
+    lints_url: Option<String>,
+
+    /// Proxy to download the lint data through, e.g. `http://proxy:3128`.
+    /// Defaults to the `HTTPS_PROXY` and `HTTP_PROXY` environment variables,
+    /// honoring `NO_PROXY`
+    #[arg(long)]
+    proxy: Option<String>,
+
+    /// How often to try downloading the lint data before giving up
+    #[arg(long, default_value_t = RetryPolicy::default().attempts)]
+    download_attempts: u32,
+
+    /// How long to wait before retrying a failed download, doubled for every
+    /// further attempt
+    #[arg(long, value_parser = parse_duration, default_value = "1s")]
+    retry_backoff: Duration,
+
+    /// Wait exactly the backoff between attempts instead of a random part of
+    /// it
+    #[arg(long)]
+    no_retry_jitter: bool,
+
+    /// How long a download attempt may take in total before it is given up
+    #[arg(long, value_parser = parse_duration, default_value = "1m")]
+    timeout: Duration,
+
+    /// How long establishing the connection may take before the attempt is
+    /// given up
+    #[arg(long, value_parser = parse_duration, default_value = "10s")]
+    connect_timeout: Duration,
+
+    /// Ignore `clippy-lints.lock` and load the latest lint data. The
+    /// lockfile takes precedence over the cache and the installed clippy
+    /// otherwise, while --input and --offline take precedence over it
+    #[arg(long)]
+    no_lockfile: bool,
+
+    /// Skip invalid entries of the lint data, with a warning, instead of
+    /// failing on them
+    #[arg(long)]
+    permissive: bool,
+}
+
+impl SourceArgs {
+    fn release(&self) -> Release {
+        self.rust_version.map_or_else(
+            || Release::Channel(self.channel.unwrap_or(Channel::Stable)),
+            Release::Version,
+        )
+    }
+
+    /// The flag that asks for other lint data than the lockfile pins, if
+    /// any.
+    fn overrides_lockfile(&self) -> Option<&'static str> {
+        if self.rust_version.is_some() {
+            Some("--rust-version")
+        } else if self.channel.is_some() {
+            Some("--channel")
+        } else if self.lints_url.is_some() {
+            Some("--lints-url")
+        } else if self.installed_clippy == InstalledClippy::Always {
+            Some("--installed-clippy always")
+        } else {
+            None
+        }
+    }
+
+    fn fetch_options(&self) -> FetchOptions {
+        FetchOptions {
+            release: self.release(),
+            cache_ttl: self.cache_ttl,
+            refresh: self.refresh,
+            url: self.lints_url.clone(),
+            proxy: self.proxy.clone(),
+            retry: RetryPolicy {
+                attempts: self.download_attempts,
+                backoff: self.retry_backoff,
+                jitter: !self.no_retry_jitter,
+            },
+            timeouts: Timeouts {
+                connect: self.connect_timeout,
+                overall: self.timeout,
+            },
+            permissive: self.permissive,
+        }
+    }
+
+    /// Where [`SourceArgs::load_unlocked_response`] got the lint data for
+    /// `manifest` from, given whether it kept JSON text in the buffer.
+    fn origin(
+        &self,
+        #[cfg_attr(
+            not(feature = "bundled"),
+            expect(unused_variables, reason = "only the snapshot is next to the manifest")
+        )]
+        manifest: Option<&Path>,
+        kept_json: bool,
+    ) -> String {
+        if let Some(ref input) = self.input {
+            return input.display().to_string();
+        }
+
+        #[cfg(feature = "bundled")]
+        if self.offline {
+            return if kept_json {
+                project_file(manifest, SNAPSHOT_NAME).display().to_string()
+            } else {
+                "lint data bundled with clippy-lints".to_owned()
+            };
+        }
+
+        if kept_json {
+            self.fetch_options().lints_url()
+        } else {
+            "clippy-driver -W help".to_owned()
+        }
+    }
+
+    /// Loads the lint data, from the lockfile of the workspace of `manifest`
+    /// (or the nearest one) if there is one. JSON lint data is kept in
+    /// `buffer`, which the response borrows from.
+    fn load_response<'a>(
+        &self,
+        manifest: Option<&Path>,
+        buffer: &'a mut String,
+    ) -> Result<Response<'a>> {
+        let start = Instant::now();
+        self.read_response(manifest, !self.no_lockfile, buffer)
+            .inspect(|response| loaded(response, start))
+            .map_err(|err| Failure::Data.wrap(err))
+    }
+
+    /// Loads the latest lint data, ignoring the lockfile.
+    fn load_unlocked_response<'a>(
+        &self,
+        manifest: Option<&Path>,
+        buffer: &'a mut String,
+    ) -> Result<Response<'a>> {
+        let start = Instant::now();
+        self.read_response(manifest, false, buffer)
+            .inspect(|response| loaded(response, start))
+            .map_err(|err| Failure::Data.wrap(err))
+    }
+
+    /// An explicitly given `--input` or `--offline` takes precedence over
+    /// the lockfile, and the flags of [`SourceArgs::overrides_lockfile`]
+    /// ignore it with a warning. `--offline` prefers a snapshot next to the
+    /// manifest to the bundled lint data.
+    fn read_response<'a>(
+        &self,
+        manifest: Option<&Path>,
+        use_lockfile: bool,
+        buffer: &'a mut String,
+    ) -> Result<Response<'a>> {
+        if let Some(ref input) = self.input {
+            return Ok(FileSource {
+                path: input.clone(),
+                permissive: self.permissive,
+            }
+            .fetch(buffer)?);
+        }
+
+        #[cfg(feature = "bundled")]
+        if self.offline {
+            let snapshot = project_file(manifest, SNAPSHOT_NAME);
+            if !snapshot.is_file() {
+                return Ok(BundledSource.fetch(buffer)?);
+            }
+            tracing::debug!("using the lint data snapshot {}", snapshot.display());
+            return Ok(FileSource {
+                path: snapshot,
+                permissive: self.permissive,
+            }
+            .fetch(buffer)?);
+        }
+
+        if use_lockfile {
+            let path = project_file(manifest, LOCKFILE_NAME);
+            if let Some(lockfile) = Lockfile::read(&path)? {
+                match self.overrides_lockfile() {
+                    Some(flag) => color::warn(&format!(
+                        "ignoring {} because {flag} was given, pass --no-lockfile to silence this",
+                        path.display()
+                    )),
+                    None => return Ok(lockfile.fetch(buffer)?),
+                }
+            }
+        }
+
+        if self.installed_clippy == InstalledClippy::Always {
+            return Ok(InstalledClippySource.fetch(buffer)?);
+        }
+
+        let loaded = self.fetch_options().fetch(buffer);
+
+        match loaded {
+            Err(err) if self.installed_clippy == InstalledClippy::Fallback => {
+                let response = Response::from_installed_clippy().map_err(|fallback_err| {
+                    anyhow::Error::from(err).context(format!(
+                        "falling back to the installed clippy failed too: {:#}",
+                        anyhow::Error::from(fallback_err)
+                    ))
+                })?;
+                color::warn("using the lint list of the installed clippy");
+                Ok(response)
+            }
+            loaded => Ok(loaded?),
+        }
+    }
+}
+
+/// Logs how long loading the lint data took and warns about the entries a
+/// permissive parse left out.
+fn loaded(response: &Response<'_>, start: Instant) {
+    tracing::debug!("loaded the lint data in {:.1?}", start.elapsed());
+    for skipped in &response.skipped {
+        let id = skipped
+            .id
+            .as_ref()
+            .map(|id| format!(" ({id})"))
+            .unwrap_or_default();
+        color::warn(&format!(
+            "skipping invalid entry {}{id} of the lint data: {}",
+            skipped.index, skipped.problem
+        ));
+    }
+}
+
+/// Parses durations like `90s`, `30m`, `24h` or `7d`. A bare number is
+/// interpreted as seconds.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let (number, multiplier) = match s.char_indices().last() {
+        Some((i, 's')) => (s.get(..i), 1),
+        Some((i, 'm')) => (s.get(..i), 60),
+        Some((i, 'h')) => (s.get(..i), 60 * 60),
+        Some((i, 'd')) => (s.get(..i), 24 * 60 * 60),
+        Some(_) => (Some(s), 1),
+        None => return Err("duration must not be empty".to_owned()),
+    };
+
+    let number: u64 = number
+        .unwrap_or_default()
+        .parse()
+        .map_err(|err| format!("invalid duration \"{s}\": {err}"))?;
+
+    number
+        .checked_mul(multiplier)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("duration \"{s}\" is too large"))
+}
+
+/// Warns about deprecated lints referenced by the profile, or fails if
+/// `strict` is set.
+fn check_deprecated(
+    profile: &ProfileDefinition,
+    all_lints: &AllLints<'_>,
+    strict: bool,
+) -> Result<()> {
+    let messages: Vec<String> = profile
+        .deprecated_lints(all_lints)
+        .into_iter()
+        .map(|lint| match lint.deprecation_reason() {
+            Some(reason) => format!("deprecated lint {}: {reason}", lint.id),
+            None => format!("deprecated lint {}", lint.id),
+        })
+        .collect();
+
+    if strict && !messages.is_empty() {
+        return Err(anyhow!(
+            "profile references {}",
+            messages.join("\nprofile references ")
+        ));
+    }
+
+    for message in messages {
+        color::warn(&format!("leaving out {message}"));
+    }
+
+    Ok(())
+}
+
+/// Fails if the profile references clippy lints the lint data does not know.
+fn check_unknown(profile: &ProfileDefinition, all_lints: &AllLints<'_>) -> Result<()> {
+    let messages: Vec<String> = profile
+        .unknown_lints(all_lints)
+        .into_iter()
+        .map(|lint| match lint.suggestion {
+            Some(suggestion) => format!("{} (did you mean {suggestion}?)", lint.name),
+            None => lint.name,
+        })
+        .collect();
+
+    if messages.is_empty() {
+        return Ok(());
+    }
+
+    Err(Failure::Usage.wrap(anyhow!(
+        "profile references unknown lints: {}",
+        messages.join(", ")
+    )))
+}
+
+/// Resolves the profiles given by --profile, each layered on top of the
+/// previous ones.
+fn load_profile(args: &Args) -> Result<ProfileDefinition> {
+    let start = Instant::now();
+    let profile = args
+        .profile_selection()
+        .and_then(|selection| Ok(selection.load()?))
+        .map_err(|err| Failure::Usage.wrap(err))?;
+    tracing::debug!("loaded the profile in {:.1?}", start.elapsed());
+    Ok(profile)
+}
+
+/// The profile with the levels of `args.adjustments`, see
+/// [`generate::adjust_profile`]. The lints of --allow, --warn and --deny
+/// keep their levels.
+fn package_profile<'a>(profile: &'a ProfileDefinition, args: &Args) -> Cow<'a, ProfileDefinition> {
+    generate::adjust_profile(profile, &args.adjustments, &args.given_lints())
+}
+
+/// Trims trailing whitespace and leading, trailing and repeated blank lines.
+fn canonicalize(output: &str) -> String {
+    let mut lines: Vec<&str> = Vec::new();
+    for line in output.lines().map(str::trim_end) {
+        if line.is_empty() && lines.last().is_none_or(|last| last.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    while lines.last().is_some_and(|last| last.is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
+/// Generates the configuration of the profile, or of one of its workspace
+/// members if `member` is given. `frame` adds the header and footer, which
+/// only belong into files of their own and not into a manifest.
+#[expect(
+    clippy::too_many_arguments,
+    reason = "the subcommands generate for different targets"
+)]
+fn generate(
+    args: &Args,
+    profile: &ProfileDefinition,
+    all_lints: &AllLints<'_>,
+    member: Option<&str>,
+    format: Format,
+    msrv: Option<RustVersion>,
+    layout: &LayoutArgs,
+    workspace: bool,
+    frame: Option<&FrameArgs>,
+) -> Result<String> {
+    let profile = package_profile(profile, args);
+
+    let start = Instant::now();
+    let mut config = resolve_config(args, &profile, all_lints, msrv, member)?;
+    tracing::debug!("resolved the configuration in {:.1?}", start.elapsed());
+
+    let start = Instant::now();
+    config.sort(layout.sort());
+    let output = config.render(format, layout.toml_options(workspace))?;
+    tracing::debug!("rendered the configuration in {:.1?}", start.elapsed());
+    let output = if layout.canonical {
+        canonicalize(&output)
+    } else {
+        output
+    };
+
+    match frame {
+        Some(frame) => frame.apply(output, format, &profile),
+        None => Ok(output),
+    }
+}
+
+/// Resolves the profile, or one of its workspace members if `member` is
+/// given, and escalates warnings if requested.
+fn resolve_config<'a>(
+    args: &Args,
+    profile: &'a ProfileDefinition,
+    all_lints: &'a AllLints<'a>,
+    msrv: Option<RustVersion>,
+    member: Option<&str>,
+) -> Result<Config<'a>> {
+    check_unknown(profile, all_lints)?;
+
+    // Only rules and providers look at the project, so the manifest is not
+    // read without them.
+    let project = if profile.rules.is_empty() && profile.providers.is_empty() {
+        Project::default()
+    } else {
+        match args.manifest.clone().or_else(|| discover_manifest().ok()) {
+            Some(path) => Project::from_manifest(&path)?,
+            None => Project::default(),
+        }
+    };
+
+    let options = GenerateOptions {
+        msrv,
+        member: member.map(str::to_owned),
+        project,
+        escalate_warnings: args.escalate_warnings,
+        exclude_groups: args.exclude_group.clone(),
+        toolchain: args.toolchain,
+    };
+    Ok(generate::resolve(profile, all_lints, &options)?)
+}
+
+fn run_diff(options: &DiffArgs) -> Result<Outcome> {
+    let profile = options.manifest.load_profile()?;
+    let outcome = options
+        .manifest
+        .for_each_manifest("up to date", |manifest| {
+            diff_manifest(options, &profile, manifest)
+        })?;
+
+    let structured = options.format != DiffFormat::Unified;
+    Ok(outcome.or(report_expired(&profile, options.expired, structured)))
+}
+
+fn diff_manifest(
+    options: &DiffArgs,
+    profile: &ProfileDefinition,
+    manifest: &Manifest,
+) -> Result<Outcome> {
+    let diff_args = &options.manifest;
+    let workspace = diff_args.workspace(manifest)?;
+    let args = diff_args.package_args(manifest, workspace)?;
+
+    let mut buffer = String::new();
+    let response = diff_args
+        .source
+        .load_response(Some(&manifest.path), &mut buffer)?;
+    let all_lints = AllLints::from_response(&response);
+    check_deprecated(profile, &all_lints, args.strict)?;
+
+    let generated = generate(
+        &args,
+        profile,
+        &all_lints,
+        None,
+        Format::Toml,
+        diff_args.msrv(manifest)?,
+        &diff_args.layout,
+        workspace,
+        None,
+    )?;
+
+    if options.format != DiffFormat::Unified {
+        let drift = diff::compare(&manifest.content, &generated, table_prefix(workspace))?;
+        let output = match options.format {
+            DiffFormat::Github => drift
+                .messages()
+                .into_iter()
+                .map(|(line, message)| github_error(&manifest.path, line, &message))
+                .collect::<Vec<String>>()
+                .join("\n"),
+            DiffFormat::Json | DiffFormat::Unified => serde_json::to_string_pretty(&drift)?,
+        };
+        if !output.is_empty() {
+            #[expect(clippy::print_stdout, reason = "this is the main program output")]
+            {
+                println!("{output}");
+            }
+        }
+        return Ok(if drift.is_empty() {
+            Outcome::Success
+        } else {
+            Outcome::Drift
+        });
+    }
+
+    let current = diff::extract_lint_tables(&manifest.content, table_prefix(workspace));
+
+    if let Some(diff) = diff::unified(
+        &current,
+        &generated,
+        &manifest.path.display().to_string(),
+        "generated",
+        color::enabled(&io::stdout()),
+    ) {
+        #[expect(clippy::print_stdout, reason = "this is the main program output")]
+        {
+            print!("{diff}");
+        }
+        return Ok(Outcome::Drift);
+    }
+
+    Ok(Outcome::Success)
+}
+
+fn run_hook(options: &HookArgs) -> Result<Outcome> {
+    let hook_args = &options.manifest;
+    let profile = hook_args.load_profile()?;
+    let outcome = hook_args.for_each_manifest("up to date", |manifest| {
+        hook_manifest(hook_args, &profile, manifest)
+    })?;
+
+    Ok(outcome.or(report_expired(&profile, options.expired, false)))
+}
+
+/// Reports the exceptions of the profile past their `expires` date, which
+/// count as drift unless `expired` is [`Expired::Warn`]. With `structured`,
+/// stdout is reserved for JSON or annotations and they go to stderr.
+fn report_expired(profile: &ProfileDefinition, expired: Expired, structured: bool) -> Outcome {
+    let exceptions = profile.expired_exceptions(Date::today());
+    if exceptions.is_empty() {
+        return Outcome::Success;
+    }
+
+    let messages = exceptions.iter().map(|exception| {
+        format!(
+            "{} expired on {}: {}",
+            exception.definition,
+            exception.expires,
+            exception.lints.join(", ")
+        )
+    });
+    if expired == Expired::Warn {
+        for message in messages {
+            color::warn(&message);
+        }
+        return Outcome::Success;
+    }
+
+    #[expect(clippy::print_stdout, reason = "this is the main program output")]
+    #[expect(clippy::print_stderr, reason = "errors go to stderr")]
+    for message in messages {
+        if structured {
+            eprintln!("{}", color::error(&message));
+        } else {
+            println!("{message}");
+        }
+    }
+    #[expect(clippy::print_stderr, reason = "errors go to stderr")]
+    {
+        eprintln!(
+            "{}",
+            color::error(
+                "exceptions of the profile expired, triage them again and update their `expires` dates"
+            )
+        );
+    }
+
+    Outcome::Drift
+}
+
+fn hook_manifest(
+    hook_args: &ManifestArgs,
+    profile: &ProfileDefinition,
+    manifest: &Manifest,
+) -> Result<Outcome> {
+    let workspace = hook_args.workspace(manifest)?;
+    let args = hook_args.package_args(manifest, workspace)?;
+
+    let mut buffer = String::new();
+    let response = hook_args
+        .source
+        .load_response(Some(&manifest.path), &mut buffer)?;
+    let all_lints = AllLints::from_response(&response);
+    check_deprecated(profile, &all_lints, args.strict)?;
+
+    let generated = generate(
+        &args,
+        profile,
+        &all_lints,
+        None,
+        Format::Toml,
+        hook_args.msrv(manifest)?,
+        &hook_args.layout,
+        workspace,
+        None,
+    )?;
+
+    let mut drifts = vec![(
+        manifest.path.clone(),
+        diff::compare(&manifest.content, &generated, table_prefix(workspace))?,
+    )];
+    let mut not_inheriting = Vec::new();
+
+    if workspace {
+        for (member, member_manifest) in member_manifests(manifest)? {
+            if !profile.members.contains_key(&member.name) {
+                if apply::inherit_workspace(&member_manifest.content)?.is_some() {
+                    not_inheriting.push(member_manifest.path);
+                }
+                continue;
+            }
+
+            let generated = hook_args.generate_member(profile, &all_lints, &member, manifest)?;
+            let drift = diff::compare(&member_manifest.content, &generated, table_prefix(false))?;
+            drifts.push((member_manifest.path, drift));
+        }
+    }
+
+    let extra = drifts.iter().any(|drift| !drift.1.extra.is_empty());
+    let messages: Vec<_> = drifts
+        .into_iter()
+        .flat_map(|(path, drift)| {
+            drift
+                .messages()
+                .into_iter()
+                .map(move |(line, message)| (path.clone(), line, message))
+        })
+        .chain(not_inheriting.into_iter().map(|path| {
+            (
+                path,
+                None,
+                "the package does not inherit the workspace lints".to_owned(),
+            )
+        }))
+        .collect();
+
+    if messages.is_empty() {
+        return Ok(Outcome::Success);
+    }
+
+    #[expect(clippy::print_stdout, reason = "this is the main program output")]
+    for (path, line, message) in messages {
+        match line {
+            Some(line) => println!("{}:{line}: {message}", path.display()),
+            None => println!("{}: {message}", path.display()),
+        }
+    }
+    #[expect(clippy::print_stderr, reason = "errors go to stderr")]
+    {
+        eprintln!(
+            "{}",
+            color::error(if extra {
+                "the lint tables drifted from the profile, run `cargo clippy-lints apply --prune`"
+            } else {
+                "the lint tables drifted from the profile, run `cargo clippy-lints apply`"
+            })
+        );
+    }
+
+    Ok(Outcome::Drift)
+}
+
+/// The members of the workspace `root`, from `cargo metadata`. If cargo
+/// cannot be run or fails, e.g. because a member inherits lints the
+/// workspace does not define yet, the `workspace.members` patterns are
+/// expanded instead.
+fn workspace_members(root: &Manifest) -> Result<Vec<workspace::Member>> {
+    match workspace::metadata(&root.path) {
+        Ok(members) => return Ok(members),
+        Err(clippy_lints::Error::Run { .. }) => {}
+        Err(err) => color::warn(&format!(
+            "{err}, falling back to the workspace.members patterns"
+        )),
+    }
+    Ok(workspace::from_patterns(&root.path)?)
+}
+
+fn write_manifest(path: &Path, content: String) -> Result<()> {
+    fs::write(path, content).with_context(|| format!("failed to write manifest {}", path.display()))
+}
+
+/// The members of the workspace `root` besides the root package, with their
+/// manifests.
+fn member_manifests(root: &Manifest) -> Result<Vec<(workspace::Member, Manifest)>> {
+    let root_path = fs::canonicalize(&root.path).unwrap_or_else(|_| root.path.clone());
+
+    let mut manifests = Vec::new();
+    for member in workspace_members(root)? {
+        // The root package is handled with the workspace tables.
+        if fs::canonicalize(&member.manifest_path).is_ok_and(|path| path == root_path) {
+            continue;
+        }
+        tracing::debug!(
+            "workspace member {} (edition {}, rust-version {}) at {}",
+            member.name,
+            member.edition,
+            member
+                .rust_version
+                .map_or_else(|| "unset".to_owned(), |version| version.to_string()),
+            member.manifest_path.display()
+        );
+
+        let path = member.manifest_path.clone();
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read manifest {}", path.display()))?;
+        manifests.push((member, Manifest { path, content }));
+    }
+    Ok(manifests)
+}
+
+/// Applies the member-specific configuration of the profile to every
+/// workspace member it defines settings for. The other members are made to
+/// inherit the workspace lints, unless they have lint settings already.
+fn apply_members(
+    options: &ApplyArgs,
+    profile: &ProfileDefinition,
+    all_lints: &AllLints<'_>,
+    manifest: &Manifest,
+) -> Result<()> {
+    let apply_args = &options.manifest;
+
+    for (member, member_manifest) in member_manifests(manifest)? {
+        let path = &member_manifest.path;
+        if !profile.members.contains_key(&member.name) {
+            if let Some(updated) = apply::inherit_workspace(&member_manifest.content)? {
+                options.write(path, &member_manifest.content, updated)?;
+            } else {
+                tracing::debug!(
+                    "leaving the lint settings of {} as they are",
+                    path.display()
+                );
+            }
+            continue;
+        }
+
+        let generated = apply_args.generate_member(profile, all_lints, &member, manifest)?;
+        let updated = apply::apply_member(&member_manifest.content, &generated, options.merge())?;
+        options.write(path, &member_manifest.content, updated)?;
+    }
+
+    Ok(())
+}
+
+fn run_apply(options: &ApplyArgs) -> Result<Outcome> {
+    let profile = options.manifest.load_profile()?;
+    options.manifest.for_each_manifest("applied", |manifest| {
+        apply_manifest(options, &profile, manifest)?;
+        Ok(Outcome::Success)
+    })
+}
+
+fn apply_manifest(
+    options: &ApplyArgs,
+    profile: &ProfileDefinition,
+    manifest: &Manifest,
+) -> Result<()> {
+    let apply_args = &options.manifest;
+
+    let workspace = apply_args.workspace(manifest)?;
+    let args = apply_args.package_args(manifest, workspace)?;
+
+    let mut buffer = String::new();
+    let response = apply_args
+        .source
+        .load_response(Some(&manifest.path), &mut buffer)?;
+    let all_lints = AllLints::from_response(&response);
+    check_deprecated(profile, &all_lints, args.strict)?;
+
+    let generated = generate(
+        &args,
+        profile,
+        &all_lints,
+        None,
+        Format::Toml,
+        apply_args.msrv(manifest)?,
+        &apply_args.layout,
+        workspace,
+        None,
+    )?;
+
+    let mut updated = apply::apply(&manifest.content, &generated, workspace, options.merge())?;
+    if workspace && manifest::package_name(&manifest.content)?.is_some() {
+        if let Some(inheriting) = apply::inherit_workspace(&updated)? {
+            updated = inheriting;
+        }
+    }
+    options.write(&manifest.path, &manifest.content, updated)?;
+
+    if workspace {
+        apply_members(options, profile, &all_lints, manifest)?;
+    }
+
+    Ok(())
+}
+
+fn run_list(list_args: &ListArgs) -> Result<()> {
+    let args = &list_args.args;
+
+    let profile = load_profile(args)?;
+
+    let mut buffer = String::new();
+
+    let response = list_args.source.load_response(None, &mut buffer)?;
+
+    let all_lints = AllLints::from_response(&response);
+
+    let config = resolve_config(args, &profile, &all_lints, args.msrv, None)?;
+
+    let mut entries: Vec<ListEntry<'_>> = all_lints
+        .iter()
+        .filter(|lint| {
+            list_args
+                .group
+                .as_ref()
+                .is_none_or(|group| lint.group == *group)
+        })
+        .map(|lint| ListEntry {
+            lint: lint.id.0,
+            group: lint.group.clone(),
+            default_level: lint.default_level,
+            level: config.clippy_level(lint),
+        })
+        .collect();
+    entries.sort_by(|a, b| (&a.group, a.lint).cmp(&(&b.group, b.lint)));
+
+    let output = match list_args.format {
+        ListFormat::Json => serde_json::to_string_pretty(&entries)?,
+        ListFormat::Table => {
+            let width = entries
+                .iter()
+                .map(|entry| entry.lint.len())
+                .chain(["LINT".len()])
+                .max()
+                .unwrap_or_default();
+
+            let color = color::enabled(&io::stdout());
+            let mut lines = vec![format!(
+                "{:<width$}  {:<11}  {:<7}  PROFILE",
+                "LINT", "GROUP", "DEFAULT"
+            )];
+            lines.extend(entries.iter().map(|entry| {
+                // Padded before coloring, the escape codes take no space.
+                let default_level = format!("{:<7}", entry.default_level.as_str());
+                format!(
+                    "{:<width$}  {:<11}  {}  {}",
+                    entry.lint,
+                    entry.group.as_str(),
+                    color::level(&default_level, Some(entry.default_level), color),
+                    color::level(
+                        entry.level.map_or("-", LintLevel::as_str),
+                        entry.level,
+                        color
+                    )
+                )
+            }));
+            lines.join("\n")
+        }
+    };
+
+    #[expect(clippy::print_stdout, reason = "this is the main program output")]
+    {
+        println!("{output}");
+    }
+
+    Ok(())
+}
+
+fn run_search(search_args: &SearchArgs) -> Result<()> {
+    let args = &search_args.args;
+
+    let profile = load_profile(args)?;
+    let mut buffer = String::new();
+    let response = search_args.source.load_response(None, &mut buffer)?;
+    let all_lints = AllLints::from_response(&response);
+    let config = resolve_config(args, &profile, &all_lints, args.msrv, None)?;
+
+    let entries: Vec<SearchEntry<'_>> = all_lints
+        .search(&search_args.keywords.join(" "))
+        .into_iter()
+        .filter(|lint| {
+            search_args
+                .group
+                .as_ref()
+                .is_none_or(|group| lint.group == *group)
+        })
+        .map(|lint| SearchEntry {
+            lint: lint.id.0,
+            group: lint.group.clone(),
+            level: config.clippy_level(lint).unwrap_or(lint.default_level),
+            summary: lint
+                .summary()
+                .map(|summary| summary.split_whitespace().collect::<Vec<_>>().join(" "))
+                .unwrap_or_default(),
+        })
+        .collect();
+
+    let output = match search_args.format {
+        ListFormat::Json => serde_json::to_string_pretty(&entries)?,
+        ListFormat::Table => {
+            if entries.is_empty() {
+                tracing::info!("no lints match {}", search_args.keywords.join(" "));
+                return Ok(());
+            }
+
+            let width = entries
+                .iter()
+                .map(|entry| entry.lint.len())
+                .chain(["LINT".len()])
+                .max()
+                .unwrap_or_default();
+
+            let color = color::enabled(&io::stdout());
+            let mut lines = vec![format!(
+                "{:<width$}  {:<11}  {:<7}  SUMMARY",
+                "LINT", "GROUP", "PROFILE"
+            )];
+            lines.extend(entries.iter().map(|entry| {
+                // Padded before coloring, the escape codes take no space.
+                let level = format!("{:<7}", entry.level.as_str());
+                format!(
+                    "{:<width$}  {:<11}  {}  {}",
+                    entry.lint,
+                    entry.group.as_str(),
+                    color::level(&level, Some(entry.level), color),
+                    entry.summary
+                )
+            }));
+            lines.join("\n")
+        }
+    };
+
+    #[expect(clippy::print_stdout, reason = "this is the main program output")]
+    {
+        println!("{output}");
+    }
+
+    Ok(())
+}
+
+fn run_compare(compare_args: &CompareArgs) -> Result<()> {
+    let args = &compare_args.args;
+    let against_args = Args {
+        profile: compare_args.against.clone(),
+        ..args.clone()
+    };
+
+    let profile = load_profile(args)?;
+    let against_profile = load_profile(&against_args)?;
+
+    let mut buffer = String::new();
+
+    let response = compare_args.source.load_response(None, &mut buffer)?;
+
+    let all_lints = AllLints::from_response(&response);
+
+    let config = resolve_config(args, &profile, &all_lints, args.msrv, None)?;
+    let against_config = resolve_config(
+        &against_args,
+        &against_profile,
+        &all_lints,
+        against_args.msrv,
+        None,
+    )?;
+
+    let mut entries: Vec<CompareEntry<'_>> = all_lints
+        .iter()
+        .map(|lint| CompareEntry {
+            lint: lint.id.0,
+            group: lint.group.clone(),
+            level: config.clippy_level(lint).unwrap_or(lint.default_level),
+            against: against_config
+                .clippy_level(lint)
+                .unwrap_or(lint.default_level),
+        })
+        .filter(|entry| entry.level != entry.against)
+        .collect();
+    entries.sort_by(|a, b| (&a.group, a.lint).cmp(&(&b.group, b.lint)));
+
+    let name = args.profile.join("+");
+    let against_name = compare_args.against.join("+");
+
+    let output = match compare_args.format {
+        ListFormat::Json => serde_json::to_string_pretty(&entries)?,
+        ListFormat::Table if entries.is_empty() => {
+            format!("{name} and {against_name} set every lint to the same level")
+        }
+        ListFormat::Table => {
+            let width = entries
+                .iter()
+                .map(|entry| entry.lint.len())
+                .chain(["LINT".len()])
+                .max()
+                .unwrap_or_default();
+            let level_width = entries
+                .iter()
+                .map(|entry| entry.level.as_str().len())
+                .chain([name.len()])
+                .max()
+                .unwrap_or_default();
+
+            let color = color::enabled(&io::stdout());
+            let mut lines = vec![format!(
+                "{:<width$}  {:<11}  {name:<level_width$}  {against_name}",
+                "LINT", "GROUP"
+            )];
+            lines.extend(entries.iter().map(|entry| {
+                // Padded before coloring, the escape codes take no space.
+                let level = format!("{:<level_width$}", entry.level.as_str());
+                format!(
+                    "{:<width$}  {:<11}  {}  {}",
+                    entry.lint,
+                    entry.group.as_str(),
+                    color::level(&level, Some(entry.level), color),
+                    color::level(entry.against.as_str(), Some(entry.against), color)
+                )
+            }));
+            lines.join("\n")
+        }
+    };
+
+    #[expect(clippy::print_stdout, reason = "this is the main program output")]
+    {
+        println!("{output}");
+    }
+
+    Ok(())
+}
+
+fn run_new_lints(source_args: &SourceArgs) -> Result<()> {
+    let mut buffer = String::new();
+    let response = source_args.load_unlocked_response(None, &mut buffer)?;
+    let current = KnownLints::from_all_lints(&AllLints::from_response(&response));
+
+    let release = source_args.release();
+
+    let report = match KnownLints::load_previous(release)? {
+        Some(previous) => {
+            let changes = LintChanges::between(&previous, &current, &RenamedLints::bundled()?);
+            if changes.is_empty() {
+                "no lints were added or removed".to_owned()
+            } else {
+                changes.report(ReportFormat::Text)
+            }
+        }
+        None => format!(
+            "no previous lint set recorded, recorded {} lints",
+            current.0.len()
+        ),
+    };
+
+    current.store(release)?;
+
+    #[expect(clippy::print_stdout, reason = "this is the main program output")]
+    {
+        println!("{report}");
+    }
+
+    Ok(())
+}
+
+fn run_update(update_args: &UpdateArgs) -> Result<()> {
+    let path = project_file(None, LOCKFILE_NAME);
+    let previous = Lockfile::read(&path)?;
+
+    // Skip the cache, the lockfile is meant to pin the latest lint data.
+    let source = SourceArgs {
+        refresh: true,
+        ..update_args.source.clone()
+    };
+    let mut buffer = String::new();
+    let lockfile = Lockfile::from_response(&source.load_unlocked_response(None, &mut buffer)?);
+    lockfile.write(&path)?;
+
+    let version = |lockfile: &Lockfile| {
+        lockfile
+            .clippy_version
+            .clone()
+            .unwrap_or_else(|| "(unknown version)".to_owned())
+    };
+
+    tracing::info!(
+        "pinned {} lints of clippy {} in {}",
+        lockfile.lints.len(),
+        version(&lockfile),
+        path.display()
+    );
+
+    let Some(previous) = previous else {
+        return Ok(());
+    };
+
+    let changes = LintChanges::between(
+        &previous.known_lints(),
+        &lockfile.known_lints(),
+        &RenamedLints::bundled()?,
+    );
+    let report = match update_args.format {
+        _ if changes.is_empty() => "no lints were added, removed, renamed or regrouped".to_owned(),
+        ReportFormat::Text => format!(
+            "lint changes from clippy {} to {}:\n\n{}",
+            version(&previous),
+            version(&lockfile),
+            changes.report(ReportFormat::Text)
+        ),
+        ReportFormat::Markdown => format!(
+            "## Clippy lint changes from {} to {}\n\n{}",
+            version(&previous),
+            version(&lockfile),
+            changes.report(ReportFormat::Markdown)
+        ),
+    };
+
+    #[expect(clippy::print_stdout, reason = "this is the main program output")]
+    {
+        println!("{report}");
+    }
+
+    Ok(())
+}
+
+fn run_snapshot(snapshot_args: &SnapshotArgs) -> Result<()> {
+    let path = snapshot_args
+        .out
+        .clone()
+        .unwrap_or_else(|| project_file(None, SNAPSHOT_NAME));
+
+    // The bundled lint data and the lint list of the installed clippy are
+    // not kept as JSON text in the buffer.
+    let mut buffer = String::new();
+    let (lints, count) = {
+        let response = snapshot_args
+            .source
+            .load_unlocked_response(None, &mut buffer)?;
+        let lints = serde_json::to_string_pretty(&response.lints)
+            .context("failed to serialize the lint data")?;
+        (lints, response.lints.len())
+    };
+    let kept_json = !buffer.is_empty();
+    let content = if kept_json { &buffer } else { &lints };
+
+    let snapshot = Snapshot::new(content, snapshot_args.source.origin(None, kept_json))?;
+    snapshot.write(&path)?;
+
+    tracing::info!(
+        "wrote {count} lints of clippy {} to {}",
+        snapshot
+            .clippy_version
+            .as_deref()
+            .unwrap_or("(unknown version)"),
+        path.display()
+    );
+
+    Ok(())
+}
+
+#[cfg(feature = "interactive")]
+fn run_interactive(interactive_args: &InteractiveArgs) -> Result<()> {
+    let args = &interactive_args.args;
+
+    let mut profile = load_profile(args)?;
+
+    let mut buffer = String::new();
+
+    let response = interactive_args
+        .source
+        .load_response(args.manifest.as_deref(), &mut buffer)?;
+
+    let all_lints = AllLints::from_response(&response);
+
+    check_deprecated(&profile, &all_lints, args.strict)?;
+
+    let mut lints: Vec<&clippy_lints::Lint<'_>> = all_lints.iter().collect();
+    lints.sort_by(|a, b| (&a.group, a.id.0).cmp(&(&b.group, b.id.0)));
+
+    let changes = {
+        let config = profile.to_config(&all_lints, args.msrv)?;
+        let entries = lints
+            .into_iter()
+            .map(|lint| interactive::Entry {
+                lint,
+                profile_level: config.clippy_level(lint),
+                level: None,
+            })
+            .collect();
+
+        match interactive::review(&format!("profile {}", args.profile.join(" + ")), entries)? {
+            Some(changes) => changes,
+            None => return Ok(()),
+        }
+    };
+
+    profile
+        .clippy
+        .retain(|lint| !changes.contains_key(&lint.lint));
+    profile
+        .clippy
+        .extend(
+            changes
+                .into_iter()
+                .map(|(lint, level)| clippy_lints::ToolLintDefinition {
+                    lint,
+                    level,
+                    priority: None,
+                    reason: None,
+                    expires: None,
+                }),
+        );
+
+    let output = match interactive_args.output.emit {
+        Emit::Lints => {
+            let layout = &interactive_args.output.layout;
+            layout.adjust(&mut profile);
+            let mut config = resolve_config(args, &profile, &all_lints, args.msrv, None)?;
+            config.sort(layout.sort());
+            let output = config.render(
+                interactive_args.output.format,
+                layout.toml_options(args.workspace().unwrap_or_default()),
+            )?;
+            let output = if layout.canonical {
+                canonicalize(&output)
+            } else {
+                output
+            };
+            interactive_args
+                .output
+                .frame
+                .apply(output, interactive_args.output.format, &profile)?
+        }
+        Emit::ClippyToml => profile.clippy_toml.to_toml(args.msrv)?,
+    };
+
+    if let Some(ref path) = interactive_args.save {
+        if !interactive_args.output.force && path.exists() {
+            return Err(anyhow!(
+                "config file {} already exists, use --force to overwrite it",
+                path.display()
+            ));
+        }
+
+        let config_file = ConfigFile {
+            profile: [(args.profile.join("+"), profile)].into(),
+        };
+        fs::write(path, config_file.to_toml()?)
+            .with_context(|| format!("failed to write config file {}", path.display()))?;
+    }
+
+    interactive_args.output.write(&output)
+}
+
+impl AuditArgs {
+    /// The manifest to run clippy on, the arguments for its package and the
+    /// MSRV to resolve the profile for, as `generate` has them.
+    fn package(&self) -> Result<(Manifest, Args, Option<RustVersion>)> {
+        let manifest = Manifest::read(&manifest_path(self.manifest_path.as_ref())?)?;
+        let workspace = manifest.workspace_tables(self.args.workspace())?;
+        let args = self.args.for_package(&manifest, workspace)?.0;
+        let msrv = manifest.msrv(self.args.msrv)?;
+        Ok((manifest, args, msrv))
+    }
+}
+
+fn run_audit(audit_args: &AuditArgs) -> Result<()> {
+    let (manifest, args, msrv) = audit_args.package()?;
+    let manifest_path = manifest.path;
+
+    let profile = load_profile(&args)?;
+    let profile = package_profile(&profile, &args);
+    let mut buffer = String::new();
+    let response = audit_args
+        .source
+        .load_response(Some(&manifest_path), &mut buffer)?;
+    let all_lints = AllLints::from_response(&response);
+    let config = resolve_config(&args, &profile, &all_lints, msrv, None)?;
+
+    let flags: Vec<String> = config
+        .to_flags()
+        .split_whitespace()
+        .map(str::to_owned)
+        .collect();
+    let output = audit::run_clippy(&manifest_path, &flags)?;
+
+    let mut counts: Vec<(String, audit::LintCount)> =
+        audit::count_findings(&output).into_iter().collect();
+    counts.sort_by_key(|&(_, count)| Reverse(count.errors.saturating_add(count.warnings)));
+
+    let width = counts
+        .iter()
+        .map(|entry| entry.0.len())
+        .chain(["TOTAL".len()])
+        .max()
+        .unwrap_or_default();
+
+    let mut lines = vec![format!(
+        "{:<width$}  {:>8}  {:>6}",
+        "LINT", "WARNINGS", "ERRORS"
+    )];
+    lines.extend(counts.iter().map(|&(ref lint, count)| {
+        format!("{lint:<width$}  {:>8}  {:>6}", count.warnings, count.errors)
+    }));
+    let (warnings, errors) = counts
+        .iter()
+        .fold((0_usize, 0_usize), |(w, e), &(_, count)| {
+            (
+                w.saturating_add(count.warnings),
+                e.saturating_add(count.errors),
+            )
+        });
+    lines.push(format!("{:<width$}  {warnings:>8}  {errors:>6}", "TOTAL"));
+
+    #[expect(clippy::print_stdout, reason = "this is the main program output")]
+    {
+        println!("{}", lines.join("\n"));
+    }
+
+    Ok(())
+}
+
+fn run_audit_allows(audit_args: &AuditArgs) -> Result<()> {
+    let (manifest, args, msrv) = audit_args.package()?;
+    let manifest_path = manifest.path;
+
+    let profile = load_profile(&args)?;
+    let profile = package_profile(&profile, &args);
+    let mut buffer = String::new();
+    let response = audit_args
+        .source
+        .load_response(Some(&manifest_path), &mut buffer)?;
+    let all_lints = AllLints::from_response(&response);
+    let config = resolve_config(&args, &profile, &all_lints, msrv, None)?;
+
+    let allowed = config.allowed_clippy_lints();
+    if allowed.is_empty() {
+        tracing::info!("the profile allows no single clippy lints");
+        return Ok(());
+    }
+
+    let fired = audit::fired_lints(&manifest_path, &allowed)?;
+
+    let unused: Vec<&str> = allowed
+        .iter()
+        .map(|lint| lint.0)
+        .filter(|lint| !fired.contains(*lint))
+        .collect();
+
+    #[expect(clippy::print_stdout, reason = "this is the main program output")]
+    for lint in &unused {
+        println!("{lint}");
+    }
+    tracing::info!(
+        "{} of {} allowed lints never fire and can be removed from the profile",
+        unused.len(),
+        allowed.len()
+    );
+
+    Ok(())
+}
+
+fn run_ratchet(ratchet_args: &RatchetArgs) -> Result<()> {
+    let audit_args = &ratchet_args.audit;
+    let (manifest, args, msrv) = audit_args.package()?;
+    let manifest_path = manifest.path;
+
+    // Fail before running clippy if the result cannot be applied.
+    let target = if ratchet_args.apply {
+        let Some(ref config) = args.config else {
+            return Err(Failure::Usage.wrap(anyhow!("--apply needs a --config file")));
+        };
+        let name = args.profile.last().context("no profile given")?;
+        if !ConfigFile::load(config)?.profile.contains_key(name) {
+            return Err(Failure::Usage.wrap(anyhow!(
+                "profile {name} is not defined in {}, define one that extends it to ratchet",
+                config.display()
+            )));
+        }
+        Some((config, name))
+    } else {
+        None
+    };
+
+    let profile = load_profile(&args)?;
+    let profile = package_profile(&profile, &args);
+    let mut buffer = String::new();
+    let response = audit_args
+        .source
+        .load_response(Some(&manifest_path), &mut buffer)?;
+    let all_lints = AllLints::from_response(&response);
+    let config = resolve_config(&args, &profile, &all_lints, msrv, None)?;
+
+    let groups = if ratchet_args.group.is_empty() {
+        LintGroup::value_variants()
+            .iter()
+            .filter(|&group| *group != LintGroup::Restriction)
+            .cloned()
+            .collect()
+    } else {
+        ratchet_args.group.clone()
+    };
+    let candidates: Vec<_> = ratchet::candidates(&config, &all_lints, &groups, msrv)
+        .into_iter()
+        .map(|lint| lint.id)
+        .collect();
+    if candidates.is_empty() {
+        tracing::info!("the profile allows no lints of these groups");
+        return Ok(());
+    }
+
+    let fired = audit::fired_lints(&manifest_path, &candidates)?;
+    let quiet: Vec<&str> = candidates
+        .iter()
+        .map(|lint| lint.0)
+        .filter(|lint| !fired.contains(*lint))
+        .collect();
+
+    #[expect(clippy::print_stdout, reason = "this is the main program output")]
+    for lint in &quiet {
+        println!("{lint}");
+    }
+    tracing::info!(
+        "{} of {} allowed lints have no findings and can be promoted to warn",
+        quiet.len(),
+        candidates.len()
+    );
+
+    if let Some((path, name)) = target {
+        if quiet.is_empty() {
+            return Ok(());
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("failed to read config file {}", path.display()))?;
+        let reason = format!("no findings when ratcheted on {}", Date::today());
+        let updated = ratchet::promote(&content, name, &quiet, &reason)?;
+        fs::write(path, updated)
+            .with_context(|| format!("failed to write config file {}", path.display()))?;
+        tracing::info!(
+            "set them to warn in profile {name} of {}, run `cargo clippy-lints apply` to update the manifest",
+            path.display()
+        );
+    }
+
+    Ok(())
+}
+
+fn run_baseline(command: &BaselineCommand) -> Result<Outcome> {
+    let baseline_args = match *command {
+        BaselineCommand::Create(ref baseline_args) | BaselineCommand::Check(ref baseline_args) => {
+            baseline_args
+        }
+    };
+    let path = baseline_args.path();
+
+    if let BaselineCommand::Create(_) = *command {
+        let findings = baseline_args.findings()?;
+        Baseline::from_findings(&findings).write(&path)?;
+        tracing::info!("recorded {} findings in {}", findings.len(), path.display());
+        return Ok(Outcome::Success);
+    }
+
+    let Some(baseline) = Baseline::read(&path)? else {
+        return Err(Failure::Usage.wrap(anyhow!(
+            "no baseline at {}, create one with `cargo clippy-lints baseline create`",
+            path.display()
+        )));
+    };
+    let findings = baseline_args.findings()?;
+
+    let fixed = baseline.fixed(&findings);
+    if fixed > 0 {
+        tracing::info!(
+            "{fixed} recorded findings are fixed, run `cargo clippy-lints baseline create` to shrink the baseline"
+        );
+    }
+
+    let new = baseline.new_findings(&findings);
+    if new.is_empty() {
+        return Ok(Outcome::Success);
+    }
+
+    #[expect(clippy::print_stdout, reason = "this is the main program output")]
+    for finding in &new {
+        println!(
+            "{}:{}: {}: {}",
+            finding.file, finding.line, finding.lint, finding.message
+        );
+    }
+    #[expect(clippy::print_stderr, reason = "errors go to stderr")]
+    {
+        eprintln!(
+            "{}",
+            color::error(&format!(
+                "{} findings are not in the baseline {}",
+                new.len(),
+                path.display()
+            ))
+        );
+    }
+
+    Ok(Outcome::Drift)
+}
+
+/// Collects the Rust source files below `dir`, sorted by path.
+fn source_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let mut entries = fs::read_dir(dir)
+        .with_context(|| format!("failed to read directory {}", dir.display()))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<PathBuf>>>()
+        .with_context(|| format!("failed to read directory {}", dir.display()))?;
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            source_files(&path, files)?;
+            continue;
+        }
+        if path.extension() == Some(OsStr::new("rs")) {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+fn run_migrate(migrate_args: &MigrateArgs) -> Result<()> {
+    let manifest_path = manifest_path(migrate_args.manifest_path.as_ref())?;
+    let src = manifest_path
+        .parent()
+        .unwrap_or_else(|| Path::new(""))
+        .join("src");
+
+    let mut files = Vec::new();
+    source_files(&src, &mut files)?;
+
+    let mut crate_level: Vec<String> = Vec::new();
+    let mut roots: Vec<(PathBuf, String)> = Vec::new();
+
+    for path in files {
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let is_root = migrate::is_crate_root(path.strip_prefix(&src).unwrap_or(&path));
+
+        for attribute in migrate::find_allows(&content) {
+            let is_crate_level = is_root && attribute.is_crate_level();
+            #[expect(clippy::print_stdout, reason = "this is the main program output")]
+            {
+                println!(
+                    "{}:{}: {}{}",
+                    path.display(),
+                    attribute.line,
+                    attribute
+                        .lints
+                        .iter()
+                        .map(|lint| format!("clippy::{lint}"))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    if is_crate_level { " (crate level)" } else { "" },
+                );
+            }
+            if is_crate_level {
+                crate_level.extend(
+                    attribute
+                        .lints
+                        .into_iter()
+                        .filter(|lint| !migrate::is_group(lint)),
+                );
+            }
+        }
+
+        if is_root {
+            roots.push((path, content));
+        }
+    }
+
+    crate_level.sort();
+    crate_level.dedup();
+
+    if crate_level.is_empty() {
+        tracing::info!("no crate-level allows of single clippy lints to move");
+        return Ok(());
+    }
+    if !migrate_args.fix {
+        tracing::info!(
+            "run with --fix to move {} crate-level allows into {}",
+            crate_level.len(),
+            manifest_path.display()
+        );
+        return Ok(());
+    }
+
+    let manifest = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("failed to read manifest {}", manifest_path.display()))?;
+    write_manifest(
+        &manifest_path,
+        migrate::add_allows(&manifest, &crate_level)?,
+    )?;
+
+    for (path, content) in roots {
+        let migrated = migrate::remove_crate_level_allows(&content, &crate_level);
+        if migrated != content {
+            fs::write(&path, migrated)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+        }
+    }
+
+    tracing::info!(
+        "moved {} crate-level allows into {}",
+        crate_level.len(),
+        manifest_path.display()
+    );
+
+    Ok(())
+}
+
+fn run_init(init_args: &InitArgs) -> Result<()> {
+    let path = match init_args.output {
+        Some(ref path) => path.clone(),
+        None => project_file(None, "clippy-lints.toml"),
+    };
+
+    if !init_args.force && path.exists() {
+        return Err(anyhow!(
+            "config file {} already exists, use --force to overwrite it",
+            path.display()
+        ));
+    }
+
+    let builtin = init_args.profile.name();
+    let name = init_args.name.clone().unwrap_or_else(|| builtin.to_owned());
+
+    let config_file = ConfigFile {
+        profile: [(name.clone(), ProfileDefinition::builtin(init_args.profile)?)].into(),
+    };
+    let content = format!(
+        "# Generated from the built-in profile {}. Use it with\n\
+         # cargo clippy-lints --config {} --profile {name}\n\n{}",
+        builtin,
+        path.file_name()
+            .unwrap_or(path.as_os_str())
+            .to_string_lossy(),
+        config_file.to_toml()?
+    );
+
+    fs::write(&path, content)
+        .with_context(|| format!("failed to write config file {}", path.display()))?;
+
+    tracing::info!("wrote profile {name} to {}", path.display());
+
+    Ok(())
+}
+
+fn run_import(import_args: &ImportArgs) -> Result<()> {
+    let manifest_path = manifest_path(import_args.manifest_path.as_ref())?;
+    let manifest = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("failed to read manifest {}", manifest_path.display()))?;
+
+    let to_stdout = import_args.output == Path::new("-");
+    if !to_stdout && !import_args.force && import_args.output.exists() {
+        return Err(anyhow!(
+            "config file {} already exists, use --force to overwrite it",
+            import_args.output.display()
+        ));
+    }
+
+    let mut buffer = String::new();
+    let response = import_args
+        .source
+        .load_response(Some(&manifest_path), &mut buffer)?;
+    let all_lints = AllLints::from_response(&response);
+
+    let imported = import::from_manifest(&manifest, &all_lints)
+        .with_context(|| format!("failed to import {}", manifest_path.display()))?;
+    for skipped in &imported.skipped {
+        color::warn(&format!("leaving out {skipped}"));
+    }
+
+    let name = &import_args.name;
+    let config_file = ConfigFile {
+        profile: [(name.clone(), imported.profile)].into(),
+    };
+    let content = format!(
+        "# Imported from {}. Use it with\n\
+         # cargo clippy-lints --config {} --profile {name}\n\n{}",
+        manifest_path.display(),
+        if to_stdout {
+            "clippy-lints.toml".into()
+        } else {
+            import_args
+                .output
+                .file_name()
+                .unwrap_or(import_args.output.as_os_str())
+                .to_string_lossy()
+        },
+        config_file.to_toml()?
+    );
+
+    if to_stdout {
+        #[expect(clippy::print_stdout, reason = "this is the main program output")]
+        {
+            print!("{content}");
+        }
+        return Ok(());
+    }
+
+    fs::write(&import_args.output, content).with_context(|| {
+        format!(
+            "failed to write config file {}",
+            import_args.output.display()
+        )
+    })?;
+
+    tracing::info!("wrote profile {name} to {}", import_args.output.display());
+
+    Ok(())
+}
+
+fn run_validate(validate_args: &ValidateArgs) -> Result<Outcome> {
+    let path = manifest_path(validate_args.manifest_path.as_ref())?;
+    let manifest = fs::read_to_string(&path)
+        .with_context(|| format!("failed to read manifest {}", path.display()))?;
+
+    let mut buffer = String::new();
+
+    let response = validate_args
+        .source
+        .load_response(Some(&path), &mut buffer)?;
+    let all_lints = AllLints::from_response(&response);
+
+    let problems = validate::validate(&manifest, &all_lints);
+    if problems.is_empty() {
+        return Ok(Outcome::Success);
+    }
+
+    #[expect(clippy::print_stdout, reason = "this is the main program output")]
+    for problem in problems {
+        match validate_args.format {
+            ProblemFormat::Text => {
+                println!("{}:{}: {}", path.display(), problem.line, problem.message);
+            }
+            ProblemFormat::Github => {
+                println!(
+                    "{}",
+                    github_error(&path, Some(problem.line), &problem.message)
+                );
+            }
+        }
+    }
+
+    Ok(Outcome::Drift)
+}
+
+/// Lists the untriaged lints of the profile, see
+/// [`ProfileDefinition::untriaged_lints`]. Returns whether there are any.
+fn report_untriaged(
+    profile: &ProfileDefinition,
+    all_lints: &AllLints<'_>,
+    msrv: Option<RustVersion>,
+) -> bool {
+    let untriaged = profile.untriaged_lints(all_lints, msrv);
+    if untriaged.is_empty() {
+        return false;
+    }
+
+    #[expect(clippy::print_stdout, reason = "this is the main program output")]
+    for lint in &untriaged {
+        println!("untriaged {} lint {}", lint.group, lint.id);
+    }
+    #[expect(clippy::print_stderr, reason = "errors go to stderr")]
+    {
+        eprintln!(
+            "{}",
+            color::error(&format!(
+                "{} lints of exhaustively listed groups need triage, add them to the exceptions or defaults of the profile",
+                untriaged.len()
+            ))
+        );
+    }
+
+    true
+}
+
+fn run_verify(verify_args: &VerifyArgs) -> Result<Outcome> {
+    let args = &verify_args.args;
+    let mut profile = load_profile(args)?;
+    verify_args.layout.adjust(&mut profile);
+
+    let mut buffer = String::new();
+    let response = verify_args.source.load_response(None, &mut buffer)?;
+    let all_lints = AllLints::from_response(&response);
+
+    // Untriaged lints, differences and expired exceptions are all reported
+    // before failing.
+    let untriaged =
+        if verify_args.require_triage && report_untriaged(&profile, &all_lints, args.msrv) {
+            Outcome::Drift
+        } else {
+            Outcome::Success
+        };
+
+    check_deprecated(&profile, &all_lints, args.strict)?;
+
+    let generated = generate(
+        args,
+        &profile,
+        &all_lints,
+        None,
+        verify_args.format,
+        verify_args.args.msrv,
+        &verify_args.layout,
+        verify_args.args.workspace().unwrap_or_default(),
+        Some(&verify_args.frame),
+    )?;
+
+    let outcome = compare_file(&verify_args.file, &generated)?;
+    Ok(untriaged
+        .or(outcome)
+        .or(report_expired(&profile, verify_args.expired, false)))
+}
+
+/// Prints how the file at `path` differs from the `generated` configuration.
+fn compare_file(path: &Path, generated: &str) -> Result<Outcome> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+
+    // Files written by this tool end with a newline the output lacks.
+    let Some(diff) = diff::unified(
+        content.strip_suffix('\n').unwrap_or(&content),
+        generated,
+        &path.display().to_string(),
+        "generated",
+        color::enabled(&io::stdout()),
+    ) else {
+        return Ok(Outcome::Success);
+    };
+
+    #[expect(clippy::print_stdout, reason = "this is the main program output")]
+    {
+        print!("{diff}");
+    }
+    #[expect(clippy::print_stderr, reason = "errors go to stderr")]
+    {
+        eprintln!(
+            "{}",
+            color::error(&format!(
+                "{} does not match the generated configuration",
+                path.display()
+            ))
+        );
+    }
+
+    Ok(Outcome::Drift)
+}
+
+/// Whether a command that compares configurations found differences.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Outcome {
+    Success,
+    Drift,
+}
+
+impl Outcome {
+    /// Drift if either of the outcomes is.
+    fn or(self, other: Self) -> Self {
+        if self == Self::Drift {
+            self
+        } else {
+            other
+        }
+    }
+}
+
+/// Errors with a dedicated exit code, see [`EXIT_CODES`].
+#[derive(Clone, Copy, Debug)]
+enum Failure {
+    Usage,
+    Data,
+}
+
+impl Failure {
+    fn code(self) -> u8 {
+        match self {
+            Self::Usage => 2,
+            Self::Data => 3,
+        }
+    }
+
+    fn wrap(self, error: anyhow::Error) -> anyhow::Error {
+        anyhow::Error::new(Classified {
+            failure: self,
+            error,
+        })
+    }
+}
+
+/// An error tagged with its [`Failure`]. It displays like the wrapped error,
+/// so the tag does not show up in the message.
+struct Classified {
+    failure: Failure,
+    error: anyhow::Error,
+}
+
+impl fmt::Debug for Classified {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.error, f)
+    }
+}
+
+impl fmt::Display for Classified {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+impl Error for Classified {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.error.source()
+    }
+}
+
+fn exit_code(error: &anyhow::Error) -> u8 {
+    error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<Classified>())
+        .map_or(4, |classified| classified.failure.code())
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(Outcome::Success) => ExitCode::SUCCESS,
+        Ok(Outcome::Drift) => ExitCode::from(1),
+        Err(err) => {
+            #[expect(
+                clippy::print_stderr,
+                clippy::use_debug,
+                reason = "the debug format of anyhow includes the causes"
+            )]
+            {
+                eprintln!("{} {err:?}", color::error("Error:"));
+            }
+            ExitCode::from(exit_code(&err))
+        }
+    }
+}
+
+/// The command line arguments. Cargo runs `cargo clippy-lints` as
+/// `cargo-clippy-lints clippy-lints`, the second argument is skipped.
+fn args() -> impl Iterator<Item = OsString> {
+    std::env::args_os()
+        .enumerate()
+        .filter(|&(index, ref arg)| index != 1 || arg != "clippy-lints")
+        .map(|(_, arg)| arg)
+}
+
+fn run() -> Result<Outcome> {
+    let cli = Cli::parse_from(args());
+    color::init(cli.color);
+    logging::init(cli.verbose, cli.quiet);
+
+    let command = match cli.command {
+        Some(command) => command,
+        None => Command::Generate(GenerateArgs {
+            args: cli.args.ok_or_else(|| {
+                Failure::Usage.wrap(anyhow!("either a subcommand or --profile is required"))
+            })?,
+            source: cli.source,
+            output: cli.output,
+        }),
+    };
+
+    match command {
+        Command::Generate(generate_args) if generate_args.output.emit == Emit::ClippyToml => {
+            let output = load_profile(&generate_args.args)?
+                .clippy_toml
+                .to_toml(generate_args.args.msrv)?;
+            generate_args.output.write(&output)?;
+        }
+        Command::Generate(generate_args) => {
+            let args = &generate_args.args;
+            let mut profile = load_profile(args)?;
+            generate_args.output.layout.adjust(&mut profile);
+
+            let mut buffer = String::new();
+            let response = generate_args.source.load_response(None, &mut buffer)?;
+            let all_lints = AllLints::from_response(&response);
+            check_deprecated(&profile, &all_lints, args.strict)?;
+
+            let output = generate(
+                args,
+                &profile,
+                &all_lints,
+                None,
+                generate_args.output.format,
+                generate_args.args.msrv,
+                &generate_args.output.layout,
+                generate_args.args.workspace().unwrap_or_default(),
+                Some(&generate_args.output.frame),
+            )?;
+            generate_args.output.write(&output)?;
+        }
+        Command::Diff(diff_args) => return run_diff(&diff_args),
+        Command::Hook(hook_args) => return run_hook(&hook_args),
+        Command::Apply(apply_args) => return run_apply(&apply_args),
+        Command::NewLints(source_args) => run_new_lints(&source_args)?,
+        Command::Update(update_args) => run_update(&update_args)?,
+        Command::Snapshot(snapshot_args) => run_snapshot(&snapshot_args)?,
+        Command::List(list_args) => run_list(&list_args)?,
+        Command::Search(search_args) => run_search(&search_args)?,
+        Command::Compare(compare_args) => run_compare(&compare_args)?,
+        Command::Verify(verify_args) => return run_verify(&verify_args),
+        Command::Validate(validate_args) => return run_validate(&validate_args),
+        Command::Audit(audit_args) => run_audit(&audit_args)?,
+        Command::AuditAllows(audit_args) => run_audit_allows(&audit_args)?,
+        Command::Ratchet(ratchet_args) => run_ratchet(&ratchet_args)?,
+        Command::Baseline(baseline_command) => return run_baseline(&baseline_command),
+        Command::Init(init_args) => run_init(&init_args)?,
+        Command::Import(import_args) => run_import(&import_args)?,
+        Command::Migrate(migrate_args) => run_migrate(&migrate_args)?,
+        Command::Completions { shell } => {
+            // The completions need a single word, cargo runs `cargo clippy-lints`
+            // as `cargo-clippy-lints`.
+            let mut command = Cli::command();
+            let bin_name = command
+                .get_bin_name()
+                .unwrap_or_else(|| command.get_name())
+                .replace(' ', "-");
+            clap_complete::generate(shell, &mut command, bin_name, &mut io::stdout());
+        }
+        #[cfg(feature = "interactive")]
+        Command::Interactive(interactive_args) => run_interactive(&interactive_args)?,
+    }
+
+    Ok(Outcome::Success)
 }
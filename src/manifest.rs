@@ -0,0 +1,648 @@
+use std::fmt;
+#[cfg(feature = "native")]
+use std::{fs, io, path::Path};
+
+use clap::ValueEnum as _;
+use toml::{Table, Value};
+
+use crate::{
+    config::{Config, ConfigGroup, Setting, SingleLintConfig, CLIPPY},
+    date::Date,
+    error::{Error, Result},
+    lint::{LintGroup, LintId, LintLevel},
+    version::RustVersion,
+};
+
+fn get<'a>(table: &'a Table, path: &[&str]) -> Option<&'a Value> {
+    let (last, parents) = path.split_last()?;
+    let mut table = table;
+    for key in parents {
+        table = table.get(*key)?.as_table()?;
+    }
+    table.get(*last)
+}
+
+/// The role of a manifest within its workspace.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ManifestKind {
+    /// A workspace root without a package of its own.
+    VirtualWorkspace,
+    /// A workspace root that is also a package.
+    RootPackage,
+    /// A package inheriting settings from a workspace.
+    Member,
+    /// A package that is not part of a workspace, or at least inherits
+    /// nothing from it.
+    Package,
+}
+
+impl ManifestKind {
+    /// Whether lints of this manifest belong into `[workspace.lints]`.
+    pub fn is_workspace(self) -> bool {
+        match self {
+            Self::VirtualWorkspace | Self::RootPackage => true,
+            Self::Member | Self::Package => false,
+        }
+    }
+}
+
+/// Determines the kind of a manifest. Members are only recognized by
+/// inheriting something from the workspace, e.g. `lints.workspace = true` or
+/// `version.workspace = true`.
+pub fn kind(manifest: &str) -> Result<ManifestKind> {
+    let manifest: Table = toml::from_str(manifest).map_err(Error::manifest)?;
+
+    let inherits = |table: Option<&Value>| {
+        table.and_then(Value::as_table).is_some_and(|table| {
+            table.contains_key("workspace")
+                || table.values().any(|value| {
+                    value
+                        .as_table()
+                        .is_some_and(|value| value.contains_key("workspace"))
+                })
+        })
+    };
+
+    Ok(
+        match (
+            manifest.contains_key("workspace"),
+            manifest.contains_key("package"),
+        ) {
+            (true, false) => ManifestKind::VirtualWorkspace,
+            (true, true) => ManifestKind::RootPackage,
+            (false, _) if inherits(manifest.get("package")) || inherits(manifest.get("lints")) => {
+                ManifestKind::Member
+            }
+            (false, _) => ManifestKind::Package,
+        },
+    )
+}
+
+/// Reads the `rust-version` of a manifest. `package.rust-version` takes
+/// precedence over `workspace.package.rust-version`, unless it is inherited
+/// from the workspace.
+pub fn rust_version(manifest: &str) -> Result<Option<RustVersion>> {
+    let manifest: Table = toml::from_str(manifest).map_err(Error::manifest)?;
+
+    [
+        &["package", "rust-version"][..],
+        &["workspace", "package", "rust-version"][..],
+    ]
+    .into_iter()
+    .find_map(|path| get(&manifest, path).and_then(Value::as_str))
+    .map(str::parse)
+    .transpose()
+    .map_err(Error::parse("rust-version in manifest"))
+}
+
+/// The Rust version pinned by a toolchain file in `dir` or its parents.
+///
+/// Like rustup, the closest `rust-toolchain.toml` or `rust-toolchain` file
+/// wins. `None` if there is no such file or it names a channel like `stable`
+/// instead of a version.
+#[cfg(feature = "native")]
+pub fn toolchain_version(dir: &Path) -> Result<Option<RustVersion>> {
+    for dir in dir.ancestors() {
+        for name in ["rust-toolchain.toml", "rust-toolchain"] {
+            let path = dir.join(name);
+            let content = match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(Error::io("read", &path)(err)),
+            };
+
+            // The legacy `rust-toolchain` file may only contain the channel.
+            let channel = match toml::from_str::<Table>(&content) {
+                Ok(toolchain) => get(&toolchain, &["toolchain", "channel"])
+                    .and_then(Value::as_str)
+                    .map(str::to_owned),
+                Err(_) if !content.contains('=') => Some(content.trim().to_owned()),
+                Err(err) => return Err(Error::parse(path.display().to_string())(err)),
+            };
+
+            return Ok(channel.and_then(|channel| channel.parse().ok()));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Reads `package.name` of a manifest, `None` for virtual manifests.
+pub fn package_name(manifest: &str) -> Result<Option<String>> {
+    let manifest: Table = toml::from_str(manifest).map_err(Error::manifest)?;
+
+    Ok(get(&manifest, &["package", "name"])
+        .and_then(Value::as_str)
+        .map(str::to_owned))
+}
+
+/// Reads the `workspace.members` patterns of a manifest.
+pub fn workspace_members(manifest: &str) -> Result<Vec<String>> {
+    let manifest: Table = toml::from_str(manifest).map_err(Error::manifest)?;
+
+    get(&manifest, &["workspace", "members"])
+        .and_then(Value::as_array)
+        .map_or(&[][..], Vec::as_slice)
+        .iter()
+        .map(|member| {
+            member.as_str().map(str::to_owned).ok_or_else(|| {
+                Error::InvalidManifest("workspace members must be strings".to_owned())
+            })
+        })
+        .collect()
+}
+
+/// The targets of a package and whether it is published, as far as they
+/// matter for the levels of lints.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PackageTargets {
+    pub lib: bool,
+    pub bin: bool,
+    /// `false` if `package.publish` is `false` or an empty list.
+    pub publish: bool,
+}
+
+/// A lint level that follows from the targets of a package.
+#[derive(Clone, Debug)]
+pub struct Adjustment {
+    pub lint: &'static str,
+    /// `None` if the level of the profile is kept.
+    pub level: Option<LintLevel>,
+    pub reason: &'static str,
+}
+
+/// Detects the targets of the package of a manifest in `dir`. `None` for
+/// virtual manifests.
+///
+/// Besides the `[lib]` and `[[bin]]` tables, `src/lib.rs`, `src/main.rs` and
+/// `src/bin` count, unless `autolib` or `autobins` turn the discovery off.
+#[cfg(feature = "native")]
+pub fn package_targets(manifest: &str, dir: &Path) -> Result<Option<PackageTargets>> {
+    let manifest: Table = toml::from_str(manifest).map_err(Error::manifest)?;
+
+    if !manifest.contains_key("package") {
+        return Ok(None);
+    }
+
+    let auto = |key: &str| {
+        get(&manifest, &["package", key])
+            .and_then(Value::as_bool)
+            .unwrap_or(true)
+    };
+
+    let lib = manifest.contains_key("lib") || auto("autolib") && dir.join("src/lib.rs").is_file();
+    let bin = get(&manifest, &["bin"])
+        .and_then(Value::as_array)
+        .is_some_and(|bins| !bins.is_empty())
+        || auto("autobins") && (dir.join("src/main.rs").is_file() || dir.join("src/bin").is_dir());
+    let publish = get(&manifest, &["package", "publish"]).is_none_or(|publish| match *publish {
+        Value::Boolean(publish) => publish,
+        Value::Array(ref registries) => !registries.is_empty(),
+        _ => true,
+    });
+
+    Ok(Some(PackageTargets { lib, bin, publish }))
+}
+
+impl PackageTargets {
+    /// The levels to change for this package. Adjustments only allow lints
+    /// that do not matter for the package, otherwise the profile decides.
+    pub fn adjustments(self) -> Vec<Adjustment> {
+        let (print_level, print_reason) = if self.bin && !self.lib {
+            (
+                Some(LintLevel::Allow),
+                "the package only has binaries, printing is their output",
+            )
+        } else {
+            (None, "the package has a library, which should not print")
+        };
+        let (metadata_level, metadata_reason) = if self.publish {
+            (
+                None,
+                "the package is published, crates.io shows its metadata",
+            )
+        } else {
+            (
+                Some(LintLevel::Allow),
+                "the package is not published, so it needs no metadata",
+            )
+        };
+
+        [
+            ("print_stdout", print_level, print_reason),
+            ("print_stderr", print_level, print_reason),
+            ("cargo_common_metadata", metadata_level, metadata_reason),
+        ]
+        .into_iter()
+        .map(|(lint, level, reason)| Adjustment {
+            lint,
+            level,
+            reason,
+        })
+        .collect()
+    }
+}
+
+impl fmt::Display for PackageTargets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let targets = match (self.lib, self.bin) {
+            (true, true) => "library and binaries",
+            (true, false) => "library",
+            (false, true) => "binaries",
+            (false, false) => "no targets",
+        };
+        let publish = if self.publish {
+            "published"
+        } else {
+            "not published"
+        };
+        write!(f, "{targets}, {publish}")
+    }
+}
+
+/// An entry of a lints table.
+#[derive(Clone, Debug)]
+pub struct LintEntry {
+    /// The lint or lint group.
+    pub name: String,
+    pub level: LintLevel,
+    pub priority: Option<isize>,
+    /// The comment lines directly above the entry, without the `# `.
+    pub comment: Option<String>,
+    /// The comment following the entry on its line.
+    pub trailing_comment: Option<String>,
+    /// The date of an `# expires <date>` comment line above the entry.
+    pub expires: Option<Date>,
+    /// The line of the entry in the manifest, starting at 1.
+    pub line: usize,
+}
+
+/// Entries of a lints table that are not separated by blank lines, with the
+/// comment above them, e.g. `# enabled groups`.
+#[derive(Clone, Debug, Default)]
+pub struct LintBlock {
+    pub comment: Option<String>,
+    pub entries: Vec<LintEntry>,
+}
+
+/// A `[lints.<tool>]` table.
+#[derive(Clone, Debug)]
+pub struct LintTable {
+    pub tool: String,
+    pub blocks: Vec<LintBlock>,
+}
+
+/// The lint tables of a manifest, with their comments, so they can be turned
+/// into a [`Config`] and written again.
+#[derive(Clone, Debug, Default)]
+pub struct LintTables {
+    pub tables: Vec<LintTable>,
+}
+
+/// The level and priority of an entry of a lints table.
+pub(crate) fn lint_value(
+    tool: &str,
+    lint: &str,
+    value: &Value,
+) -> Result<(LintLevel, Option<isize>)> {
+    let (level, priority) = match *value {
+        Value::String(_) => (value, None),
+        Value::Table(ref table) => (
+            table.get("level").ok_or_else(|| {
+                Error::InvalidManifest(format!("lint {tool}.{lint} has no level"))
+            })?,
+            table.get("priority"),
+        ),
+        _ => {
+            return Err(Error::InvalidManifest(format!(
+                "lint {tool}.{lint} has an invalid value"
+            )))
+        }
+    };
+
+    let level = match level.clone().try_into() {
+        Ok(LintLevel::None) | Err(_) => {
+            return Err(Error::InvalidManifest(format!(
+                "lint {tool}.{lint} has the unsupported level {level}"
+            )))
+        }
+        Ok(level) => level,
+    };
+    let priority = priority
+        .map(|priority| {
+            priority
+                .as_integer()
+                .and_then(|priority| isize::try_from(priority).ok())
+                .ok_or_else(|| {
+                    Error::InvalidManifest(format!("lint {tool}.{lint} has an invalid priority"))
+                })
+        })
+        .transpose()?;
+
+    Ok((level, priority))
+}
+
+/// The dotted name of a table header like `[lints.clippy]`, `None` for other
+/// lines.
+fn table_header(line: &str) -> Option<String> {
+    let name = line.strip_prefix('[')?.trim_start_matches('[');
+    let name = name.split(']').next().unwrap_or_default();
+    Some(
+        name.split('.')
+            .map(|key| key.trim().trim_matches('"'))
+            .collect::<Vec<_>>()
+            .join("."),
+    )
+}
+
+impl LintTables {
+    /// Parses the `[<prefix>.<tool>]` tables of `manifest`, e.g. with the
+    /// prefix `lints` or `workspace.lints`.
+    ///
+    /// The tables are scanned line by line to keep the comments, so entries
+    /// have to be on lines of their own, as in the generated tables. The
+    /// first comment line of a block is taken as its comment, further ones
+    /// as the comment of the entry below them, except for `# expires <date>`
+    /// lines, see [`LintEntry::expires`]. Comments linking to the
+    /// documentation of a lint are left out, they are generated.
+    pub fn parse(manifest: &str, prefix: &str) -> Result<Self> {
+        let document: Table = toml::from_str(manifest).map_err(Error::manifest)?;
+
+        let mut tables: Vec<LintTable> = Vec::new();
+        let mut in_table = false;
+        let mut comments: Vec<&str> = Vec::new();
+        let mut expires = None;
+
+        for (index, text) in manifest.lines().enumerate() {
+            let text = text.trim();
+
+            if let Some(name) = table_header(text) {
+                in_table = false;
+                comments.clear();
+                expires = None;
+                if let Some(tool) = name
+                    .strip_prefix(prefix)
+                    .and_then(|name| name.strip_prefix('.'))
+                    .filter(|tool| !tool.contains('.'))
+                {
+                    tables.push(LintTable {
+                        tool: tool.to_owned(),
+                        blocks: vec![LintBlock::default()],
+                    });
+                    in_table = true;
+                }
+                continue;
+            }
+            let Some(table) = tables.last_mut().filter(|_| in_table) else {
+                continue;
+            };
+            let Some(block) = table.blocks.last_mut() else {
+                continue;
+            };
+
+            if text.is_empty() {
+                if !block.entries.is_empty() || block.comment.is_some() {
+                    table.blocks.push(LintBlock::default());
+                }
+                comments.clear();
+                expires = None;
+                continue;
+            }
+
+            if let Some(comment) = text.strip_prefix('#') {
+                let comment = comment.strip_prefix(' ').unwrap_or(comment);
+                if let Some(date) = comment
+                    .strip_prefix("expires ")
+                    .and_then(|date| date.parse().ok())
+                {
+                    expires = Some(date);
+                    continue;
+                }
+                if block.entries.is_empty() && block.comment.is_none() && comments.is_empty() {
+                    block.comment = Some(comment.to_owned());
+                } else {
+                    comments.push(comment);
+                }
+                continue;
+            }
+
+            let Some((key, value)) = text.split_once('=') else {
+                return Err(Error::InvalidManifest(format!(
+                    "line {} of {prefix}.{} is not an entry",
+                    index.saturating_add(1),
+                    table.tool
+                )));
+            };
+            let name = key.trim().trim_matches('"');
+            let (value, trailing_comment) = match value.split_once('#') {
+                Some((value, comment)) => (value, Some(comment.trim().to_owned())),
+                None => (value, None),
+            };
+            let value: Value = value.trim().parse().map_err(Error::manifest)?;
+            let (level, priority) = lint_value(&table.tool, name, &value)?;
+
+            let docs_link = format!("#{name}");
+            comments.retain(|comment| !(comment.contains("://") && comment.ends_with(&docs_link)));
+            block.entries.push(LintEntry {
+                name: name.to_owned(),
+                level,
+                priority,
+                comment: (!comments.is_empty()).then(|| comments.join("\n")),
+                trailing_comment,
+                expires: expires.take(),
+                line: index.saturating_add(1),
+            });
+            comments.clear();
+        }
+
+        for table in &mut tables {
+            table.blocks.retain(|block| !block.entries.is_empty());
+
+            let path: Vec<&str> = prefix.split('.').chain([table.tool.as_str()]).collect();
+            let expected = get(&document, &path)
+                .and_then(Value::as_table)
+                .map_or(0, Table::len);
+            let found: usize = table.blocks.iter().map(|block| block.entries.len()).sum();
+            if expected != found {
+                return Err(Error::InvalidManifest(format!(
+                    "{prefix}.{} has entries that are not on lines of their own",
+                    table.tool
+                )));
+            }
+        }
+
+        Ok(Self { tables })
+    }
+
+    /// The configuration the tables set, with a [`ConfigGroup`] per block.
+    /// Clippy entries named after a known group or `all` are group settings.
+    /// The comments of entries become their reasons, trailing comments their
+    /// descriptions.
+    pub fn to_config(&self) -> Config<'_> {
+        let groups = self
+            .tables
+            .iter()
+            .flat_map(|table| {
+                table.blocks.iter().map(|block| ConfigGroup {
+                    tool: &table.tool,
+                    comment: block.comment.clone(),
+                    settings: block
+                        .entries
+                        .iter()
+                        .map(|entry| entry.to_setting(&table.tool))
+                        .collect(),
+                })
+            })
+            .collect();
+
+        Config { groups }
+    }
+}
+
+impl LintEntry {
+    fn to_setting(&self, tool: &str) -> Setting<'_> {
+        if tool == CLIPPY && (self.name == "all" || LintGroup::from_str(&self.name, false).is_ok())
+        {
+            return Setting::group(LintGroup::from_name(&self.name), self.level, self.priority);
+        }
+
+        Setting::Single(SingleLintConfig {
+            lint: LintId(&self.name),
+            priority: self.priority.into(),
+            level: self.level,
+            description: self.trailing_comment.as_deref(),
+            reason: self.comment.as_deref(),
+            expires: self.expires,
+            version: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"
+[package]
+name = "demo"
+
+[lints.clippy]
+# enabled groups
+pedantic = { level = "warn", priority = -1 }
+
+# pedantic overrides
+# reads badly
+# https://rust-lang.github.io/rust-clippy/master/index.html#module_name_repetitions
+# expires 2025-09-01
+module_name_repetitions = "allow" # names repeat the module
+
+[lints.rust]
+unsafe_code = "deny"
+
+[dependencies]
+serde = "1"
+"#;
+
+    #[test]
+    fn parse_keeps_blocks_and_comments() {
+        let tables = LintTables::parse(MANIFEST, "lints").expect("the tables are valid");
+
+        let tools: Vec<&str> = tables
+            .tables
+            .iter()
+            .map(|table| table.tool.as_str())
+            .collect();
+        assert_eq!(tools, ["clippy", "rust"]);
+
+        let clippy = tables.tables.first().expect("there is a clippy table");
+        let comments: Vec<Option<&str>> = clippy
+            .blocks
+            .iter()
+            .map(|block| block.comment.as_deref())
+            .collect();
+        assert_eq!(
+            comments,
+            [Some("enabled groups"), Some("pedantic overrides")]
+        );
+
+        let pedantic = clippy
+            .blocks
+            .first()
+            .and_then(|block| block.entries.first())
+            .expect("the group is an entry");
+        assert_eq!(pedantic.name, "pedantic");
+        assert_eq!(pedantic.level, LintLevel::Warn);
+        assert_eq!(pedantic.priority, Some(-1));
+
+        let allowed = clippy
+            .blocks
+            .get(1)
+            .and_then(|block| block.entries.first())
+            .expect("the override is an entry");
+        assert_eq!(allowed.level, LintLevel::Allow);
+        assert_eq!(allowed.comment.as_deref(), Some("reads badly"));
+        assert_eq!(
+            allowed.trailing_comment.as_deref(),
+            Some("names repeat the module")
+        );
+        assert_eq!(allowed.expires, "2025-09-01".parse().ok());
+        assert_eq!(allowed.line, 13);
+    }
+
+    #[test]
+    fn parse_reads_workspace_tables_by_prefix() {
+        let manifest =
+            "[workspace.lints.clippy]\nall = \"deny\"\n\n[lints.clippy]\nall = \"warn\"\n";
+        let tables = LintTables::parse(manifest, "workspace.lints").expect("the tables are valid");
+
+        let entries: Vec<(&str, LintLevel)> = tables
+            .tables
+            .iter()
+            .flat_map(|table| &table.blocks)
+            .flat_map(|block| &block.entries)
+            .map(|entry| (entry.name.as_str(), entry.level))
+            .collect();
+        assert_eq!(entries, [("all", LintLevel::Deny)]);
+    }
+
+    #[test]
+    fn parse_rejects_entries_it_cannot_read() {
+        let err = LintTables::parse(
+            "[lints.clippy]\nall = { level = \"warn\", priority = -1 }\nlints = { a = 1 }\n",
+            "lints",
+        )
+        .expect_err("the value is no level");
+        assert_eq!(err.to_string(), "lint clippy.lints has no level");
+
+        let err = LintTables::parse(
+            "[lints.clippy]\nall = \"warn\"\n\n[lints.clippy.pedantic]\nlevel = \"warn\"\n",
+            "lints",
+        )
+        .expect_err("the group is a table of its own");
+        assert!(
+            err.to_string().contains("not on lines of their own"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn to_config_tells_groups_from_lints() {
+        let tables = LintTables::parse(MANIFEST, "lints").expect("the tables are valid");
+        let config = tables.to_config();
+
+        let settings: Vec<(&str, bool)> = config
+            .groups
+            .iter()
+            .flat_map(|group| &group.settings)
+            .map(|setting| (setting.name(), matches!(*setting, Setting::Group(_))))
+            .collect();
+        assert_eq!(
+            settings,
+            [
+                ("pedantic", true),
+                ("module_name_repetitions", false),
+                ("unsafe_code", false)
+            ]
+        );
+    }
+}
@@ -0,0 +1,178 @@
+use std::path::{Component, Path};
+
+use clap::ValueEnum as _;
+use toml_edit::DocumentMut;
+
+use crate::{
+    apply::table_at,
+    error::{Error, Result},
+    lint::LintGroup,
+};
+
+const CLIPPY_PREFIX: &str = "clippy::";
+
+/// An `allow` attribute with clippy lints. Only attributes on a line of
+/// their own are recognized.
+#[derive(Debug)]
+pub struct AllowAttribute {
+    /// The line number, starting at 1.
+    pub line: usize,
+    /// Whether this is an inner attribute (`#![allow(...)]`) that applies to
+    /// the enclosing module.
+    pub inner: bool,
+    /// Whether the attribute is indented, i.e. not at the top level of the
+    /// file.
+    pub indented: bool,
+    /// The clippy lints, without the `clippy::` prefix.
+    pub lints: Vec<String>,
+}
+
+impl AllowAttribute {
+    /// Whether the attribute applies to the whole crate, given it is in a
+    /// crate root.
+    pub fn is_crate_level(&self) -> bool {
+        self.inner && !self.indented
+    }
+}
+
+/// Splits the attribute arguments at commas outside of string literals,
+/// e.g. in `reason = "..."`.
+fn split_arguments(arguments: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut start = 0;
+
+    for (index, c) in arguments.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            ',' if !in_string => {
+                items.push(arguments.get(start..index).unwrap_or_default().trim());
+                start = index.saturating_add(1);
+            }
+            _ => (),
+        }
+    }
+    items.push(arguments.get(start..).unwrap_or_default().trim());
+
+    items.retain(|item| !item.is_empty());
+    items
+}
+
+/// Parses `#[allow(...)]` or `#![allow(...)]` into whether it is an inner
+/// attribute and its arguments.
+fn parse_allow(line: &str) -> Option<(bool, Vec<&str>)> {
+    let line = line.trim();
+    let (inner, rest) = match line.strip_prefix("#![") {
+        Some(rest) => (true, rest),
+        None => (false, line.strip_prefix("#[")?),
+    };
+    let arguments = rest.strip_prefix("allow")?.trim_start();
+    let arguments = arguments.strip_prefix('(')?.strip_suffix(")]")?;
+    Some((inner, split_arguments(arguments)))
+}
+
+/// Finds the `allow` attributes with clippy lints in a source file.
+pub fn find_allows(source: &str) -> Vec<AllowAttribute> {
+    source
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let (inner, arguments) = parse_allow(line)?;
+            let lints: Vec<String> = arguments
+                .iter()
+                .filter_map(|argument| argument.strip_prefix(CLIPPY_PREFIX))
+                .map(str::to_owned)
+                .collect();
+            (!lints.is_empty()).then(|| AllowAttribute {
+                line: index.saturating_add(1),
+                inner,
+                indented: line.starts_with(char::is_whitespace),
+                lints,
+            })
+        })
+        .collect()
+}
+
+/// Whether the source file `path`, relative to the `src` directory of a
+/// package, is the root of one of its default targets.
+pub fn is_crate_root(path: &Path) -> bool {
+    let components: Vec<&str> = path
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => name.to_str(),
+            _ => None,
+        })
+        .collect();
+
+    matches!(
+        *components.as_slice(),
+        ["lib.rs" | "main.rs"] | ["bin", _] | ["bin", _, "main.rs"]
+    )
+}
+
+/// Whether a lint name is a lint group. Allowing a group in `Cargo.toml`
+/// needs a priority and then behaves differently from the attribute, so
+/// groups are not migrated.
+pub fn is_group(lint: &str) -> bool {
+    lint == "all" || LintGroup::from_str(lint, false).is_ok()
+}
+
+/// Removes `lints` from the crate-level `allow` attributes of `source`.
+/// Attributes left without lints are removed completely.
+pub fn remove_crate_level_allows(source: &str, lints: &[String]) -> String {
+    let mut output = String::with_capacity(source.len());
+
+    for line in source.split_inclusive('\n') {
+        let attribute =
+            parse_allow(line).filter(|&(inner, _)| inner && !line.starts_with(char::is_whitespace));
+        let Some((_, arguments)) = attribute else {
+            output.push_str(line);
+            continue;
+        };
+
+        let remaining: Vec<&str> = arguments
+            .into_iter()
+            .filter(|argument| {
+                argument
+                    .strip_prefix(CLIPPY_PREFIX)
+                    .is_none_or(|lint| !lints.iter().any(|removed| removed == lint))
+            })
+            .collect();
+
+        if remaining.iter().any(|argument| !argument.contains('=')) {
+            let ending = line
+                .strip_prefix(line.trim_end_matches(['\r', '\n']))
+                .unwrap_or_default();
+            output.push_str("#![allow(");
+            output.push_str(&remaining.join(", "));
+            output.push_str(")]");
+            output.push_str(ending);
+        }
+    }
+
+    output
+}
+
+/// Adds `lints` to `[lints.clippy]` of a package manifest as allowed.
+pub fn add_allows(manifest: &str, lints: &[String]) -> Result<String> {
+    let mut document: DocumentMut = manifest.parse().map_err(Error::manifest)?;
+
+    let table = table_at(document.as_table_mut(), &["lints"])?;
+    if table.contains_key("workspace") {
+        return Err(Error::InvalidManifest(
+            "manifest inherits its lints from the workspace, add the lints to the workspace instead"
+                .to_owned(),
+        ));
+    }
+
+    let clippy = table_at(table, &["clippy"])?;
+    clippy.set_implicit(false);
+    for lint in lints {
+        clippy.insert(lint, toml_edit::value("allow"));
+    }
+
+    Ok(document.to_string())
+}
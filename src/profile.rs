@@ -0,0 +1,1149 @@
+use std::collections::{BTreeMap, HashSet};
+#[cfg(feature = "native")]
+use std::{fs, path::Path};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    clippy_config::ClippyConfig,
+    condition::Condition,
+    config::{
+        Config, ConfigGroup, Exceptions, PrioritySetting, Setting, SingleLintConfig, CLIPPY, RUST,
+        RUSTDOC,
+    },
+    date::Date,
+    error::{Error, Result},
+    lint::{AllLints, Applicability, Lint, LintFilter, LintGroup, LintId, LintLevel},
+    provider::CommandProvider,
+    renames::RenamedLints,
+    version::RustVersion,
+};
+
+/// The built-in profiles.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum Profile {
+    Publish,
+    Personal,
+    /// Denies or warns about everything feasible, as a starting point to
+    /// prune from.
+    Strict,
+    /// For `no_std` crates: prefers `core` and `alloc` over `std` and
+    /// denies lints for code that can panic.
+    Embedded,
+}
+
+impl Profile {
+    /// The name used on the command line and in `extends`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Publish => "publish",
+            Self::Personal => "personal",
+            Self::Strict => "strict",
+            Self::Embedded => "embedded",
+        }
+    }
+
+    /// The definition of the profile, in the format of a profile in a config
+    /// file. Bundled at build time from `data/profiles`.
+    fn definition(self) -> &'static str {
+        match self {
+            Self::Publish => include_str!("../data/profiles/publish.toml"),
+            Self::Personal => include_str!("../data/profiles/personal.toml"),
+            Self::Strict => include_str!("../data/profiles/strict.toml"),
+            Self::Embedded => include_str!("../data/profiles/embedded.toml"),
+        }
+    }
+}
+
+/// The level of a whole lint group.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct GroupDefinition {
+    pub group: LintGroup,
+    pub level: LintLevel,
+    pub priority: Option<isize>,
+}
+
+/// Lints of a group that are allowed despite the group level.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct OverrideDefinition {
+    pub group: LintGroup,
+    pub comment: Option<String>,
+    pub allow: Vec<String>,
+    /// The priority of the allowed lints.
+    pub priority: Option<isize>,
+    /// When the allowed lints are due for review, see
+    /// [`ProfileDefinition::expired_exceptions`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<Date>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExceptionsDefinition {
+    pub level: LintLevel,
+    pub lints: Vec<String>,
+    pub comment: Option<String>,
+    pub priority: Option<isize>,
+    /// When the exceptions are due for review.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<Date>,
+}
+
+/// A group whose lints are all listed individually: the exceptions at their
+/// own level, every other lint at `level`.
+///
+/// Works for any group, e.g. to review every pedantic lint. Lints allowed by
+/// the overrides of the group stay allowed, and a level for the whole group
+/// in `groups` is left out.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExhaustiveDefinition {
+    pub group: LintGroup,
+    pub level: LintLevel,
+    pub comment: Option<String>,
+    /// The priority of the lints that are not exceptions.
+    pub priority: Option<isize>,
+    pub exceptions: ExceptionsDefinition,
+    /// Lints that were reviewed and stay at `level`. Only needed to tell
+    /// reviewed lints from new ones, see
+    /// [`ProfileDefinition::untriaged_lints`].
+    #[serde(default)]
+    pub defaults: Vec<String>,
+    /// Set the whole group to `level` and only list the exceptions, instead
+    /// of listing every lint. Lints introduced after the MSRV are then not
+    /// left out.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub compact: bool,
+}
+
+/// Clippy lints selected by their properties instead of their names, e.g.
+/// every machine-applicable pedantic lint, set to `level`.
+///
+/// Lints set individually by the overrides or exhaustive groups of the
+/// profile keep their level, and the individual `clippy` lints take
+/// precedence over the selection.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SelectionDefinition {
+    /// Groups whose lints are selected, all groups if empty.
+    #[serde(default)]
+    pub groups: Vec<LintGroup>,
+    /// Applicabilities whose lints are selected, any if empty.
+    #[serde(default)]
+    pub applicability: Vec<Applicability>,
+    pub level: LintLevel,
+    pub comment: Option<String>,
+    pub priority: Option<isize>,
+}
+
+impl SelectionDefinition {
+    pub fn filter(&self) -> LintFilter {
+        LintFilter {
+            groups: self.groups.clone(),
+            applicability: self.applicability.clone(),
+        }
+    }
+
+    fn describe(&self) -> String {
+        let mut parts: Vec<&str> = self
+            .applicability
+            .iter()
+            .map(|applicability| applicability.as_str())
+            .collect();
+        parts.extend(self.groups.iter().map(LintGroup::as_str));
+        if parts.is_empty() {
+            "selected lints".to_owned()
+        } else {
+            format!("selected {} lints", parts.join(" "))
+        }
+    }
+}
+
+/// A single lint with its own level. Only clippy lints are validated against
+/// the clippy lint data.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ToolLintDefinition {
+    pub lint: String,
+    pub level: LintLevel,
+    pub priority: Option<isize>,
+    /// Why the lint has this level, see [`ProfileDefinition::reasons`].
+    pub reason: Option<String>,
+    /// When the level is due for review.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<Date>,
+}
+
+/// Levels for clippy lints that only apply to projects matching a
+/// condition, e.g. allowing `print_stdout` in packages with a `cli` feature.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleDefinition {
+    /// When the rule applies, e.g. `feature("cli")`.
+    pub when: Condition,
+    pub comment: Option<String>,
+    #[serde(default)]
+    pub allow: Vec<String>,
+    #[serde(default)]
+    pub warn: Vec<String>,
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+/// Additional settings for a single workspace member, identified by its
+/// package name.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct MemberDefinition {
+    pub comment: Option<String>,
+    #[serde(default)]
+    pub allow: Vec<String>,
+}
+
+/// Everything that makes up a profile, either built-in or loaded from a
+/// config file.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileDefinition {
+    /// The profile this one is based on, either built-in or from the same
+    /// config file.
+    pub extends: Option<String>,
+    #[serde(default)]
+    pub groups: Vec<GroupDefinition>,
+    #[serde(default)]
+    pub overrides: Vec<OverrideDefinition>,
+    #[serde(default)]
+    pub exhaustive: Vec<ExhaustiveDefinition>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub selections: Vec<SelectionDefinition>,
+    /// Individual clippy lints, overriding the settings above.
+    #[serde(default)]
+    pub clippy: Vec<ToolLintDefinition>,
+    #[serde(default)]
+    pub rust: Vec<ToolLintDefinition>,
+    #[serde(default)]
+    pub rustdoc: Vec<ToolLintDefinition>,
+    #[serde(default)]
+    pub members: BTreeMap<String, MemberDefinition>,
+    /// Values for `clippy.toml`.
+    #[serde(default)]
+    pub clippy_toml: ClippyConfig,
+    /// Lints and groups that keep warning when warnings are escalated, see
+    /// [`Config::escalate_warnings`].
+    #[serde(default)]
+    pub soft: Vec<String>,
+    /// Why lints have the level the profile gives them, by lint name. The
+    /// reasons are written as comments above the entries of the lints, e.g.
+    /// to document the allowed lints of overrides.
+    #[serde(default)]
+    pub reasons: BTreeMap<String, String>,
+    /// Text written as a comment above the generated configuration, e.g. a
+    /// note that it is generated and how to regenerate it.
+    pub header: Option<String>,
+    /// Text written as a comment below the generated configuration.
+    pub footer: Option<String>,
+    /// Levels for projects matching a condition, layered over the settings
+    /// above.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<RuleDefinition>,
+    /// Commands whose settings are layered over those of the profile and
+    /// its rules, see [`CommandProvider`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub providers: Vec<CommandProvider>,
+}
+
+/// A TOML file with user-defined profiles in `[profile.<name>]` tables.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    #[serde(default)]
+    pub profile: BTreeMap<String, ProfileDefinition>,
+}
+
+impl ConfigFile {
+    #[cfg(feature = "native")]
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).map_err(Error::io("read config file", path))?;
+        toml::from_str(&content).map_err(Error::parse(format!("config file {}", path.display())))
+    }
+
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string(self).map_err(Error::serialize("config file"))
+    }
+}
+
+/// A lint the profile references under a name clippy has since changed.
+#[derive(Debug)]
+pub struct RenamedLint {
+    pub old: String,
+    /// The new name, with the `clippy::` prefix for clippy lints and without
+    /// one for lints that moved to rustc.
+    pub new: String,
+}
+
+/// Replaces each of `lints` that was renamed with its new name, or removes it
+/// if it is no longer a clippy lint. If that makes a lint appear twice, the
+/// last one is kept, as later entries come from profiles further down the
+/// inheritance chain.
+fn rename_all<T>(
+    lints: &mut Vec<T>,
+    name: impl Fn(&mut T) -> &mut String,
+    renames: &RenamedLints,
+    found: &mut Vec<RenamedLint>,
+) {
+    lints.retain_mut(|lint| {
+        let lint = name(lint);
+        let Some(new) = renames.get(lint) else {
+            return true;
+        };
+        found.push(RenamedLint {
+            old: lint.clone(),
+            new: new.to_owned(),
+        });
+        match new.strip_prefix("clippy::") {
+            Some(new) => {
+                new.clone_into(lint);
+                true
+            }
+            None => false,
+        }
+    });
+
+    let mut seen = HashSet::new();
+    lints.reverse();
+    lints.retain_mut(|lint| seen.insert(name(lint).clone()));
+    lints.reverse();
+}
+
+/// A clippy lint name in a profile that the lint data does not know.
+#[derive(Debug)]
+pub struct UnknownLint {
+    pub name: String,
+    /// The known lint with the most similar name, if one is close enough.
+    pub suggestion: Option<String>,
+}
+
+fn tool_lints_to_groups<'a>(
+    tool: &'a str,
+    lints: &'a [ToolLintDefinition],
+) -> Vec<ConfigGroup<'a>> {
+    if lints.is_empty() {
+        return Vec::new();
+    }
+
+    vec![ConfigGroup {
+        tool,
+        comment: None,
+        settings: lints
+            .iter()
+            .map(|lint| {
+                Setting::Single(SingleLintConfig {
+                    lint: LintId(&lint.lint),
+                    priority: lint.priority.into(),
+                    level: lint.level,
+                    description: None,
+                    reason: lint.reason.as_deref(),
+                    expires: lint.expires,
+                    version: None,
+                })
+            })
+            .collect(),
+    }]
+}
+
+/// Marks the single lints of `settings` as due for review at `expires`.
+fn set_expires(settings: &mut [Setting<'_>], expires: Option<Date>) {
+    for setting in settings {
+        if let Setting::Single(ref mut single_lint_config) = *setting {
+            single_lint_config.expires = expires;
+        }
+    }
+}
+
+/// Exceptions of a profile that are past their review date.
+#[derive(Debug, Serialize)]
+pub struct ExpiredException {
+    /// Where the exceptions are defined, e.g. `pedantic overrides`.
+    pub definition: String,
+    pub lints: Vec<String>,
+    pub expires: Date,
+}
+
+impl ProfileDefinition {
+    /// Looks up the profile `name` in the config file, falling back to the
+    /// built-in profiles, and resolves what it extends.
+    pub fn resolve(name: &str, config_file: Option<ConfigFile>) -> Result<Self> {
+        let mut profiles = config_file.map(|config_file| config_file.profile);
+        Self::resolve_chain(name, profiles.as_mut(), &mut Vec::new())
+    }
+
+    /// `chain` holds the names of the profiles that extend `name`, to detect
+    /// cycles. A profile extending its own name extends the built-in profile
+    /// of that name.
+    fn resolve_chain(
+        name: &str,
+        mut profiles: Option<&mut BTreeMap<String, Self>>,
+        chain: &mut Vec<String>,
+    ) -> Result<Self> {
+        let extends_itself = chain.last().is_some_and(|last| last == name);
+
+        if chain.iter().any(|extending| extending == name) && !extends_itself {
+            chain.push(name.to_owned());
+            return Err(Error::ProfileCycle(chain.clone()));
+        }
+
+        let definition = match profiles.as_deref_mut() {
+            Some(_) if extends_itself => None,
+            Some(profiles) => profiles.remove(name),
+            None => None,
+        };
+
+        let Some(mut definition) = definition else {
+            let profile = Profile::from_str(name, true)
+                .map_err(|_err| Error::UnknownProfile(name.to_owned()))?;
+            return Self::builtin(profile);
+        };
+
+        let Some(parent) = definition.extends.take() else {
+            return Ok(definition);
+        };
+
+        chain.push(name.to_owned());
+        let parent =
+            Self::resolve_chain(&parent, profiles, chain).map_err(|source| Error::Profile {
+                name: name.to_owned(),
+                source: Box::new(source),
+            })?;
+        chain.pop();
+
+        Ok(definition.inherit(parent))
+    }
+
+    /// Replaces the old names of renamed clippy lints with their new names.
+    /// Lints that moved to rustc are removed, they have to be configured as
+    /// rust lints instead. Selections pick lints by their properties and are
+    /// left as they are.
+    pub fn rename_lints(&mut self, renames: &RenamedLints) -> Vec<RenamedLint> {
+        let mut found = Vec::new();
+
+        for override_definition in &mut self.overrides {
+            rename_all(
+                &mut override_definition.allow,
+                |lint| lint,
+                renames,
+                &mut found,
+            );
+        }
+        for exhaustive in &mut self.exhaustive {
+            rename_all(
+                &mut exhaustive.exceptions.lints,
+                |lint| lint,
+                renames,
+                &mut found,
+            );
+            rename_all(&mut exhaustive.defaults, |lint| lint, renames, &mut found);
+        }
+        rename_all(&mut self.clippy, |lint| &mut lint.lint, renames, &mut found);
+        for member in self.members.values_mut() {
+            rename_all(&mut member.allow, |lint| lint, renames, &mut found);
+        }
+        for rule in &mut self.rules {
+            for lints in [&mut rule.allow, &mut rule.warn, &mut rule.deny] {
+                rename_all(lints, |lint| lint, renames, &mut found);
+            }
+        }
+        rename_all(&mut self.soft, |lint| lint, renames, &mut found);
+
+        // The reasons follow their lints, also to rustc.
+        self.reasons = std::mem::take(&mut self.reasons)
+            .into_iter()
+            .map(|(lint, reason)| match renames.get(&lint) {
+                Some(new) => (
+                    new.strip_prefix("clippy::").unwrap_or(new).to_owned(),
+                    reason,
+                ),
+                None => (lint, reason),
+            })
+            .collect();
+
+        found
+    }
+
+    /// Settings of `parent` that [`ProfileDefinition::inherit`] replaces with
+    /// a different value, described for humans.
+    pub fn conflicts(&self, parent: &Self) -> Vec<String> {
+        let mut conflicts = Vec::new();
+
+        for group in &self.groups {
+            if let Some(existing) = parent
+                .groups
+                .iter()
+                .find(|existing| existing.group == group.group)
+            {
+                if existing.level != group.level || existing.priority != group.priority {
+                    conflicts.push(format!(
+                        "group {} changes from {} to {}",
+                        group.group,
+                        existing.level.as_str(),
+                        group.level.as_str()
+                    ));
+                }
+            }
+        }
+
+        for exhaustive in &self.exhaustive {
+            if parent
+                .exhaustive
+                .iter()
+                .any(|existing| existing.group == exhaustive.group)
+            {
+                conflicts.push(format!("exhaustive group {} is replaced", exhaustive.group));
+            }
+        }
+
+        for (tool, lints, parent_lints) in [
+            ("clippy", &self.clippy, &parent.clippy),
+            ("rust", &self.rust, &parent.rust),
+            ("rustdoc", &self.rustdoc, &parent.rustdoc),
+        ] {
+            for lint in lints {
+                if let Some(existing) = parent_lints
+                    .iter()
+                    .find(|existing| existing.lint == lint.lint)
+                {
+                    if existing.level != lint.level || existing.priority != lint.priority {
+                        conflicts.push(format!(
+                            "{tool} lint {} changes from {} to {}",
+                            lint.lint,
+                            existing.level.as_str(),
+                            lint.level.as_str()
+                        ));
+                    }
+                }
+            }
+        }
+
+        for (key, value) in &self.clippy_toml.0 {
+            if parent
+                .clippy_toml
+                .0
+                .get(key)
+                .is_some_and(|existing| existing != value)
+            {
+                conflicts.push(format!("clippy.toml value {key} is replaced"));
+            }
+        }
+
+        conflicts
+    }
+
+    /// Merges `self` into `parent`. Settings of `self` take precedence over
+    /// those of `parent` for the same group, lint, member or key, allow lists
+    /// of the same group are combined unless they expire at different dates.
+    #[must_use]
+    pub fn inherit(self, mut parent: Self) -> Self {
+        for group in self.groups {
+            parent
+                .groups
+                .retain(|existing| existing.group != group.group);
+            parent.groups.push(group);
+        }
+
+        for override_definition in self.overrides {
+            match parent.overrides.iter_mut().find(|existing| {
+                existing.group == override_definition.group
+                    && existing.expires == override_definition.expires
+            }) {
+                Some(existing) => {
+                    for lint in override_definition.allow {
+                        if !existing.allow.contains(&lint) {
+                            existing.allow.push(lint);
+                        }
+                    }
+                    if override_definition.comment.is_some() {
+                        existing.comment = override_definition.comment;
+                    }
+                    if override_definition.priority.is_some() {
+                        existing.priority = override_definition.priority;
+                    }
+                }
+                None => parent.overrides.push(override_definition),
+            }
+        }
+
+        for exhaustive in self.exhaustive {
+            parent
+                .exhaustive
+                .retain(|existing| existing.group != exhaustive.group);
+            parent.exhaustive.push(exhaustive);
+        }
+
+        parent.selections.extend(self.selections);
+
+        for (lints, parent_lints) in [
+            (self.clippy, &mut parent.clippy),
+            (self.rust, &mut parent.rust),
+            (self.rustdoc, &mut parent.rustdoc),
+        ] {
+            for lint in lints {
+                parent_lints.retain(|existing| existing.lint != lint.lint);
+                parent_lints.push(lint);
+            }
+        }
+
+        for lint in self.soft {
+            if !parent.soft.contains(&lint) {
+                parent.soft.push(lint);
+            }
+        }
+
+        parent.members.extend(self.members);
+        parent.clippy_toml.0.extend(self.clippy_toml.0);
+        parent.reasons.extend(self.reasons);
+        if self.header.is_some() {
+            parent.header = self.header;
+        }
+        if self.footer.is_some() {
+            parent.footer = self.footer;
+        }
+        parent.rules.extend(self.rules);
+        parent.providers.extend(self.providers);
+
+        parent
+    }
+
+    /// The definition of a built-in profile. Like profiles from config
+    /// files, it can be checked against the lint data with
+    /// [`ProfileDefinition::unknown_lints`].
+    pub fn builtin(profile: Profile) -> Result<Self> {
+        toml::from_str(profile.definition())
+            .map_err(Error::parse(format!("built-in profile {}", profile.name())))
+    }
+
+    /// The lints in override and exception lists that are deprecated
+    /// according to the lint data.
+    pub fn deprecated_lints<'a>(&self, all_lints: &'a AllLints<'a>) -> Vec<&'a Lint<'a>> {
+        self.overrides
+            .iter()
+            .flat_map(|override_definition| &override_definition.allow)
+            .chain(
+                self.exhaustive
+                    .iter()
+                    .flat_map(|exhaustive| &exhaustive.exceptions.lints),
+            )
+            .filter_map(|lint| all_lints.get(LintId(lint)))
+            .filter(|lint| lint.group == LintGroup::Deprecated)
+            .collect()
+    }
+
+    /// The clippy lints referenced anywhere in the profile that do not exist
+    /// in the lint data at all, each with the closest known name.
+    pub fn unknown_lints(&self, all_lints: &AllLints<'_>) -> Vec<UnknownLint> {
+        let mut names: Vec<&String> = self
+            .overrides
+            .iter()
+            .flat_map(|override_definition| &override_definition.allow)
+            .chain(self.exhaustive.iter().flat_map(|exhaustive| {
+                exhaustive
+                    .exceptions
+                    .lints
+                    .iter()
+                    .chain(&exhaustive.defaults)
+            }))
+            .chain(self.clippy.iter().map(|lint| &lint.lint))
+            .chain(self.members.values().flat_map(|member| &member.allow))
+            .chain(
+                self.rules
+                    .iter()
+                    .flat_map(|rule| rule.allow.iter().chain(&rule.warn).chain(&rule.deny)),
+            )
+            .chain(
+                self.soft
+                    .iter()
+                    .filter(|name| LintGroup::from_str(name, false).is_err()),
+            )
+            .filter(|name| all_lints.get(LintId(name)).is_none())
+            .collect();
+        names.sort();
+        names.dedup();
+
+        names
+            .into_iter()
+            .map(|name| UnknownLint {
+                name: name.clone(),
+                suggestion: all_lints.closest(name).map(str::to_owned),
+            })
+            .collect()
+    }
+
+    /// The lints of exhaustively listed groups that are neither exceptions nor
+    /// listed as reviewed defaults, e.g. lints added by a new clippy release.
+    /// Lints introduced after `msrv` are left out.
+    pub fn untriaged_lints<'a>(
+        &self,
+        all_lints: &'a AllLints<'a>,
+        msrv: Option<RustVersion>,
+    ) -> Vec<&'a Lint<'a>> {
+        self.exhaustive
+            .iter()
+            .flat_map(|exhaustive| {
+                all_lints.iter().filter(move |lint| {
+                    let mentioned = |lints: &[String]| lints.iter().any(|name| *name == lint.id.0);
+                    lint.group == exhaustive.group
+                        && lint.is_available_in(msrv)
+                        && !mentioned(&exhaustive.exceptions.lints)
+                        && !mentioned(&exhaustive.defaults)
+                        && !self.overrides.iter().any(|override_definition| {
+                            override_definition.group == exhaustive.group
+                                && mentioned(&override_definition.allow)
+                        })
+                })
+            })
+            .collect()
+    }
+
+    /// The exceptions whose `expires` date lies before `today`, to triage
+    /// them again.
+    pub fn expired_exceptions(&self, today: Date) -> Vec<ExpiredException> {
+        let expired = |definition: String, lints: &[String], expires: Option<Date>| {
+            expires
+                .filter(|&expires| expires < today)
+                .map(|expires| ExpiredException {
+                    definition,
+                    lints: lints.to_vec(),
+                    expires,
+                })
+        };
+
+        let overrides = self.overrides.iter().filter_map(|override_definition| {
+            expired(
+                format!("{} overrides", override_definition.group),
+                &override_definition.allow,
+                override_definition.expires,
+            )
+        });
+        let exceptions = self.exhaustive.iter().filter_map(|exhaustive| {
+            expired(
+                format!("{} exceptions", exhaustive.group),
+                &exhaustive.exceptions.lints,
+                exhaustive.exceptions.expires,
+            )
+        });
+        let lints = [
+            (CLIPPY, &self.clippy),
+            (RUST, &self.rust),
+            (RUSTDOC, &self.rustdoc),
+        ]
+        .into_iter()
+        .flat_map(|(tool, lints)| {
+            lints.iter().filter_map(move |lint| {
+                expired(
+                    format!("{tool}::{}", lint.lint),
+                    std::slice::from_ref(&lint.lint),
+                    lint.expires,
+                )
+            })
+        });
+
+        overrides.chain(exceptions).chain(lints).collect()
+    }
+
+    /// Whether the profile lists every lint of `group` individually. The
+    /// level of the group itself is then left out of the configuration.
+    fn is_exhaustive(&self, group: &LintGroup) -> bool {
+        self.exhaustive
+            .iter()
+            .any(|exhaustive| exhaustive.group == *group)
+    }
+
+    /// Resolves the profile against the lint data, failing if it references
+    /// lints that do not exist in the expected group.
+    ///
+    /// Deprecated lints are left out, see
+    /// [`ProfileDefinition::deprecated_lints`]. Lints introduced after `msrv`
+    /// are left out of exhaustively expanded groups.
+    pub fn to_config<'a>(
+        &'a self,
+        all_lints: &'a AllLints<'a>,
+        msrv: Option<RustVersion>,
+    ) -> Result<Config<'a>> {
+        let is_deprecated = |lint: &&String| {
+            all_lints
+                .get(LintId(lint))
+                .is_some_and(|lint| lint.group == LintGroup::Deprecated)
+        };
+
+        let mut groups = vec![ConfigGroup {
+            tool: CLIPPY,
+            comment: Some("enabled groups".to_owned()),
+            settings: self
+                .groups
+                .iter()
+                .filter(|group| !self.is_exhaustive(&group.group))
+                .map(|group| Setting::group(group.group.clone(), group.level, group.priority))
+                .collect(),
+        }];
+
+        for override_definition in &self.overrides {
+            let lints: Vec<LintId<'a>> = override_definition
+                .allow
+                .iter()
+                .filter(|lint| !is_deprecated(lint))
+                .map(|lint| LintId(lint))
+                .collect();
+            let mut settings = Setting::allow(
+                all_lints,
+                &override_definition.group,
+                &lints,
+                override_definition.priority,
+            )?;
+            set_expires(&mut settings, override_definition.expires);
+            groups.push(ConfigGroup {
+                tool: CLIPPY,
+                comment: Some(
+                    override_definition
+                        .comment
+                        .clone()
+                        .unwrap_or_else(|| format!("{} overrides", override_definition.group)),
+                ),
+                settings,
+            });
+        }
+
+        for exhaustive in &self.exhaustive {
+            let mut split = Setting::split_group_exhaustive(
+                all_lints,
+                &exhaustive.group,
+                exhaustive.level,
+                exhaustive.priority.into(),
+                &Exceptions {
+                    level: exhaustive.exceptions.level,
+                    priority: exhaustive.exceptions.priority.into(),
+                    lints: exhaustive
+                        .exceptions
+                        .lints
+                        .iter()
+                        .filter(|lint| !is_deprecated(lint))
+                        .map(String::as_str)
+                        .collect::<Vec<&str>>()
+                        .into(),
+                },
+                msrv,
+            )?;
+            // The overrides of the group take precedence over its expansion.
+            let overridden = |setting: &Setting<'_>| {
+                self.overrides.iter().any(|override_definition| {
+                    override_definition.group == exhaustive.group
+                        && override_definition
+                            .allow
+                            .iter()
+                            .any(|lint| lint == setting.name())
+                })
+            };
+            split.exceptions.retain(|setting| !overridden(setting));
+            split.defaults.retain(|setting| !overridden(setting));
+            set_expires(&mut split.exceptions, exhaustive.exceptions.expires);
+            groups.push(ConfigGroup {
+                tool: CLIPPY,
+                comment: Some(
+                    exhaustive
+                        .exceptions
+                        .comment
+                        .clone()
+                        .unwrap_or_else(|| format!("{} exceptions", exhaustive.group)),
+                ),
+                settings: split.exceptions,
+            });
+            // The group gets the lowest priority, so the exceptions and
+            // overrides still apply.
+            if exhaustive.compact {
+                if let Some(enabled) = groups.first_mut() {
+                    enabled.settings.push(Setting::group(
+                        exhaustive.group.clone(),
+                        exhaustive.level,
+                        None,
+                    ));
+                }
+                continue;
+            }
+            groups.push(ConfigGroup {
+                tool: CLIPPY,
+                comment: Some(
+                    exhaustive
+                        .comment
+                        .clone()
+                        .unwrap_or_else(|| format!("{} defaults", exhaustive.group)),
+                ),
+                settings: split.defaults,
+            });
+        }
+
+        // Lints set individually so far keep their level, later selections
+        // take precedence over earlier ones.
+        let individual: HashSet<String> = groups
+            .iter()
+            .flat_map(|group| &group.settings)
+            .map(|setting| setting.name().to_owned())
+            .collect();
+        let first_selection = groups.len();
+        for selection in &self.selections {
+            let filter = selection.filter();
+            let settings: Vec<Setting<'a>> = all_lints
+                .filter(&filter)
+                .filter(|lint| {
+                    lint.group != LintGroup::Deprecated
+                        && lint.is_available_in(msrv)
+                        && !individual.contains(lint.id.0)
+                })
+                .map(|lint| {
+                    Setting::Single(SingleLintConfig {
+                        lint: lint.id,
+                        priority: selection.priority.into(),
+                        level: selection.level,
+                        description: None,
+                        reason: None,
+                        expires: None,
+                        version: lint.version,
+                    })
+                })
+                .collect();
+            for earlier in groups.iter_mut().skip(first_selection) {
+                earlier.settings.retain(|setting| {
+                    !settings
+                        .iter()
+                        .any(|selected| selected.name() == setting.name())
+                });
+            }
+            groups.push(ConfigGroup {
+                tool: CLIPPY,
+                comment: Some(
+                    selection
+                        .comment
+                        .clone()
+                        .unwrap_or_else(|| selection.describe()),
+                ),
+                settings,
+            });
+        }
+
+        groups.extend(tool_lints_to_groups(RUST, &self.rust));
+        groups.extend(tool_lints_to_groups(RUSTDOC, &self.rustdoc));
+        let mut config = Config { groups };
+
+        if !self.clippy.is_empty() {
+            config.override_lints(ConfigGroup {
+                tool: CLIPPY,
+                comment: Some("individual lints".to_owned()),
+                settings: self
+                    .clippy
+                    .iter()
+                    .map(|lint| {
+                        all_lints
+                            .get(LintId(&lint.lint))
+                            .map(|known| {
+                                Setting::Single(SingleLintConfig {
+                                    lint: LintId(&lint.lint),
+                                    priority: lint.priority.into(),
+                                    level: lint.level,
+                                    description: None,
+                                    reason: lint.reason.as_deref(),
+                                    expires: lint.expires,
+                                    version: known.version,
+                                })
+                            })
+                            .ok_or_else(|| Error::UnknownLint {
+                                lint: lint.lint.clone(),
+                                group: None,
+                            })
+                    })
+                    .collect::<Result<_>>()?,
+            });
+        }
+
+        config.add_reasons(&self.reasons);
+        config.assign_priorities();
+        config.trace_settings();
+
+        Ok(config)
+    }
+
+    /// Like [`ProfileDefinition::to_config`], with the settings of the
+    /// workspace member `member` added. These replace settings of the same
+    /// lints from the rest of the profile. Fails if the profile defines no such
+    /// member or the member allows unknown lints.
+    pub fn to_member_config<'a>(
+        &'a self,
+        member: &str,
+        all_lints: &'a AllLints<'a>,
+        msrv: Option<RustVersion>,
+    ) -> Result<Config<'a>> {
+        let member_definition = self
+            .members
+            .get(member)
+            .ok_or_else(|| Error::UnknownMember(member.to_owned()))?;
+
+        let mut config = self.to_config(all_lints, msrv)?;
+
+        config.override_lints(ConfigGroup {
+            tool: CLIPPY,
+            comment: Some(
+                member_definition
+                    .comment
+                    .clone()
+                    .unwrap_or_else(|| format!("{member} overrides")),
+            ),
+            settings: member_definition
+                .allow
+                .iter()
+                .map(|lint| {
+                    all_lints
+                        .get(LintId(lint))
+                        .map(|known| {
+                            Setting::Single(SingleLintConfig {
+                                lint: LintId(lint),
+                                priority: PrioritySetting::Unspecified,
+                                level: LintLevel::Allow,
+                                description: None,
+                                reason: None,
+                                expires: None,
+                                version: known.version,
+                            })
+                        })
+                        .ok_or_else(|| Error::UnknownLint {
+                            lint: lint.clone(),
+                            group: None,
+                        })
+                })
+                .collect::<Result<_>>()?,
+        });
+
+        config.add_reasons(&self.reasons);
+        config.assign_priorities();
+        config.trace_settings();
+
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn renames() -> RenamedLints {
+        RenamedLints(BTreeMap::from([
+            (
+                "block_in_if_condition_stmt".to_owned(),
+                "clippy::blocks_in_conditions".to_owned(),
+            ),
+            (
+                "mismatched_target_os".to_owned(),
+                "unexpected_cfgs".to_owned(),
+            ),
+        ]))
+    }
+
+    #[test]
+    fn renames_apply_to_rules() {
+        let mut profile: ProfileDefinition = toml::from_str(
+            r#"
+            [[rules]]
+            when = 'feature("cli")'
+            allow = ["block_in_if_condition_stmt"]
+            deny = ["mismatched_target_os", "print_stdout"]
+            "#,
+        )
+        .expect("the profile is valid");
+
+        let found = profile.rename_lints(&renames());
+
+        let rule = profile.rules.first().expect("the rule is kept");
+        assert_eq!(rule.allow, ["blocks_in_conditions"]);
+        assert_eq!(rule.deny, ["print_stdout"]);
+        let old: Vec<&str> = found.iter().map(|renamed| renamed.old.as_str()).collect();
+        assert_eq!(old, ["block_in_if_condition_stmt", "mismatched_target_os"]);
+    }
+
+    #[test]
+    fn renames_apply_to_reasons_and_keep_the_last_duplicate() {
+        let mut profile: ProfileDefinition = toml::from_str(
+            r#"
+            clippy = [
+                { lint = "block_in_if_condition_stmt", level = "warn" },
+                { lint = "blocks_in_conditions", level = "deny" },
+            ]
+
+            [reasons]
+            block_in_if_condition_stmt = "reads badly"
+            "#,
+        )
+        .expect("the profile is valid");
+
+        profile.rename_lints(&renames());
+
+        assert_eq!(profile.clippy.len(), 1);
+        assert!(profile.clippy.first().is_some_and(
+            |lint| lint.lint == "blocks_in_conditions" && lint.level == LintLevel::Deny
+        ));
+        assert_eq!(
+            profile
+                .reasons
+                .get("blocks_in_conditions")
+                .map(String::as_str),
+            Some("reads badly")
+        );
+    }
+
+    fn config_file(toml: &str) -> ConfigFile {
+        toml::from_str(toml).expect("the config file is valid")
+    }
+
+    #[test]
+    fn resolve_detects_cycles() {
+        let err = ProfileDefinition::resolve(
+            "a",
+            Some(config_file(
+                "[profile.a]\nextends = \"b\"\n[profile.b]\nextends = \"c\"\n[profile.c]\nextends = \"a\"\n",
+            )),
+        )
+        .expect_err("the profiles extend each other");
+
+        let mut messages = vec![err.to_string()];
+        let mut source = std::error::Error::source(&err);
+        while let Some(cause) = source {
+            messages.push(cause.to_string());
+            source = cause.source();
+        }
+        assert_eq!(
+            messages,
+            [
+                "failed to resolve profile a",
+                "failed to resolve profile b",
+                "failed to resolve profile c",
+                "profile inheritance cycle: a -> b -> c -> a",
+            ]
+        );
+    }
+
+    #[test]
+    fn resolve_extends_the_builtin_profile_of_the_same_name() {
+        let profile = ProfileDefinition::resolve(
+            "strict",
+            Some(config_file(
+                "[profile.strict]\nextends = \"strict\"\nclippy = [{ lint = \"print_stdout\", level = \"allow\" }]\n",
+            )),
+        )
+        .expect("extending the built-in profile is no cycle");
+
+        assert!(
+            !profile.groups.is_empty(),
+            "the built-in groups are inherited"
+        );
+        assert!(profile
+            .clippy
+            .iter()
+            .any(|lint| lint.lint == "print_stdout" && lint.level == LintLevel::Allow));
+    }
+}
@@ -0,0 +1,331 @@
+use std::path::PathBuf;
+#[cfg(feature = "native")]
+use std::{
+    cell::OnceCell,
+    fs,
+    io::Write as _,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+#[cfg(feature = "native")]
+use crate::profile::ProfileDefinition;
+
+use serde::{Deserialize, Serialize};
+use toml::{Table, Value};
+
+use crate::{
+    config::{Config, ConfigGroup, PrioritySetting, Setting, SingleLintConfig, CLIPPY},
+    error::{Error, Result},
+    lint::{AllLints, LintId, LintLevel},
+    manifest,
+    profile::RuleDefinition,
+    version::RustVersion,
+};
+
+/// What a [`ProfileProvider`] gets to know about the project it configures.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Project {
+    /// `package.name`, `None` for virtual workspaces.
+    pub name: Option<String>,
+    /// The workspace member the configuration is generated for, if any.
+    pub member: Option<String>,
+    /// The manifest the configuration is generated for, that of the member
+    /// for workspace members.
+    pub manifest_path: Option<PathBuf>,
+    pub rust_version: Option<RustVersion>,
+    /// The features declared in the manifest.
+    pub features: Vec<String>,
+    /// The crates in `[dependencies]`.
+    pub dependencies: Vec<String>,
+    /// `package.metadata`, e.g. to look up the team owning the package.
+    pub metadata: Table,
+}
+
+impl Project {
+    /// Reads the manifest at `path`.
+    #[cfg(feature = "native")]
+    pub fn from_manifest(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).map_err(Error::io("read manifest", path))?;
+        Ok(Self {
+            manifest_path: Some(path.to_owned()),
+            ..Self::parse(&content)?
+        })
+    }
+
+    /// Describes the package of the manifest `content`, without a
+    /// [`Project::manifest_path`].
+    pub fn parse(content: &str) -> Result<Self> {
+        let table: Table = toml::from_str(content).map_err(Error::manifest)?;
+        let keys = |key: &str| -> Vec<String> {
+            table
+                .get(key)
+                .and_then(Value::as_table)
+                .map(|table| table.keys().cloned().collect())
+                .unwrap_or_default()
+        };
+
+        Ok(Self {
+            name: manifest::package_name(content)?,
+            member: None,
+            manifest_path: None,
+            rust_version: manifest::rust_version(content)?,
+            features: keys("features"),
+            dependencies: keys("dependencies"),
+            metadata: table
+                .get("package")
+                .and_then(|package| package.get("metadata"))
+                .and_then(Value::as_table)
+                .cloned()
+                .unwrap_or_default(),
+        })
+    }
+}
+
+/// Custom profile logic, e.g. policies that depend on the name or the
+/// owner of a package. The settings of a provider are layered over those of
+/// the profile with [`Config::layer`].
+pub trait ProfileProvider {
+    /// The settings for `project`, which may borrow from the provider and
+    /// the lint data.
+    fn config<'a>(&'a self, all_lints: &'a AllLints<'a>, project: &Project) -> Result<Config<'a>>;
+}
+
+/// A provider from a config file: a command that gets the [`Project`] as
+/// JSON on stdin and prints a profile, in the format of the profiles of the
+/// config file and without `extends`.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct CommandProvider {
+    /// The program and its arguments.
+    command: Vec<String>,
+    /// The profiles printed by the command, one per project it ran for.
+    #[cfg(feature = "native")]
+    #[serde(skip)]
+    printed: OnceCell<Box<Printed>>,
+}
+
+/// The profile a [`CommandProvider`] printed for a project, followed by
+/// those printed for other projects. Entries are only ever appended, so the
+/// configurations borrowing from earlier ones stay valid.
+#[cfg(feature = "native")]
+#[derive(Debug)]
+struct Printed {
+    /// The [`Project`] the command got, as JSON.
+    input: Vec<u8>,
+    profile: ProfileDefinition,
+    next: OnceCell<Box<Self>>,
+}
+
+impl Clone for CommandProvider {
+    /// Clones start without the profiles printed by the command.
+    fn clone(&self) -> Self {
+        Self::new(self.command.clone())
+    }
+}
+
+impl CommandProvider {
+    pub fn new(command: Vec<String>) -> Self {
+        Self {
+            command,
+            #[cfg(feature = "native")]
+            printed: OnceCell::new(),
+        }
+    }
+
+    /// The program and its arguments.
+    pub fn command(&self) -> &[String] {
+        &self.command
+    }
+
+    /// The profile printed for `project`, running the command if it did not
+    /// run for the same project before.
+    #[cfg(feature = "native")]
+    fn profile(&self, project: &Project) -> Result<&ProfileDefinition> {
+        let input = serde_json::to_vec(project).map_err(Error::serialize("project"))?;
+
+        let mut cell = &self.printed;
+        while let Some(printed) = cell.get() {
+            if printed.input == input {
+                return Ok(&printed.profile);
+            }
+            cell = &printed.next;
+        }
+
+        let profile = self.run(&input)?;
+        Ok(&cell
+            .get_or_init(|| {
+                Box::new(Printed {
+                    input,
+                    profile,
+                    next: OnceCell::new(),
+                })
+            })
+            .profile)
+    }
+
+    #[cfg(feature = "native")]
+    fn run(&self, input: &[u8]) -> Result<ProfileDefinition> {
+        let (program, args) = self.command.split_first().ok_or_else(|| Error::Provider {
+            command: String::new(),
+            message: "no command given".to_owned(),
+        })?;
+        let command = self.command.join(" ");
+
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(Error::io("run profile provider", Path::new(program)))?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(input)
+                .map_err(Error::io("write to profile provider", Path::new(program)))?;
+        }
+
+        let output = child
+            .wait_with_output()
+            .map_err(Error::io("run profile provider", Path::new(program)))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(Error::Provider {
+                command,
+                message: stderr.lines().last().unwrap_or_default().trim().to_owned(),
+            });
+        }
+
+        let profile: ProfileDefinition = toml::from_str(&String::from_utf8_lossy(&output.stdout))
+            .map_err(Error::parse(format!(
+            "output of profile provider {command}"
+        )))?;
+        if profile.extends.is_some() {
+            return Err(Error::Provider {
+                command,
+                message: "the printed profile must not extend another one".to_owned(),
+            });
+        }
+        Ok(profile)
+    }
+}
+
+#[cfg(feature = "native")]
+impl ProfileProvider for CommandProvider {
+    /// Runs the command once per project, later calls for the same project
+    /// reuse the profile it printed. The comments of the groups name the
+    /// command.
+    fn config<'a>(&'a self, all_lints: &'a AllLints<'a>, project: &Project) -> Result<Config<'a>> {
+        let profile = self.profile(project)?;
+
+        let mut config = profile.to_config(all_lints, project.rust_version)?;
+        for group in &mut config.groups {
+            group.comment = Some(format!("{}, from {}", group.name(), self.command.join(" ")));
+        }
+        Ok(config)
+    }
+}
+
+impl ProfileProvider for RuleDefinition {
+    /// The levels of the rule if its condition matches, nothing otherwise.
+    /// Fails for unknown lints.
+    fn config<'a>(&'a self, all_lints: &'a AllLints<'a>, project: &Project) -> Result<Config<'a>> {
+        if !self.when.matches(project) {
+            return Ok(Config { groups: Vec::new() });
+        }
+
+        let settings = [
+            (&self.allow, LintLevel::Allow),
+            (&self.warn, LintLevel::Warn),
+            (&self.deny, LintLevel::Deny),
+        ]
+        .into_iter()
+        .flat_map(|(lints, level)| lints.iter().map(move |lint| (lint, level)))
+        .map(|(lint, level)| {
+            let lint = all_lints
+                .get(LintId(lint))
+                .ok_or_else(|| Error::UnknownLint {
+                    lint: lint.clone(),
+                    group: None,
+                })?;
+            Ok(Setting::Single(SingleLintConfig {
+                lint: lint.id,
+                priority: PrioritySetting::Unspecified,
+                level,
+                description: None,
+                reason: None,
+                expires: None,
+                version: lint.version,
+            }))
+        })
+        .collect::<Result<_>>()?;
+
+        Ok(Config {
+            groups: vec![ConfigGroup {
+                tool: CLIPPY,
+                comment: Some(
+                    self.comment
+                        .clone()
+                        .unwrap_or_else(|| format!("when {}", self.when)),
+                ),
+                settings,
+            }],
+        })
+    }
+}
+
+#[cfg(test)]
+#[cfg(all(feature = "native", unix))]
+mod tests {
+    use super::*;
+    use crate::source::Response;
+
+    const LINTS: &str = r#"[{"id": "print_stderr", "group": "restriction", "level": "allow", "version": "1.50.0"}]"#;
+
+    fn level(config: &Config<'_>, lint: &str) -> Option<LintLevel> {
+        config
+            .groups
+            .iter()
+            .flat_map(|group| &group.settings)
+            .find_map(|setting| match *setting {
+                Setting::Single(ref single) if single.lint.0 == lint => Some(single.level),
+                _ => None,
+            })
+    }
+
+    fn member(name: &str) -> Project {
+        Project {
+            member: Some(name.to_owned()),
+            ..Project::default()
+        }
+    }
+
+    #[test]
+    fn command_runs_for_each_project() {
+        let response = Response::parse(LINTS).expect("the lint data is valid");
+        let all_lints = AllLints::from_response(&response);
+        let provider = CommandProvider::new(
+            [
+                "sh",
+                "-c",
+                r#"if grep -q '"member":"a"'; then level=deny; else level=warn; fi
+                echo "clippy = [{ lint = \"print_stderr\", level = \"$level\" }]""#,
+            ]
+            .map(str::to_owned)
+            .to_vec(),
+        );
+
+        for _ in 0_u8..2 {
+            let a = provider
+                .config(&all_lints, &member("a"))
+                .expect("the provider succeeds");
+            assert_eq!(level(&a, "print_stderr"), Some(LintLevel::Deny));
+
+            let b = provider
+                .config(&all_lints, &member("b"))
+                .expect("the provider succeeds");
+            assert_eq!(level(&b, "print_stderr"), Some(LintLevel::Warn));
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use toml_edit::{value, ArrayOfTables, DocumentMut, InlineTable, Item, Table, Value};
+
+use crate::{
+    config::Config,
+    error::{Error, Result},
+    lint::{AllLints, Lint, LintGroup, LintLevel},
+    version::RustVersion,
+};
+
+/// The clippy lints of `groups` that `config` leaves at `allow`, the
+/// candidates for promoting to `warn`. Deprecated lints and lints introduced
+/// after `msrv` are left out.
+pub fn candidates<'a>(
+    config: &Config<'_>,
+    all_lints: &'a AllLints<'a>,
+    groups: &[LintGroup],
+    msrv: Option<RustVersion>,
+) -> Vec<&'a Lint<'a>> {
+    all_lints
+        .iter()
+        .filter(|lint| {
+            lint.group != LintGroup::Deprecated
+                && groups.contains(&lint.group)
+                && lint.is_available_in(msrv)
+                && config.clippy_level(lint).unwrap_or(lint.default_level) == LintLevel::Allow
+        })
+        .collect()
+}
+
+/// Sets `lints` to `warn` in the `clippy` list of the profile `profile` in
+/// the config file `config_file`, with `reason`. Everything else in the
+/// config file is left untouched.
+///
+/// Entries of the lints that are already in the list are updated, the other
+/// lints are appended. Single lints take precedence over the groups and
+/// overrides of the profile, so the lints end up at `warn` whatever else the
+/// profile or its parents say about them.
+pub fn promote(config_file: &str, profile: &str, lints: &[&str], reason: &str) -> Result<String> {
+    let mut document: DocumentMut = config_file.parse().map_err(Error::parse("config file"))?;
+
+    let definition = document
+        .get_mut("profile")
+        .and_then(|profiles| profiles.get_mut(profile))
+        .and_then(Item::as_table_like_mut)
+        .ok_or_else(|| Error::UnknownProfile(profile.to_owned()))?;
+    let clippy = definition
+        .entry("clippy")
+        .or_insert_with(|| Item::ArrayOfTables(ArrayOfTables::new()));
+
+    match *clippy {
+        Item::ArrayOfTables(ref mut entries) => {
+            for lint in lints {
+                let existing = entries
+                    .iter_mut()
+                    .find(|entry| entry.get("lint").and_then(Item::as_str) == Some(lint));
+                if let Some(entry) = existing {
+                    entry.insert("level", value("warn"));
+                    entry.insert("reason", value(reason));
+                } else {
+                    let mut entry = Table::new();
+                    entry.insert("lint", value(*lint));
+                    entry.insert("level", value("warn"));
+                    entry.insert("reason", value(reason));
+                    entries.push(entry);
+                }
+            }
+        }
+        Item::Value(Value::Array(ref mut entries)) => {
+            for lint in lints {
+                let existing = entries.iter_mut().find_map(|entry| {
+                    entry
+                        .as_inline_table_mut()
+                        .filter(|entry| entry.get("lint").and_then(Value::as_str) == Some(lint))
+                });
+                if let Some(entry) = existing {
+                    entry.insert("level", "warn".into());
+                    entry.insert("reason", reason.into());
+                } else {
+                    let mut entry = InlineTable::new();
+                    entry.insert("lint", (*lint).into());
+                    entry.insert("level", "warn".into());
+                    entry.insert("reason", reason.into());
+                    entries.push(entry);
+                }
+            }
+        }
+        _ => {
+            return Err(Error::InvalidConfigFile(format!(
+                "the clippy lints of profile {profile} are not a list"
+            )))
+        }
+    }
+
+    Ok(document.to_string())
+}
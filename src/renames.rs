@@ -0,0 +1,27 @@
+use std::collections::BTreeMap;
+
+use crate::error::{Error, Result};
+
+/// Clippy's mapping of renamed lints, as reported by clippy for lints under
+/// their old names.
+const BUNDLED_RENAMES: &str = include_str!("../data/renamed_lints.json");
+
+/// Old clippy lint names, without the `clippy::` prefix, mapped to their new
+/// names. New names keep their prefix, as some lints moved to rustc.
+#[derive(Debug)]
+pub struct RenamedLints(pub BTreeMap<String, String>);
+
+impl RenamedLints {
+    /// The mapping bundled at build time.
+    pub fn bundled() -> Result<Self> {
+        serde_json::from_str(BUNDLED_RENAMES)
+            .map(Self)
+            .map_err(Error::parse("bundled lint renames"))
+    }
+
+    /// The new name of the clippy lint `old`, e.g. `clippy::blocks_in_conditions`
+    /// for `block_in_if_condition_stmt` or `unexpected_cfgs` for rustc lints.
+    pub fn get(&self, old: &str) -> Option<&str> {
+        self.0.get(old).map(String::as_str)
+    }
+}
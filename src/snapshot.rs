@@ -0,0 +1,77 @@
+#[cfg(feature = "native")]
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+
+use crate::{
+    error::{Error, Result},
+    lint::AllLints,
+    source::Response,
+};
+
+/// The file name of the lint data snapshot, next to the manifest.
+pub const SNAPSHOT_NAME: &str = "lints-snapshot.json";
+
+/// A copy of the lint data kept in the repository, as an auditable record of
+/// the lints a configuration was generated against.
+///
+/// Unlike the [`Lockfile`](crate::Lockfile), it keeps `lints.json` as it was
+/// loaded, including the full documentation. [`Response::parse`] accepts
+/// snapshots in place of `lints.json`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Snapshot<'a> {
+    /// The newest release that introduced one of the lints, i.e. the clippy
+    /// version the lint data is from. `None` if the lint data has no
+    /// versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clippy_version: Option<String>,
+    /// Where the lint data came from, e.g. the URL it was downloaded from.
+    pub source: String,
+    /// The version of clippy-lints that took the snapshot.
+    pub generator: String,
+    /// The contents of `lints.json`.
+    #[serde(borrow)]
+    pub lints: &'a RawValue,
+}
+
+impl<'a> Snapshot<'a> {
+    /// A snapshot of `content`, either a `lints.json` or another snapshot.
+    /// Invalid entries are kept, they do not count for the clippy version.
+    pub fn new(content: &'a str, source: String) -> Result<Self> {
+        let lints = lints_json(content)?;
+        let response = Response::parse_permissive(lints)?;
+
+        Ok(Self {
+            clippy_version: AllLints::from_response(&response)
+                .iter()
+                .filter_map(|lint| lint.version)
+                .max()
+                .map(|version| version.to_string()),
+            source,
+            generator: format!("clippy-lints {}", env!("CARGO_PKG_VERSION")),
+            lints: serde_json::from_str(lints).map_err(Error::parse("lint data"))?,
+        })
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(Error::serialize("snapshot"))
+    }
+
+    #[cfg(feature = "native")]
+    pub fn write(&self, path: &Path) -> Result<()> {
+        fs::write(path, format!("{}\n", self.to_json()?)).map_err(Error::io("write snapshot", path))
+    }
+}
+
+/// The `lints.json` part of `content`, which is either a snapshot or
+/// `lints.json` itself. Snapshots are JSON objects, `lints.json` is an array.
+pub fn lints_json(content: &str) -> Result<&str> {
+    if !content.trim_start().starts_with('{') {
+        return Ok(content);
+    }
+
+    let snapshot: Snapshot<'_> =
+        serde_json::from_str(content).map_err(Error::parse("lint data snapshot"))?;
+    Ok(snapshot.lints.get())
+}
@@ -0,0 +1,502 @@
+use std::borrow::Cow;
+#[cfg(feature = "native")]
+use std::{
+    fs,
+    hash::{DefaultHasher, Hash as _, Hasher as _},
+    io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use clap::ValueEnum;
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+use serde_json::{value::RawValue, Value};
+
+#[cfg(feature = "native")]
+use crate::{
+    cache::Cache,
+    fetch::{self, Fetched, RetryPolicy, Timeouts, Validators},
+};
+use crate::{
+    error::{Error, Result},
+    help,
+    lint::{Applicability, LintGroup, LintLevel},
+    snapshot,
+    version::RustVersion,
+};
+
+/// A lint as described in clippy's `lints.json`. The strings borrow from the
+/// JSON text unless they contain escapes, like most documentation does.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct LintResponse<'a> {
+    #[serde(borrow)]
+    pub id: Cow<'a, str>,
+    pub group: LintGroup,
+    #[serde(rename = "level")]
+    pub default_level: LintLevel,
+    #[serde(borrow)]
+    pub version: Cow<'a, str>,
+    #[serde(default, borrow)]
+    pub docs: Cow<'a, str>,
+    /// How reliable the suggestions of the lint are, `None` for lint data
+    /// that does not report it.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_applicability",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub applicability: Option<Applicability>,
+}
+
+/// The applicability of a lint, either by itself or, in older lint data, in a
+/// table next to `is_multi_part_suggestion`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ApplicabilityValue {
+    Plain(Applicability),
+    Table {
+        applicability: Option<Applicability>,
+    },
+}
+
+fn deserialize_applicability<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Applicability>, D::Error> {
+    Ok(
+        match Option::<ApplicabilityValue>::deserialize(deserializer)? {
+            Some(ApplicabilityValue::Plain(applicability)) => Some(applicability),
+            Some(ApplicabilityValue::Table { applicability }) => applicability,
+            None => None,
+        },
+    )
+}
+
+/// An entry of the lint data that a permissive parse left out, because it
+/// does not have the expected shape.
+#[derive(Clone, Debug)]
+pub struct SkippedLint {
+    /// The position of the entry, counting from 0.
+    pub index: usize,
+    pub id: Option<String>,
+    /// What is wrong, naming the field if possible.
+    pub problem: String,
+}
+
+impl From<SkippedLint> for Error {
+    fn from(skipped: SkippedLint) -> Self {
+        Self::InvalidLintEntry {
+            index: skipped.index,
+            id: skipped.id,
+            problem: skipped.problem,
+        }
+    }
+}
+
+/// The contents of clippy's `lints.json`.
+#[derive(Debug, Default)]
+pub struct Response<'a> {
+    pub lints: Vec<LintResponse<'a>>,
+    /// The entries left out by a permissive parse, see
+    /// [`Response::parse_permissive`].
+    pub skipped: Vec<SkippedLint>,
+}
+
+/// Checks that a field of an entry deserializes to `T`.
+fn check<T: DeserializeOwned>(value: &Value) -> Result<(), serde_json::Error> {
+    T::deserialize(value).map(drop)
+}
+
+type FieldCheck = fn(&Value) -> Result<(), serde_json::Error>;
+
+/// The fields of an entry of `lints.json` that are read, whether they are
+/// required, and how to check them.
+const ENTRY_FIELDS: [(&str, bool, FieldCheck); 6] = [
+    ("id", true, check::<String>),
+    ("group", true, check::<LintGroup>),
+    ("level", true, check::<LintLevel>),
+    ("version", true, check::<String>),
+    ("docs", false, check::<String>),
+    ("applicability", false, check::<Option<ApplicabilityValue>>),
+];
+
+/// Why the entry at `index` of the lint data failed to deserialize with
+/// `err`, naming the offending field if there is one.
+fn invalid_entry(index: usize, entry: &Value, err: &serde_json::Error) -> SkippedLint {
+    let problem = ENTRY_FIELDS
+        .iter()
+        .find_map(|&(field, required, check)| match entry.get(field) {
+            None if required => Some(format!("missing field `{field}`")),
+            None => None,
+            Some(value) => check(value)
+                .err()
+                .map(|err| format!("field `{field}`: {err}")),
+        })
+        .unwrap_or_else(|| err.to_string());
+
+    SkippedLint {
+        index,
+        id: entry.get("id").and_then(Value::as_str).map(str::to_owned),
+        problem,
+    }
+}
+
+const LINTS_BASE_URL: &str = "https://rust-lang.github.io/rust-clippy";
+
+/// A release channel of clippy.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum Channel {
+    Stable,
+    Beta,
+    Master,
+}
+
+impl Channel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Stable => "stable",
+            Self::Beta => "beta",
+            Self::Master => "master",
+        }
+    }
+}
+
+/// The clippy release whose lint data is used.
+#[derive(Clone, Copy, Debug)]
+pub enum Release {
+    Channel(Channel),
+    Version(RustVersion),
+}
+
+impl Release {
+    pub fn name(self) -> String {
+        match self {
+            Self::Channel(channel) => channel.as_str().to_owned(),
+            Self::Version(version) => format!("rust-{version}"),
+        }
+    }
+
+    pub fn url(self) -> String {
+        format!("{LINTS_BASE_URL}/{}/lints.json", self.name())
+    }
+
+    /// Lint data of a released version never changes, so a cached copy can
+    /// be reused indefinitely.
+    pub fn is_immutable(self) -> bool {
+        match self {
+            Self::Channel(_) => false,
+            Self::Version(_) => true,
+        }
+    }
+}
+
+/// Controls how [`Response::load`] obtains the lint data.
+#[cfg(feature = "native")]
+#[derive(Clone, Debug)]
+pub struct FetchOptions {
+    pub release: Release,
+    /// How long a cached copy of mutable lint data is reused.
+    pub cache_ttl: Duration,
+    /// Download the lint data even if a fresh cached copy exists.
+    pub refresh: bool,
+    /// Download from this URL instead of the official one, e.g. from a
+    /// mirror. `{release}` is replaced by the name of the release.
+    pub url: Option<String>,
+    /// Download through this proxy. If unset, the proxy is taken from the
+    /// `HTTPS_PROXY` and `HTTP_PROXY` environment variables, honoring
+    /// `NO_PROXY`.
+    pub proxy: Option<String>,
+    pub retry: RetryPolicy,
+    pub timeouts: Timeouts,
+    /// Leave out invalid entries instead of failing, see
+    /// [`Response::parse_permissive`].
+    pub permissive: bool,
+}
+
+#[cfg(feature = "native")]
+impl FetchOptions {
+    /// The URL the lint data is downloaded from.
+    pub fn lints_url(&self) -> String {
+        match self.url {
+            Some(ref url) => url.replace("{release}", &self.release.name()),
+            None => self.release.url(),
+        }
+    }
+
+    /// Lint data from a custom URL is cached separately, keyed by a hash of
+    /// the URL.
+    fn cache_name(&self) -> String {
+        match self.url {
+            Some(_) => {
+                let mut hasher = DefaultHasher::new();
+                self.lints_url().hash(&mut hasher);
+                format!("custom-{:016x}", hasher.finish())
+            }
+            None => self.release.name(),
+        }
+    }
+
+    /// Custom URLs may serve different data at any time, so their cached
+    /// copies always expire.
+    fn ttl(&self) -> Duration {
+        if self.url.is_none() && self.release.is_immutable() {
+            Duration::MAX
+        } else {
+            self.cache_ttl
+        }
+    }
+}
+
+#[cfg(feature = "bundled")]
+const BUNDLED_LINTS: &str = include_str!("../data/lints.json");
+
+/// Moves `content` into `buffer`, for a [`Response`] to borrow from.
+#[cfg(feature = "native")]
+fn keep(buffer: &mut String, content: String) -> &str {
+    *buffer = content;
+    buffer
+}
+
+impl<'a> Response<'a> {
+    /// Loads the lint data from the cache, downloading it if the cached copy
+    /// is missing or stale. A stale cached copy is only downloaded again if
+    /// the server reports that it changed. The JSON text is kept in `buffer`,
+    /// which the response borrows from.
+    #[cfg(feature = "native")]
+    pub fn load(options: &FetchOptions, buffer: &'a mut String) -> Result<Self> {
+        let cache = Cache::new(&options.cache_name());
+
+        if !options.refresh {
+            if let Some(ref cache) = cache {
+                if let Some(content) = cache.read_fresh(options.ttl())? {
+                    return Self::parse_as(
+                        keep(buffer, content),
+                        "cached lint data",
+                        options.permissive,
+                    );
+                }
+            }
+        }
+
+        let cached = match cache {
+            Some(ref cache) => cache.read_fresh(Duration::MAX)?,
+            None => None,
+        };
+
+        let validators_cache = Cache::new(&format!("{}.validators", options.cache_name()));
+
+        let validators: Option<Validators> = match validators_cache {
+            Some(ref validators_cache) if cached.is_some() => validators_cache
+                .read_fresh(Duration::MAX)?
+                .and_then(|content| serde_json::from_str(&content).ok()),
+            _ => None,
+        };
+
+        match fetch::get(
+            &options.lints_url(),
+            options.proxy.as_deref(),
+            options.retry,
+            options.timeouts,
+            validators.as_ref(),
+        )? {
+            Fetched::NotModified => {
+                let (Some(content), Some(cache)) = (cached, cache) else {
+                    return Err(Error::InvalidLintData(
+                        "server reported unchanged lint data, but none is cached".to_owned(),
+                    ));
+                };
+                cache.touch()?;
+                Self::parse_as(
+                    keep(buffer, content),
+                    "cached lint data",
+                    options.permissive,
+                )
+            }
+            Fetched::Modified {
+                content,
+                validators,
+            } => {
+                let content = keep(buffer, content);
+                let response = Self::parse_as(content, "downloaded lint data", options.permissive)?;
+
+                if let Some(ref cache) = cache {
+                    cache.write(content)?;
+                }
+                if let Some(ref validators_cache) = validators_cache {
+                    validators_cache.write(
+                        &serde_json::to_string(&validators)
+                            .map_err(Error::serialize("validators"))?,
+                    )?;
+                }
+
+                Ok(response)
+            }
+        }
+    }
+
+    /// Loads the lint data from `source` without blocking the thread. The
+    /// JSON text is kept in `buffer`, which the response borrows from.
+    #[cfg(feature = "async")]
+    pub async fn load_async(
+        source: &(impl AsyncLintSource + Sync),
+        buffer: &'a mut String,
+    ) -> Result<Self> {
+        *buffer = source.fetch_json().await?;
+        Self::parse(buffer)
+    }
+
+    /// Parses the contents of a `lints.json` file, borrowing from it. Fails
+    /// on the first entry that does not have the expected shape, e.g. after
+    /// `lints.json` changed upstream.
+    ///
+    /// A [`Snapshot`](crate::Snapshot) is accepted in place of `lints.json`.
+    pub fn parse(content: &'a str) -> Result<Self> {
+        Self::parse_as(content, "lint data", false)
+    }
+
+    /// Like [`Response::parse`], but leaves out invalid entries instead of
+    /// failing. They are listed in [`Response::skipped`].
+    pub fn parse_permissive(content: &'a str) -> Result<Self> {
+        Self::parse_as(content, "lint data", true)
+    }
+
+    /// Like [`Response::parse`], for lint data that is not known to be UTF-8
+    /// yet, e.g. the body of a response in a browser.
+    pub fn parse_bytes(content: &'a [u8]) -> Result<Self> {
+        let content = std::str::from_utf8(content).map_err(Error::parse("lint data"))?;
+        Self::parse(content)
+    }
+
+    /// Like [`Response::parse`], naming the lint data `what` in errors.
+    fn parse_as(content: &'a str, what: &str, permissive: bool) -> Result<Self> {
+        let entries: Vec<&'a RawValue> =
+            serde_json::from_str(snapshot::lints_json(content)?).map_err(Error::parse(what))?;
+
+        let mut response = Self {
+            lints: Vec::with_capacity(entries.len()),
+            skipped: Vec::new(),
+        };
+
+        for (index, entry) in entries.into_iter().enumerate() {
+            let err = match serde_json::from_str(entry.get()) {
+                Ok(lint) => {
+                    response.lints.push(lint);
+                    continue;
+                }
+                Err(err) => err,
+            };
+
+            let entry: Value = serde_json::from_str(entry.get()).map_err(Error::parse(what))?;
+            let skipped = invalid_entry(index, &entry, &err);
+            if !permissive {
+                return Err(Error::Parse {
+                    what: what.to_owned(),
+                    source: Box::new(Error::from(skipped)),
+                });
+            }
+            response.skipped.push(skipped);
+        }
+
+        Ok(response)
+    }
+}
+
+impl Response<'static> {
+    /// Parses the output of `clippy-driver -W help`. It lacks the versions
+    /// that introduced the lints and their full documentation, only a
+    /// one-line description is available.
+    pub fn from_lint_help(output: &str) -> Result<Self> {
+        help::parse(output)
+    }
+
+    /// Reads the lint list of the installed clippy by running
+    /// `clippy-driver -W help`.
+    #[cfg(feature = "native")]
+    pub fn from_installed_clippy() -> Result<Self> {
+        help::installed()
+    }
+
+    /// Returns the lint data bundled at build time.
+    #[cfg(feature = "bundled")]
+    pub fn bundled() -> Result<Self> {
+        Self::parse_as(BUNDLED_LINTS, "bundled lint data", false)
+    }
+}
+
+/// Where the lint data comes from. Implement it to load the lint data in
+/// other ways, e.g. with another HTTP client, from test fixtures or from an
+/// internal artifact store.
+pub trait LintSource {
+    /// Loads the lint data. JSON text is kept in `buffer`, which the response
+    /// may borrow from.
+    fn fetch<'a>(&self, buffer: &'a mut String) -> Result<Response<'a>>;
+}
+
+/// Downloads the lint data with ureq and caches it, see [`Response::load`].
+#[cfg(feature = "native")]
+impl LintSource for FetchOptions {
+    fn fetch<'a>(&self, buffer: &'a mut String) -> Result<Response<'a>> {
+        Response::load(self, buffer)
+    }
+}
+
+/// A `lints.json` file.
+#[cfg(feature = "native")]
+#[derive(Clone, Debug)]
+pub struct FileSource {
+    /// `-` for stdin.
+    pub path: PathBuf,
+    /// Leave out invalid entries instead of failing, see
+    /// [`Response::parse_permissive`].
+    pub permissive: bool,
+}
+
+#[cfg(feature = "native")]
+impl LintSource for FileSource {
+    fn fetch<'a>(&self, buffer: &'a mut String) -> Result<Response<'a>> {
+        *buffer = if self.path == Path::new("-") {
+            io::read_to_string(io::stdin())
+                .map_err(Error::io("read lint data from", Path::new("stdin")))?
+        } else {
+            fs::read_to_string(&self.path).map_err(Error::io("read lint data", &self.path))?
+        };
+        Response::parse_as(buffer, "lint data", self.permissive)
+    }
+}
+
+/// The lint data bundled at build time, see [`Response::bundled`].
+#[cfg(feature = "bundled")]
+#[derive(Clone, Copy, Debug)]
+pub struct BundledSource;
+
+#[cfg(feature = "bundled")]
+impl LintSource for BundledSource {
+    fn fetch<'a>(&self, _buffer: &'a mut String) -> Result<Response<'a>> {
+        Response::bundled()
+    }
+}
+
+/// The lint list of the installed clippy, see
+/// [`Response::from_installed_clippy`].
+#[cfg(feature = "native")]
+#[derive(Clone, Copy, Debug)]
+pub struct InstalledClippySource;
+
+#[cfg(feature = "native")]
+impl LintSource for InstalledClippySource {
+    fn fetch<'a>(&self, _buffer: &'a mut String) -> Result<Response<'a>> {
+        Response::from_installed_clippy()
+    }
+}
+
+/// The async counterpart of [`LintSource`], for services that must not block
+/// a runtime thread.
+///
+/// The crate does not depend on an async HTTP client or runtime. Implement it
+/// with the client of the service, e.g. by downloading
+/// [`FetchOptions::lints_url`], and load the lint data with
+/// [`Response::load_async`]. Generating the configuration from it does no I/O.
+#[cfg(feature = "async")]
+pub trait AsyncLintSource {
+    /// Loads the JSON text of the lint data.
+    fn fetch_json(&self) -> impl std::future::Future<Output = Result<String>> + Send;
+}
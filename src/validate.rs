@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+
+use clap::ValueEnum as _;
+
+use crate::lint::{AllLints, LintGroup, LintId};
+
+/// Tables whose entries are validated.
+const CLIPPY_TABLES: [&str; 2] = ["lints.clippy", "workspace.lints.clippy"];
+
+/// A problem with an entry of a clippy lints table.
+#[derive(Debug)]
+pub struct Problem {
+    /// The line number in the manifest, starting at 1.
+    pub line: usize,
+    pub message: String,
+}
+
+/// The group a comment like `# pedantic overrides` says the entries below it
+/// belong to.
+fn comment_group(comment: &str) -> Option<LintGroup> {
+    let word = comment.split_whitespace().next()?;
+    LintGroup::from_str(word, false).ok()
+}
+
+/// Checks the clippy lints tables of `manifest` against the lint data: for
+/// unknown and deprecated lints, lints below a comment naming a group they
+/// are not part of, and keys that appear more than once.
+///
+/// The manifest is scanned line by line instead of parsed, so duplicate keys
+/// can be found. Entries are expected on lines of their own, as in the
+/// generated tables.
+pub fn validate(manifest: &str, all_lints: &AllLints<'_>) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let mut in_table = false;
+    let mut group = None;
+    let mut seen: HashMap<String, usize> = HashMap::new();
+
+    for (index, text) in manifest.lines().enumerate() {
+        let line_number = index.saturating_add(1);
+        let text = text.trim();
+        let mut problem = |message: String| {
+            problems.push(Problem {
+                line: line_number,
+                message,
+            });
+        };
+
+        if let Some(header) = text.strip_prefix('[') {
+            let name = header
+                .trim_start_matches('[')
+                .split(']')
+                .next()
+                .unwrap_or_default();
+            in_table = CLIPPY_TABLES.contains(&name.trim());
+            group = None;
+            seen.clear();
+            continue;
+        }
+        if !in_table {
+            continue;
+        }
+        if text.is_empty() {
+            group = None;
+            continue;
+        }
+        if let Some(comment) = text.strip_prefix('#') {
+            group = comment_group(comment);
+            continue;
+        }
+
+        let Some((key, _)) = text.split_once('=') else {
+            continue;
+        };
+        let key = key.trim().trim_matches('"');
+
+        match seen.get(key) {
+            Some(&first) => problem(format!(
+                "duplicate key {key}, first defined on line {first}"
+            )),
+            None => {
+                seen.insert(key.to_owned(), line_number);
+            }
+        }
+
+        if key == "all" || LintGroup::from_str(key, false).is_ok() {
+            continue;
+        }
+
+        let Some(lint) = all_lints.get(LintId(key)) else {
+            problem(match all_lints.closest(key) {
+                Some(suggestion) => format!("unknown lint {key} (did you mean {suggestion}?)"),
+                None => format!("unknown lint {key}"),
+            });
+            continue;
+        };
+
+        if lint.group == LintGroup::Deprecated {
+            problem(match lint.deprecation_reason() {
+                Some(reason) => format!("deprecated lint {key}: {reason}"),
+                None => format!("deprecated lint {key}"),
+            });
+            continue;
+        }
+
+        if let Some(group) = group.as_ref().filter(|&group| *group != lint.group) {
+            problem(format!(
+                "lint {key} is listed below a comment for group {group}, but belongs to group {}",
+                lint.group
+            ));
+        }
+    }
+
+    problems
+}
@@ -0,0 +1,104 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Serialize, Serializer};
+
+use crate::error::{Error, Result};
+
+/// A Rust release, as used for the versioned clippy lint lists.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RustVersion {
+    major: u64,
+    minor: u64,
+    patch: u64,
+}
+
+impl RustVersion {
+    /// Parses the version of a lint in the lint data. Lints that predate
+    /// version tracking are marked like `pre 1.29.0` and are treated as
+    /// introduced in that version.
+    pub fn from_lint_data(version: &str) -> Option<Self> {
+        version.trim_start_matches("pre ").parse().ok()
+    }
+}
+
+impl FromStr for RustVersion {
+    type Err = Error;
+
+    /// Accepts both `1.77` and `1.77.0`.
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::InvalidVersion(s.to_owned());
+
+        let mut parts = s.trim().split('.');
+
+        let mut next = |required: bool| -> Result<u64> {
+            match parts.next() {
+                Some(part) => part.parse().map_err(|_err| invalid()),
+                None if required => Err(invalid()),
+                None => Ok(0),
+            }
+        };
+
+        let version = Self {
+            major: next(true)?,
+            minor: next(true)?,
+            patch: next(false)?,
+        };
+
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+
+        Ok(version)
+    }
+}
+
+impl fmt::Display for RustVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Serializes as a string like `1.77.0`.
+impl Serialize for RustVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(s: &str) -> RustVersion {
+        s.parse().expect("the version is valid")
+    }
+
+    #[test]
+    fn parses_with_and_without_patch() {
+        assert_eq!(version("1.77").to_string(), "1.77.0");
+        assert_eq!(version(" 1.77.2 ").to_string(), "1.77.2");
+        assert!(version("1.9.0") < version("1.10.0"));
+    }
+
+    #[test]
+    fn rejects_malformed_versions() {
+        for invalid in ["1", "1.x", "1.77.0.1", "", "v1.77"] {
+            let err = invalid
+                .parse::<RustVersion>()
+                .expect_err("the version is malformed");
+            assert_eq!(
+                err.to_string(),
+                Error::InvalidVersion(invalid.to_owned()).to_string()
+            );
+        }
+    }
+
+    #[test]
+    fn lint_data_versions_before_tracking() {
+        assert_eq!(
+            RustVersion::from_lint_data("pre 1.29.0"),
+            Some(version("1.29.0"))
+        );
+        assert_eq!(RustVersion::from_lint_data("none"), None);
+    }
+}
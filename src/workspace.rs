@@ -0,0 +1,179 @@
+use std::path::PathBuf;
+#[cfg(feature = "native")]
+use std::{fs, path::Path, process::Command};
+
+use serde::Deserialize;
+use toml::{Table, Value};
+
+#[cfg(feature = "native")]
+use crate::manifest;
+use crate::{
+    error::{Error, Result},
+    version::RustVersion,
+};
+
+/// A package of a workspace, as far as it matters for generating its lint
+/// tables.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Member {
+    pub name: String,
+    pub manifest_path: PathBuf,
+    pub edition: String,
+    /// `rust-version`, including one inherited from the workspace.
+    pub rust_version: Option<RustVersion>,
+}
+
+/// The parts of the output of `cargo metadata` that are read.
+#[derive(Deserialize)]
+struct Metadata {
+    packages: Vec<MetadataPackage>,
+    workspace_members: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct MetadataPackage {
+    id: String,
+    name: String,
+    manifest_path: PathBuf,
+    edition: String,
+    rust_version: Option<String>,
+}
+
+/// Reads the workspace members from the output of `cargo metadata
+/// --format-version 1`, ordered by manifest path.
+pub fn from_metadata(json: &str) -> Result<Vec<Member>> {
+    let metadata: Metadata =
+        serde_json::from_str(json).map_err(Error::parse("output of cargo metadata"))?;
+
+    let mut members = metadata
+        .packages
+        .into_iter()
+        .filter(|package| metadata.workspace_members.contains(&package.id))
+        .map(|package| {
+            Ok(Member {
+                rust_version: package
+                    .rust_version
+                    .as_deref()
+                    .map(str::parse)
+                    .transpose()
+                    .map_err(Error::parse(format!("rust-version of {}", package.name)))?,
+                name: package.name,
+                manifest_path: package.manifest_path,
+                edition: package.edition,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    members.sort_by(|a, b| a.manifest_path.cmp(&b.manifest_path));
+    Ok(members)
+}
+
+/// Describes the package of the manifest `content` at `manifest_path`, with
+/// the settings it inherits taken from the workspace root manifest `root`.
+/// `None` for virtual manifests.
+pub fn member(manifest_path: PathBuf, content: &str, root: &str) -> Result<Option<Member>> {
+    let manifest: Table = toml::from_str(content).map_err(Error::manifest)?;
+    let root: Table = toml::from_str(root).map_err(Error::manifest)?;
+
+    let Some(package) = manifest.get("package").and_then(Value::as_table) else {
+        return Ok(None);
+    };
+    let setting = |key: &str| match *package.get(key)? {
+        Value::String(ref value) => Some(value.as_str()),
+        Value::Table(ref value) if value.get("workspace") == Some(&Value::Boolean(true)) => root
+            .get("workspace")
+            .and_then(|workspace| workspace.get("package"))
+            .and_then(|package| package.get(key))
+            .and_then(Value::as_str),
+        _ => None,
+    };
+
+    let Some(name) = package.get("name").and_then(Value::as_str) else {
+        return Err(Error::InvalidManifest(format!(
+            "{} has no package name",
+            manifest_path.display()
+        )));
+    };
+
+    Ok(Some(Member {
+        name: name.to_owned(),
+        edition: setting("edition").unwrap_or("2015").to_owned(),
+        rust_version: setting("rust-version")
+            .map(str::parse)
+            .transpose()
+            .map_err(Error::parse(format!("rust-version of {name}")))?,
+        manifest_path,
+    }))
+}
+
+/// Runs `cargo metadata` for the workspace of the manifest at `root`.
+#[cfg(feature = "native")]
+pub fn metadata(root: &Path) -> Result<Vec<Member>> {
+    let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let output = Command::new(cargo)
+        .args(["metadata", "--no-deps", "--format-version", "1"])
+        .arg("--manifest-path")
+        .arg(root)
+        .output()
+        .map_err(|source| Error::Run {
+            program: "cargo metadata",
+            source,
+        })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::Command {
+            command: "cargo metadata",
+            message: stderr
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .trim_start_matches("error: ")
+                .to_owned(),
+        });
+    }
+
+    from_metadata(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Reads the members of the workspace root at `root` from their manifests,
+/// for when cargo cannot describe the workspace.
+///
+/// Only `*` as the last path component of the `workspace.members` patterns
+/// is supported as a wildcard. The root package counts as a member.
+#[cfg(feature = "native")]
+pub fn from_patterns(root: &Path) -> Result<Vec<Member>> {
+    let root_content = fs::read_to_string(root).map_err(Error::io("read manifest", root))?;
+    let dir = root.parent().unwrap_or_else(|| Path::new(""));
+
+    let mut manifests = vec![root.to_owned()];
+    for pattern in manifest::workspace_members(&root_content)? {
+        if let Some(pattern_dir) = pattern.strip_suffix("/*") {
+            let pattern_dir = dir.join(pattern_dir);
+            let entries =
+                fs::read_dir(&pattern_dir).map_err(Error::io("read directory", &pattern_dir))?;
+            for entry in entries {
+                let path = entry
+                    .map_err(Error::io("read directory", &pattern_dir))?
+                    .path()
+                    .join("Cargo.toml");
+                if path.is_file() {
+                    manifests.push(path);
+                }
+            }
+        } else if pattern.contains(['*', '?', '[']) {
+            return Err(Error::InvalidManifest(format!(
+                "unsupported workspace member pattern {pattern}"
+            )));
+        } else {
+            manifests.push(dir.join(pattern).join("Cargo.toml"));
+        }
+    }
+    manifests.sort();
+
+    let mut members = Vec::new();
+    for path in manifests {
+        let content = fs::read_to_string(&path).map_err(Error::io("read manifest", &path))?;
+        members.extend(member(path, &content, &root_content)?);
+    }
+    Ok(members)
+}